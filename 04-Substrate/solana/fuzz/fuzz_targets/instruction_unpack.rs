@@ -0,0 +1,15 @@
+//! 把任意字节作为指令数据解析和处理
+//!
+//! 运行：`cargo +nightly fuzz run instruction_unpack`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solana_spl_token::fuzz::{check_instruction_data, install_syscall_stubs};
+
+fuzz_target!(
+    init: install_syscall_stubs(),
+    |data: &[u8]| {
+        check_instruction_data(data);
+    }
+);
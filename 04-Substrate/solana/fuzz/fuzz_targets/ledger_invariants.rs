@@ -0,0 +1,28 @@
+//! 在进程内账本上执行随机的原生账本指令序列，并检查供应量、签名和冻结不变量
+//!
+//! 运行：`cargo +nightly fuzz run ledger_invariants`
+
+#![no_main]
+
+use libfuzzer_sys::{
+    arbitrary::{Arbitrary, Unstructured},
+    fuzz_target,
+};
+use solana_spl_token::fuzz::{install_syscall_stubs, FuzzLedger, LedgerOp};
+
+fuzz_target!(
+    init: install_syscall_stubs(),
+    |data: &[u8]| {
+        // 逐个解码操作直到数据用完：`Vec<LedgerOp>` 的默认解码大多只得到一两个操作，
+        // 无法构成先铸造、再冻结、再转账这样的序列
+        let mut input = Unstructured::new(data);
+        let mut ops = Vec::new();
+        while !input.is_empty() {
+            match LedgerOp::arbitrary(&mut input) {
+                Ok(op) => ops.push(op),
+                Err(_) => break,
+            }
+        }
+        FuzzLedger::new().run(&ops);
+    }
+);
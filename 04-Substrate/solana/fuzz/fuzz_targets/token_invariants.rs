@@ -0,0 +1,27 @@
+//! 在进程内执行随机的经 SPL Token 的指令序列，并检查供应量、签名、冻结和分红不变量
+//!
+//! 运行：`cargo +nightly fuzz run token_invariants`
+
+#![no_main]
+
+use libfuzzer_sys::{
+    arbitrary::{Arbitrary, Unstructured},
+    fuzz_target,
+};
+use solana_spl_token::fuzz::{install_syscall_stubs, FuzzToken, TokenOp};
+
+fuzz_target!(
+    init: install_syscall_stubs(),
+    |data: &[u8]| {
+        // 与 `ledger_invariants` 相同，逐个解码操作直到数据用完
+        let mut input = Unstructured::new(data);
+        let mut ops = Vec::new();
+        while !input.is_empty() {
+            match TokenOp::arbitrary(&mut input) {
+                Ok(op) => ops.push(op),
+                Err(_) => break,
+            }
+        }
+        FuzzToken::new().run(&ops);
+    }
+);
@@ -0,0 +1,437 @@
+//! 指令账户校验
+//!
+//! 每条指令对应一个 `*Accounts` 结构体，从 `AccountInfo` 切片按顺序解析账户，
+//! 并在进入处理逻辑之前统一校验签名、可写、所有者程序和地址等约束，
+//! 防止调用方用伪造的程序或账户替换真实账户。
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::{
+    config::MintConfigTransferAccounts, dividend::DividendTransferAccounts,
+    memo::find_memo_requirement_address, TokenError,
+};
+
+/// 校验调用的程序 ID 与本程序声明的 ID 一致
+pub fn check_program_account(program_id: &Pubkey) -> ProgramResult {
+    if program_id != &crate::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// 校验账户已签名
+pub fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        return Err(TokenError::MissingSignature.into());
+    }
+    Ok(())
+}
+
+/// 校验账户可写
+pub fn check_writable(account: &AccountInfo) -> ProgramResult {
+    if !account.is_writable {
+        return Err(TokenError::AccountNotWritable.into());
+    }
+    Ok(())
+}
+
+/// 校验账户的所有者程序
+pub fn check_owner(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if account.owner != owner {
+        return Err(TokenError::IncorrectAccountOwner.into());
+    }
+    Ok(())
+}
+
+/// 校验账户地址
+pub fn check_key(account: &AccountInfo, expected: &Pubkey, error: TokenError) -> ProgramResult {
+    if account.key != expected {
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// 校验系统程序
+pub fn check_system_program(account: &AccountInfo) -> ProgramResult {
+    check_key(
+        account,
+        &system_program::id(),
+        TokenError::IncorrectSystemProgram,
+    )
+}
+
+/// 校验 SPL Token 程序
+pub fn check_token_program(account: &AccountInfo) -> ProgramResult {
+    check_key(account, &spl_token::id(), TokenError::IncorrectTokenProgram)
+}
+
+/// 校验 SPL Token 或 Token-2022 程序
+pub fn check_any_token_program(account: &AccountInfo) -> ProgramResult {
+    if account.key != &spl_token::id() && account.key != &spl_token_2022::id() {
+        return Err(TokenError::IncorrectTokenProgram.into());
+    }
+    Ok(())
+}
+
+/// 校验关联代币账户程序
+pub fn check_associated_token_program(account: &AccountInfo) -> ProgramResult {
+    check_key(
+        account,
+        &spl_associated_token_account::id(),
+        TokenError::IncorrectAssociatedTokenProgram,
+    )
+}
+
+/// 校验租金 sysvar
+pub fn check_rent_sysvar(account: &AccountInfo) -> ProgramResult {
+    check_key(
+        account,
+        &sysvar::rent::id(),
+        TokenError::IncorrectRentSysvar,
+    )
+}
+
+/// 校验 instructions sysvar
+pub fn check_instructions_sysvar(account: &AccountInfo) -> ProgramResult {
+    check_key(
+        account,
+        &sysvar::instructions::id(),
+        TokenError::IncorrectInstructionsSysvar,
+    )
+}
+
+/// 校验由 SPL Token 程序持有的可写账户（铸造账户或代币账户）
+pub fn check_token_owned_writable(account: &AccountInfo) -> ProgramResult {
+    check_writable(account)?;
+    check_owner(account, &spl_token::id())
+}
+
+/// 校验账户由 SPL Token 或 Token-2022 程序持有
+pub fn check_any_token_owner(account: &AccountInfo) -> ProgramResult {
+    if account.owner != &spl_token::id() && account.owner != &spl_token_2022::id() {
+        return Err(TokenError::IncorrectAccountOwner.into());
+    }
+    Ok(())
+}
+
+/// 校验由指定代币程序持有的可写账户，`token_program` 需已通过 [`check_any_token_program`]
+pub fn check_owned_writable_by(
+    account: &AccountInfo,
+    token_program: &AccountInfo,
+) -> ProgramResult {
+    check_writable(account)?;
+    check_owner(account, token_program.key)
+}
+
+/// `InitializeMint` 指令的账户
+pub struct InitializeMintAccounts<'a, 'info> {
+    /// `[signer, writable]` 新的铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[signer, writable]` 付费账户
+    pub payer: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+    /// `[]` 租金 sysvar
+    pub rent: &'a AccountInfo<'info>,
+    /// `[writable]` 可选的铸造账户配置 PDA，创建不可转让代币时必须提供
+    pub config: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> InitializeMintAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            mint: next_account_info(accounts_iter)?,
+            payer: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+            rent: next_account_info(accounts_iter)?,
+            config: accounts_iter.next(),
+        };
+
+        check_signer(ctx.mint)?;
+        check_writable(ctx.mint)?;
+        check_owner(ctx.mint, &system_program::id())?;
+        check_signer(ctx.payer)?;
+        check_writable(ctx.payer)?;
+        check_system_program(ctx.system_program)?;
+        check_any_token_program(ctx.token_program)?;
+        check_rent_sysvar(ctx.rent)?;
+        if let Some(config) = ctx.config {
+            check_writable(config)?;
+        }
+
+        Ok(ctx)
+    }
+}
+
+/// `MintTokens` 指令的账户
+pub struct MintTokensAccounts<'a, 'info> {
+    /// `[writable]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 接收代币的账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[signer]` 铸造权限
+    pub mint_authority: &'a AccountInfo<'info>,
+    /// `[]` 付费账户
+    pub payer: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> MintTokensAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            mint: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            mint_authority: next_account_info(accounts_iter)?,
+            payer: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_any_token_program(ctx.token_program)?;
+        check_owned_writable_by(ctx.mint, ctx.token_program)?;
+        check_owned_writable_by(ctx.token_account, ctx.token_program)?;
+        check_signer(ctx.mint_authority)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `TransferTokens` 指令的账户
+pub struct TransferTokensAccounts<'a, 'info> {
+    /// `[writable]` 转出账户
+    pub from: &'a AccountInfo<'info>,
+    /// `[writable]` 转入账户
+    pub to: &'a AccountInfo<'info>,
+    /// `[signer]` 转出账户的所有者或委托人
+    pub authority: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户，`transfer_checked` 据此校验双方代币账户并读取精度
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` SPL Memo 程序，仅 `TransferWithMemo` 附带
+    pub memo_program: Option<&'a AccountInfo<'info>>,
+    /// `[]` 转入账户的备注要求 PDA，账户为空表示不要求备注
+    pub memo_requirement: &'a AccountInfo<'info>,
+    /// 铸造账户配置与双方的开盘记录，配置 PDA 为空时为 `None`
+    pub config: Option<MintConfigTransferAccounts<'a, 'info>>,
+    /// 分红池与双方的分红检查点，分红池为空时为 `None`
+    pub dividends: Option<DividendTransferAccounts<'a, 'info>>,
+}
+
+impl<'a, 'info> TransferTokensAccounts<'a, 'info> {
+    /// 解析并校验账户
+    ///
+    /// 铸造账户之后依次为 Memo 程序（仅 `with_memo` 时）、转入账户的备注要求 PDA、铸造账户
+    /// 配置 PDA 和分红池 PDA（均必需，按地址校验）、配置存在时的配置账户，以及分红池存在时
+    /// 双方的分红检查点。
+    pub fn parse(
+        accounts: &'a [AccountInfo<'info>],
+        with_memo: bool,
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let from = next_account_info(accounts_iter)?;
+        let to = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        let token_program = next_account_info(accounts_iter)?;
+        let mint = next_account_info(accounts_iter)?;
+
+        check_any_token_program(token_program)?;
+        check_owned_writable_by(from, token_program)?;
+        check_owned_writable_by(to, token_program)?;
+        check_signer(authority)?;
+        check_owner(mint, token_program.key)?;
+
+        let memo_program = if with_memo {
+            let memo_program = next_account_info(accounts_iter)?;
+            if *memo_program.key != spl_memo::id() {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            Some(memo_program)
+        } else {
+            None
+        };
+        let memo_requirement = next_account_info(accounts_iter)?;
+        check_key(
+            memo_requirement,
+            &find_memo_requirement_address(&crate::id(), to.key).0,
+            TokenError::InvalidProgramAddress,
+        )?;
+
+        let config = next_account_info(accounts_iter)?;
+        let dividend_pool = next_account_info(accounts_iter)?;
+        let config = MintConfigTransferAccounts::parse(config, accounts_iter, mint.key, from, to)?;
+        let dividends =
+            DividendTransferAccounts::parse(dividend_pool, accounts_iter, mint.key, from, to)?;
+
+        Ok(Self {
+            from,
+            to,
+            authority,
+            token_program,
+            mint,
+            memo_program,
+            memo_requirement,
+            config,
+            dividends,
+        })
+    }
+}
+
+/// `CreateTokenAccount` 指令的账户
+pub struct CreateTokenAccountAccounts<'a, 'info> {
+    /// `[signer, writable]` 付费账户
+    pub payer: &'a AccountInfo<'info>,
+    /// `[]` 钱包地址
+    pub wallet: &'a AccountInfo<'info>,
+    /// `[writable]` 关联代币账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+    /// `[]` 关联代币账户程序
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateTokenAccountAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            payer: next_account_info(accounts_iter)?,
+            wallet: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+            associated_token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.payer)?;
+        check_writable(ctx.payer)?;
+        check_any_token_program(ctx.token_program)?;
+        check_writable(ctx.token_account)?;
+        check_key(
+            ctx.token_account,
+            &get_associated_token_address_with_program_id(
+                ctx.wallet.key,
+                ctx.mint.key,
+                ctx.token_program.key,
+            ),
+            TokenError::InvalidProgramAddress,
+        )?;
+        check_owner(ctx.mint, ctx.token_program.key)?;
+        check_system_program(ctx.system_program)?;
+        check_associated_token_program(ctx.associated_token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `BurnTokens` 指令的账户
+pub struct BurnTokensAccounts<'a, 'info> {
+    /// `[writable]` 被销毁代币的账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[signer]` 代币账户的所有者或委托人
+    pub authority: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> BurnTokensAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            token_account: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_any_token_program(ctx.token_program)?;
+        check_owned_writable_by(ctx.token_account, ctx.token_program)?;
+        check_owned_writable_by(ctx.mint, ctx.token_program)?;
+        check_signer(ctx.authority)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `SetAuthority` 指令的账户
+pub struct SetAuthorityAccounts<'a, 'info> {
+    /// `[writable]` 铸造账户或代币账户
+    pub account: &'a AccountInfo<'info>,
+    /// `[signer]` 当前权限账户
+    pub current_authority: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetAuthorityAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            account: next_account_info(accounts_iter)?,
+            current_authority: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_any_token_program(ctx.token_program)?;
+        check_owned_writable_by(ctx.account, ctx.token_program)?;
+        check_signer(ctx.current_authority)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `FreezeAccount` 指令的账户
+pub struct FreezeAccountAccounts<'a, 'info> {
+    /// `[writable]` 被冻结的代币账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[signer]` 冻结权限
+    pub freeze_authority: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> FreezeAccountAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            token_account: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            freeze_authority: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_any_token_program(ctx.token_program)?;
+        check_owned_writable_by(ctx.token_account, ctx.token_program)?;
+        check_owner(ctx.mint, ctx.token_program.key)?;
+        check_signer(ctx.freeze_authority)?;
+
+        Ok(ctx)
+    }
+}
@@ -0,0 +1,9 @@
+//! idl：输出程序的 Anchor 兼容 JSON IDL
+//!
+//! IDL 由 `lib.rs` 中的指令注释和各模块的类型定义生成，参见 [`solana_spl_token::idl`]。
+//!
+//! 更新仓库中的 IDL：`cargo run --bin idl > idl/solana_spl_token.json`
+
+fn main() {
+    print!("{}", solana_spl_token::idl::Idl::generate().to_json());
+}
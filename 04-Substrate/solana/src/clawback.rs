@@ -0,0 +1,258 @@
+//! 回收（clawback）
+//!
+//! 受监管代币需要从被盗或受制裁的账户追回资金。回收权限在 `InitializeMint` 时写入铸造账户
+//! 配置，只有它可以调用 `Clawback` 把任意持有人的代币转到回收账户。可回收代币的冻结权限固定为
+//! 配置 PDA，回收时由它解冻和冻结持有人账户：
+//!
+//! - Token-2022 铸造账户启用 PermanentDelegate 扩展，永久委托为配置 PDA，可以直接转走任意账户
+//!   的代币；被冻结的账户先解冻，回收后恢复冻结。
+//! - SPL Token 没有永久委托，持有人需通过 [`approve_clawback_delegate`] 把配置 PDA 设为委托。
+//!   回收后源账户保持冻结；委托额度不足（例如持有人撤销了委托）时只冻结源账户，
+//!   回收数量记为 0，资金留在原账户中无法转出。
+//!
+//! 每次回收都会输出 `ClawedBack` 事件，记录回收权限、源账户、回收账户、数量和冻结状态。
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_option::COption,
+    pubkey::Pubkey,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    instruction::{freeze_account, thaw_account, transfer_checked},
+    state::{Account, Mint},
+};
+
+use crate::{
+    accounts::{
+        check_any_token_program, check_owned_writable_by, check_owner, check_program_account,
+        check_signer,
+    },
+    config::{find_mint_config_address, MintConfig, MINT_CONFIG_SEED},
+    events::TokenEvent,
+    state::ProgramAccount,
+    TokenError, TokenInstruction,
+};
+
+/// `Clawback` 指令的账户
+pub struct ClawbackAccounts<'a, 'info> {
+    /// `[signer]` 回收权限
+    pub authority: &'a AccountInfo<'info>,
+    /// `[writable]` 被回收的代币账户
+    pub source: &'a AccountInfo<'info>,
+    /// `[writable]` 接收回收代币的账户
+    pub destination: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户配置 PDA
+    pub config: &'a AccountInfo<'info>,
+    /// `[]` 代币程序（SPL Token 或 Token-2022）
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ClawbackAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            authority: next_account_info(accounts_iter)?,
+            source: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.authority)?;
+        check_any_token_program(ctx.token_program)?;
+        check_owned_writable_by(ctx.source, ctx.token_program)?;
+        check_owned_writable_by(ctx.destination, ctx.token_program)?;
+        check_owner(ctx.mint, ctx.token_program.key)?;
+        check_owner(ctx.config, program_id)?;
+        let (config, _) = find_mint_config_address(program_id, ctx.mint.key);
+        if config != *ctx.config.key {
+            return Err(TokenError::InvalidProgramAddress.into());
+        }
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `Clawback`：由回收权限把 `amount` 个代币从源账户转到回收账户
+pub fn process_clawback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ClawbackAccounts {
+        authority,
+        source,
+        destination,
+        mint,
+        config: config_account,
+        token_program,
+    } = ClawbackAccounts::parse(program_id, accounts)?;
+
+    if amount == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    let config = MintConfig::unpack_account(&config_account.data.borrow())?;
+    match config.clawback_authority {
+        None => return Err(TokenError::ClawbackNotEnabled.into()),
+        Some(clawback_authority) if clawback_authority != *authority.key => {
+            return Err(TokenError::InsufficientPrivileges.into())
+        }
+        Some(_) => {}
+    }
+
+    let decimals = StateWithExtensions::<Mint>::unpack(&mint.data.borrow())?
+        .base
+        .decimals;
+    let source_state = StateWithExtensions::<Account>::unpack(&source.data.borrow())?.base;
+    if source_state.mint != *mint.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+    let was_frozen = source_state.is_frozen();
+
+    // SPL Token 只能通过持有人授予的委托转出，额度不足时退回到冻结
+    let permanent_delegate = *token_program.key == spl_token_2022::id();
+    let delegated = source_state.delegate == COption::Some(*config_account.key)
+        && source_state.delegated_amount >= amount;
+    let recovered = if permanent_delegate || delegated {
+        amount
+    } else {
+        0
+    };
+
+    let signer_seeds: &[&[u8]] = &[MINT_CONFIG_SEED, mint.key.as_ref(), &[config.bump]];
+    let freeze_accounts = [source.clone(), mint.clone(), config_account.clone()];
+
+    if recovered > 0 {
+        if was_frozen {
+            invoke_signed(
+                &thaw_account(
+                    token_program.key,
+                    source.key,
+                    mint.key,
+                    config_account.key,
+                    &[],
+                )?,
+                &freeze_accounts,
+                &[signer_seeds],
+            )?;
+        }
+        invoke_signed(
+            &transfer_checked(
+                token_program.key,
+                source.key,
+                mint.key,
+                destination.key,
+                config_account.key,
+                &[],
+                recovered,
+                decimals,
+            )?,
+            &[
+                source.clone(),
+                mint.clone(),
+                destination.clone(),
+                config_account.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+    }
+
+    // SPL Token 的源账户回收后保持冻结，防止持有人撤销委托后继续转出
+    let frozen = was_frozen || !permanent_delegate;
+    if frozen && (recovered > 0 || !was_frozen) {
+        invoke_signed(
+            &freeze_account(
+                token_program.key,
+                source.key,
+                mint.key,
+                config_account.key,
+                &[],
+            )?,
+            &freeze_accounts,
+            &[signer_seeds],
+        )?;
+    }
+
+    TokenEvent::ClawedBack {
+        mint: *mint.key,
+        source: *source.key,
+        destination: *destination.key,
+        amount: recovered,
+        authority: *authority.key,
+        frozen,
+    }
+    .emit();
+
+    if recovered > 0 {
+        log_msg!(
+            "已回收 {} 个代币: {} -> {}",
+            recovered,
+            source.key,
+            destination.key
+        );
+    } else {
+        log_msg!("委托额度不足，已冻结账户: {}", source.key);
+    }
+
+    Ok(())
+}
+
+/// 构建 `Clawback` 指令
+pub fn clawback(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::Clawback { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    }
+}
+
+/// 构建 SPL Token 的 `Approve` 指令，把配置 PDA 设为 `token_account` 的无限额委托
+///
+/// 经典 SPL Token 铸造账户的持有人需要执行该指令后才能被回收。
+pub fn approve_clawback_delegate(
+    program_id: &Pubkey,
+    token_account: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    spl_token::instruction::approve(
+        &spl_token::id(),
+        token_account,
+        &config,
+        owner,
+        &[],
+        u64::MAX,
+    )
+}
@@ -0,0 +1,549 @@
+//! 铸造账户配置
+//!
+//! 每个铸造账户可以有一个配置 PDA，保存本程序在转账时额外执行的规则，由铸造权限维护。
+//!
+//! 每笔 `TransferTokens` 都必须在备注要求 PDA 之后附带铸造账户的配置 PDA，省略或地址不符时拒绝；
+//! 配置 PDA 为空表示该铸造账户没有额外规则。
+//!
+//! 不可转让（灵魂绑定）代币只能使用 Token-2022 创建：铸造账户启用 NonTransferable 扩展，
+//! 由代币程序本身禁止任何转账，配置中同时写入 `non_transferable`，`TransferTokens` 一律拒绝。
+//! SPL Token 铸造账户无法阻止绕过本程序的直接转账，`InitializeMint` 拒绝为其设置该标记。
+//!
+//! 回收权限同样在 `InitializeMint` 时写入，之后不可修改，回收流程见 [`crate::clawback`]。
+//! 计息代币的利率和变更历史也保存在配置中，见 [`crate::interest`]。
+//! 转账钩子程序由铸造权限通过 `SetTransferHook` 设置，调用方式见 [`crate::hook`]。
+//!
+//! 开盘防护（launch guard）：
+//!
+//! - 开盘 slot 之前拒绝通过本程序转账；
+//! - 开盘后的前 `launch_slots` 个 slot 内，同一钱包两次转出至少间隔 `cooldown_slots`（转入不受冷却限制），
+//!   每个钱包累计转入不超过 `max_amount_per_wallet`，且不允许 0 数量的转账；
+//! - 开盘窗口结束后防护自动失效，无需再次修改配置。
+//!
+//! 冷却与额度按代币账户所有者（钱包）记录在钱包记录 PDA 中，首次转账时由转账签名者付费创建。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_option::COption,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::Sysvar,
+};
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    state::{Account as SplAccount, Mint},
+};
+
+use crate::{
+    accounts::{
+        check_any_token_owner, check_key, check_owner, check_program_account, check_signer,
+        check_system_program, check_writable,
+    },
+    create_pda_account,
+    events::TokenEvent,
+    hook::TransferHookAccounts,
+    instruction,
+    interest::InterestRate,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    TokenError, TokenInstruction,
+};
+
+/// 铸造账户配置 PDA 种子前缀
+pub const MINT_CONFIG_SEED: &[u8] = b"mint_config";
+/// 开盘钱包记录 PDA 种子前缀
+pub const LAUNCH_WALLET_SEED: &[u8] = b"launch_wallet";
+
+/// 开盘防护参数
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaunchGuard {
+    /// 开盘 slot，之前拒绝转账
+    pub trading_start_slot: u64,
+    /// 开盘窗口长度（slot），窗口结束后防护失效
+    pub launch_slots: u64,
+    /// 窗口内同一钱包两次转出的最小间隔（slot），转入不受限制
+    pub cooldown_slots: u64,
+    /// 窗口内每个钱包累计转入的上限
+    pub max_amount_per_wallet: u64,
+}
+
+impl LaunchGuard {
+    /// 序列化后的长度
+    pub const LEN: usize = 8 * 4;
+
+    /// `slot` 是否仍在开盘窗口内
+    pub fn is_active(&self, slot: u64) -> bool {
+        slot < self.trading_start_slot.saturating_add(self.launch_slots)
+    }
+}
+
+/// 铸造账户配置
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct MintConfig {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 铸造账户
+    pub mint: Pubkey,
+    /// 是否为不可转让代币
+    pub non_transferable: bool,
+    /// 开盘防护，`None` 表示未启用
+    pub launch_guard: Option<LaunchGuard>,
+    /// 回收权限，`None` 表示不可回收
+    pub clawback_authority: Option<Pubkey>,
+    /// 计息参数，`None` 表示不计息
+    pub interest_rate: Option<InterestRate>,
+    /// 转账钩子程序，`None` 表示不调用钩子
+    pub transfer_hook_program: Option<Pubkey>,
+    /// 配置 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for MintConfig {
+    const ACCOUNT_TYPE: AccountType = AccountType::MintConfig;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN
+        + 1
+        + 32
+        + 1
+        + (1 + LaunchGuard::LEN)
+        + (1 + 32)
+        + (1 + InterestRate::LEN)
+        + (1 + 32)
+        + 1;
+}
+
+/// 开盘窗口内钱包的转账记录
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct LaunchWallet {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 铸造账户
+    pub mint: Pubkey,
+    /// 钱包地址（代币账户所有者）
+    pub wallet: Pubkey,
+    /// 最近一次转出所在的 slot
+    pub last_transfer_slot: u64,
+    /// 窗口内累计转入的数量
+    pub received: u64,
+    /// 记录 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for LaunchWallet {
+    const ACCOUNT_TYPE: AccountType = AccountType::LaunchWallet;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 + 32 + 8 + 8 + 1;
+}
+
+/// 计算铸造账户配置 PDA 地址
+pub fn find_mint_config_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_CONFIG_SEED, mint.as_ref()], program_id)
+}
+
+/// 计算开盘钱包记录 PDA 地址
+pub fn find_launch_wallet_address(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    wallet: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LAUNCH_WALLET_SEED, mint.as_ref(), wallet.as_ref()],
+        program_id,
+    )
+}
+
+/// `SetLaunchGuard` 和 `SetTransferHook` 指令的账户
+pub struct MintConfigAuthorityAccounts<'a, 'info> {
+    /// `[signer, writable]` 铸造权限（付费账户）
+    pub authority: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户（SPL Token 或 Token-2022）
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户配置 PDA，不存在时创建
+    pub config: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> MintConfigAuthorityAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            authority: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.authority)?;
+        check_writable(ctx.authority)?;
+        check_any_token_owner(ctx.mint)?;
+        check_writable(ctx.config)?;
+        check_system_program(ctx.system_program)?;
+
+        Ok(ctx)
+    }
+
+    /// 校验签名者为铸造权限并读取配置，配置不存在时由签名者付费创建
+    fn load_config(&self, program_id: &Pubkey) -> Result<MintConfig, ProgramError> {
+        let mint_state = StateWithExtensions::<Mint>::unpack(&self.mint.data.borrow())?.base;
+        if mint_state.mint_authority != COption::Some(*self.authority.key) {
+            return Err(TokenError::InsufficientPrivileges.into());
+        }
+        load_or_create_config(
+            program_id,
+            self.authority,
+            self.mint,
+            self.config,
+            self.system_program,
+        )
+    }
+}
+
+/// `TransferTokens` 的铸造账户配置账户
+pub struct MintConfigTransferAccounts<'a, 'info> {
+    /// `[]` 铸造账户配置 PDA
+    pub config: &'a AccountInfo<'info>,
+    /// `[writable]` 转出钱包的开盘记录 PDA
+    pub from_wallet: &'a AccountInfo<'info>,
+    /// `[writable]` 转入钱包的开盘记录 PDA
+    pub to_wallet: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// 配置中设置了转账钩子时附带的钩子账户
+    pub hook: Option<TransferHookAccounts<'a, 'info>>,
+}
+
+impl<'a, 'info> MintConfigTransferAccounts<'a, 'info> {
+    /// 校验必需的配置 PDA，配置为空时返回 `None`，否则从剩余账户中解析双方开盘记录、系统程序和钩子账户
+    ///
+    /// 配置存在时开盘记录同样必需，地址按 `from`、`to` 的所有者校验，不论开盘防护是否生效。
+    pub fn parse(
+        config: &'a AccountInfo<'info>,
+        accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+        mint: &Pubkey,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+    ) -> Result<Option<Self>, ProgramError> {
+        check_key(
+            config,
+            &find_mint_config_address(&crate::id(), mint).0,
+            TokenError::InvalidProgramAddress,
+        )?;
+        if config.data_is_empty() {
+            return Ok(None);
+        }
+        let from_wallet = next_account_info(accounts_iter)?;
+        let to_wallet = next_account_info(accounts_iter)?;
+        let system_program = next_account_info(accounts_iter)?;
+
+        check_owner(config, &crate::id())?;
+        check_writable(from_wallet)?;
+        check_writable(to_wallet)?;
+        check_system_program(system_program)?;
+        for (token_account, record) in [(from, from_wallet), (to, to_wallet)] {
+            let wallet = StateWithExtensions::<SplAccount>::unpack(&token_account.data.borrow())?
+                .base
+                .owner;
+            check_key(
+                record,
+                &find_launch_wallet_address(&crate::id(), mint, &wallet).0,
+                TokenError::InvalidProgramAddress,
+            )?;
+        }
+
+        let hook = match MintConfig::unpack_account(&config.data.borrow())?.transfer_hook_program {
+            Some(hook_program) => Some(TransferHookAccounts::parse(
+                accounts_iter,
+                &hook_program,
+                mint,
+            )?),
+            None => None,
+        };
+
+        Ok(Some(Self {
+            config,
+            from_wallet,
+            to_wallet,
+            system_program,
+            hook,
+        }))
+    }
+
+    /// 转账前执行配置中的规则，开盘窗口内更新双方钱包记录
+    ///
+    /// 冷却只约束转出方：转入不会刷新接收方的冷却，第三方无法通过向其转账锁住其钱包。
+    /// 开盘窗口内拒绝 0 数量的转账，避免不转移任何代币就为他人创建或刷新钱包记录。
+    pub fn check_transfer(
+        &self,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        amount: u64,
+    ) -> ProgramResult {
+        let config = MintConfig::unpack_account(&self.config.data.borrow())?;
+        if !config.is_initialized {
+            return Err(TokenError::AccountNotInitialized.into());
+        }
+        if config.non_transferable {
+            return Err(TokenError::NonTransferable.into());
+        }
+        let Some(guard) = config.launch_guard else {
+            return Ok(());
+        };
+
+        let slot = Clock::get()?.slot;
+        if slot < guard.trading_start_slot {
+            return Err(TokenError::TradingNotStarted.into());
+        }
+        if !guard.is_active(slot) {
+            return Ok(());
+        }
+        if amount == 0 {
+            return Err(TokenError::InvalidAmount.into());
+        }
+
+        let from_owner = StateWithExtensions::<SplAccount>::unpack(&from.data.borrow())?
+            .base
+            .owner;
+        let to_owner = StateWithExtensions::<SplAccount>::unpack(&to.data.borrow())?
+            .base
+            .owner;
+        let sender = self.load_wallet(&config.mint, self.from_wallet, &from_owner, authority)?;
+        let receiver = self.load_wallet(&config.mint, self.to_wallet, &to_owner, authority)?;
+        if sender.last_transfer_slot != 0
+            && slot
+                < sender
+                    .last_transfer_slot
+                    .saturating_add(guard.cooldown_slots)
+        {
+            return Err(TokenError::TransferCooldown.into());
+        }
+        let received = receiver
+            .received
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+        if received > guard.max_amount_per_wallet {
+            return Err(TokenError::LaunchLimitExceeded.into());
+        }
+
+        // 转出与转入可能是同一钱包，先写转出方，再在其基础上更新转入方
+        let mut sender = sender;
+        sender.last_transfer_slot = slot;
+        sender.pack_account(&mut self.from_wallet.data.borrow_mut())?;
+        let mut receiver = LaunchWallet::unpack_account(&self.to_wallet.data.borrow())?;
+        receiver.received = receiver
+            .received
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+        receiver.pack_account(&mut self.to_wallet.data.borrow_mut())?;
+
+        Ok(())
+    }
+
+    /// 读取钱包记录，不存在时由转账签名者付费创建
+    fn load_wallet(
+        &self,
+        mint: &Pubkey,
+        record_account: &AccountInfo<'info>,
+        wallet: &Pubkey,
+        payer: &AccountInfo<'info>,
+    ) -> Result<LaunchWallet, ProgramError> {
+        let (address, bump) = find_launch_wallet_address(&crate::id(), mint, wallet);
+        if address != *record_account.key {
+            return Err(TokenError::InvalidProgramAddress.into());
+        }
+        if !record_account.data_is_empty() {
+            return LaunchWallet::unpack_account(&record_account.data.borrow());
+        }
+
+        check_writable(payer)?;
+        create_pda_account(
+            payer,
+            record_account,
+            self.system_program,
+            &crate::id(),
+            LaunchWallet::LEN,
+            &[LAUNCH_WALLET_SEED, mint.as_ref(), wallet.as_ref(), &[bump]],
+        )?;
+        let record = LaunchWallet {
+            is_initialized: true,
+            mint: *mint,
+            wallet: *wallet,
+            last_transfer_slot: 0,
+            received: 0,
+            bump,
+        };
+        record.pack_account(&mut record_account.data.borrow_mut())?;
+        Ok(record)
+    }
+}
+
+/// 处理开盘防护设置
+pub fn process_set_launch_guard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    guard: Option<LaunchGuard>,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ctx = MintConfigAuthorityAccounts::parse(accounts)?;
+    let mut config = ctx.load_config(program_id)?;
+    config.launch_guard = guard;
+    config.pack_account(&mut ctx.config.data.borrow_mut())?;
+
+    TokenEvent::LaunchGuardSet {
+        mint: *ctx.mint.key,
+        guard,
+    }
+    .emit();
+
+    match guard {
+        Some(guard) => log_msg!(
+            "开盘防护: 开盘 slot {}，窗口 {} 个 slot",
+            guard.trading_start_slot,
+            guard.launch_slots
+        ),
+        None => log_msg!("开盘防护已关闭"),
+    }
+
+    Ok(())
+}
+
+/// 处理转账钩子设置
+pub fn process_set_transfer_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hook_program: Option<Pubkey>,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ctx = MintConfigAuthorityAccounts::parse(accounts)?;
+    if hook_program == Some(*program_id) {
+        return Err(TokenError::TransferHookMismatch.into());
+    }
+    let mut config = ctx.load_config(program_id)?;
+    config.transfer_hook_program = hook_program;
+    config.pack_account(&mut ctx.config.data.borrow_mut())?;
+
+    TokenEvent::TransferHookSet {
+        mint: *ctx.mint.key,
+        hook_program,
+    }
+    .emit();
+
+    match hook_program {
+        Some(hook_program) => log_msg!("转账钩子: {}", hook_program),
+        None => log_msg!("转账钩子已移除"),
+    }
+
+    Ok(())
+}
+
+/// 构建 `SetLaunchGuard` 指令，`authority` 需为铸造权限
+pub fn set_launch_guard(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    guard: Option<LaunchGuard>,
+) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::SetLaunchGuard { guard }.pack(),
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// 构建 `SetTransferHook` 指令，`authority` 需为铸造权限
+pub fn set_transfer_hook(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    hook_program: Option<Pubkey>,
+) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::SetTransferHook { hook_program }.pack(),
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// 构建铸造账户配置存在时的 `TransferTokens` 指令，在固定账户之后附带双方开盘记录和系统程序
+///
+/// `from_owner`、`to_owner` 为双方代币账户的所有者。转账签名者在开盘窗口内需为
+/// 新钱包记录付费，因此标记为可写。分红池存在时再用 [`crate::dividend::with_dividend_checkpoints`]
+/// 把检查点追加在最后。
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_tokens_with_config(
+    program_id: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    from_owner: &Pubkey,
+    to_owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (from_wallet, _) = find_launch_wallet_address(program_id, mint, from_owner);
+    let (to_wallet, _) = find_launch_wallet_address(program_id, mint, to_owner);
+    let mut ix = instruction::transfer_tokens(program_id, from, to, authority, mint, amount);
+    ix.accounts[2].is_writable = true;
+    ix.accounts.extend([
+        AccountMeta::new(from_wallet, false),
+        AccountMeta::new(to_wallet, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]);
+    ix
+}
+
+/// 读取铸造账户配置，不存在时由 `payer` 付费创建
+pub(crate) fn load_or_create_config<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    config_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+) -> Result<MintConfig, ProgramError> {
+    let (address, bump) = find_mint_config_address(program_id, mint.key);
+    if address != *config_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if !config_account.data_is_empty() {
+        return MintConfig::unpack_account(&config_account.data.borrow());
+    }
+
+    create_pda_account(
+        payer,
+        config_account,
+        system_program_account,
+        program_id,
+        MintConfig::LEN,
+        &[MINT_CONFIG_SEED, mint.key.as_ref(), &[bump]],
+    )?;
+    Ok(MintConfig {
+        is_initialized: true,
+        mint: *mint.key,
+        non_transferable: false,
+        launch_guard: None,
+        clawback_authority: None,
+        interest_rate: None,
+        transfer_hook_program: None,
+        bump,
+    })
+}
@@ -0,0 +1,208 @@
+//! 供其他链上程序调用本程序的 CPI 辅助函数
+//!
+//! 调用方以 `no-entrypoint` feature 依赖本 crate，构造 [`CpiContext`] 后调用对应函数：
+//!
+//! ```ignore
+//! let ctx = CpiContext::new_with_signer(
+//!     token_program_account.clone(),
+//!     cpi::MintTokens { mint, token_account, mint_authority, payer, token_program },
+//!     &[&[b"rewards_authority", &[bump]]],
+//! );
+//! cpi::mint_tokens(ctx, amount)?;
+//! ```
+
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, instruction::AccountMeta,
+    program::invoke_signed,
+};
+
+use crate::instruction;
+
+/// CPI 调用上下文：本程序账户、指令账户以及 PDA 签名种子
+pub struct CpiContext<'a, 'info, T> {
+    /// 本程序的可执行账户
+    pub program: AccountInfo<'info>,
+    /// 指令所需的账户
+    pub accounts: T,
+    /// 调用方 PDA 的签名种子，不需要 PDA 签名时为空
+    pub signer_seeds: &'a [&'a [&'a [u8]]],
+}
+
+impl<'a, 'info, T> CpiContext<'a, 'info, T> {
+    /// 创建不需要 PDA 签名的上下文
+    pub fn new(program: AccountInfo<'info>, accounts: T) -> Self {
+        Self {
+            program,
+            accounts,
+            signer_seeds: &[],
+        }
+    }
+
+    /// 创建由调用方 PDA 签名的上下文
+    pub fn new_with_signer(
+        program: AccountInfo<'info>,
+        accounts: T,
+        signer_seeds: &'a [&'a [&'a [u8]]],
+    ) -> Self {
+        Self {
+            program,
+            accounts,
+            signer_seeds,
+        }
+    }
+}
+
+/// [`mint_tokens`] 的账户
+pub struct MintTokens<'info> {
+    /// 铸造账户
+    pub mint: AccountInfo<'info>,
+    /// 接收代币的账户
+    pub token_account: AccountInfo<'info>,
+    /// 铸造权限
+    pub mint_authority: AccountInfo<'info>,
+    /// 付费账户
+    pub payer: AccountInfo<'info>,
+    /// SPL Token 程序
+    pub token_program: AccountInfo<'info>,
+}
+
+/// [`transfer_tokens`] 的账户
+pub struct TransferTokens<'info> {
+    /// 转出账户
+    pub from: AccountInfo<'info>,
+    /// 转入账户
+    pub to: AccountInfo<'info>,
+    /// 转出账户的所有者或委托人
+    pub authority: AccountInfo<'info>,
+    /// SPL Token 或 Token-2022 程序
+    pub token_program: AccountInfo<'info>,
+    /// 铸造账户
+    pub mint: AccountInfo<'info>,
+    /// 转入账户的备注要求 PDA（`memo::find_memo_requirement_address`）
+    pub memo_requirement: AccountInfo<'info>,
+    /// 铸造账户配置 PDA（`config::find_mint_config_address`）
+    pub config: AccountInfo<'info>,
+    /// 分红池 PDA（`dividend::find_dividend_pool_address`）
+    pub dividend_pool: AccountInfo<'info>,
+    /// 配置存在时的开盘记录、系统程序和钩子账户，以及分红池存在时的检查点，按 `TransferTokens` 的顺序排列
+    pub remaining_accounts: Vec<AccountInfo<'info>>,
+}
+
+/// [`burn_tokens`] 的账户
+pub struct BurnTokens<'info> {
+    /// 被销毁代币的账户
+    pub token_account: AccountInfo<'info>,
+    /// 铸造账户
+    pub mint: AccountInfo<'info>,
+    /// 代币账户的所有者或委托人
+    pub authority: AccountInfo<'info>,
+    /// SPL Token 程序
+    pub token_program: AccountInfo<'info>,
+}
+
+/// 通过本程序铸造代币
+pub fn mint_tokens<'info>(
+    ctx: CpiContext<'_, 'info, MintTokens<'info>>,
+    amount: u64,
+) -> ProgramResult {
+    let MintTokens {
+        mint,
+        token_account,
+        mint_authority,
+        payer,
+        token_program,
+    } = ctx.accounts;
+    let ix = instruction::mint_tokens(
+        ctx.program.key,
+        mint.key,
+        token_account.key,
+        mint_authority.key,
+        payer.key,
+        amount,
+    );
+    invoke_signed(
+        &ix,
+        &[
+            mint,
+            token_account,
+            mint_authority,
+            payer,
+            token_program,
+            ctx.program,
+        ],
+        ctx.signer_seeds,
+    )
+}
+
+/// 通过本程序转移代币
+pub fn transfer_tokens<'info>(
+    ctx: CpiContext<'_, 'info, TransferTokens<'info>>,
+    amount: u64,
+) -> ProgramResult {
+    let TransferTokens {
+        from,
+        to,
+        authority,
+        token_program,
+        mint,
+        memo_requirement,
+        config,
+        dividend_pool,
+        remaining_accounts,
+    } = ctx.accounts;
+    let mut ix = instruction::with_token_program(
+        instruction::transfer_tokens(
+            ctx.program.key,
+            from.key,
+            to.key,
+            authority.key,
+            mint.key,
+            amount,
+        ),
+        token_program.key,
+    );
+    ix.accounts
+        .extend(remaining_accounts.iter().map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: account.is_signer,
+            is_writable: account.is_writable,
+        }));
+    let mut account_infos = vec![
+        from,
+        to,
+        authority,
+        token_program,
+        mint,
+        memo_requirement,
+        config,
+        dividend_pool,
+    ];
+    account_infos.extend(remaining_accounts);
+    account_infos.push(ctx.program);
+    invoke_signed(&ix, &account_infos, ctx.signer_seeds)
+}
+
+/// 通过本程序销毁代币
+pub fn burn_tokens<'info>(
+    ctx: CpiContext<'_, 'info, BurnTokens<'info>>,
+    amount: u64,
+) -> ProgramResult {
+    let BurnTokens {
+        token_account,
+        mint,
+        authority,
+        token_program,
+    } = ctx.accounts;
+    let ix = instruction::burn_tokens(
+        ctx.program.key,
+        token_account.key,
+        mint.key,
+        authority.key,
+        amount,
+    );
+    invoke_signed(
+        &ix,
+        &[token_account, mint, authority, token_program, ctx.program],
+        ctx.signer_seeds,
+    )
+}
@@ -0,0 +1,765 @@
+//! 持有人分红
+//!
+//! 每个铸造账户对应一个分红池 PDA。`DepositDividends` 把同一代币存入分红金库，并按登记的
+//! 持有量累加 `reward_per_token`；每个代币账户有一个分红检查点 PDA，记录登记的余额和上次
+//! 结算时的 `reward_per_token`，`ClaimDividends` 按两者之差发放分红。首次调用
+//! `ClaimDividends` 即为登记，登记之前的分红不参与分配。
+//!
+//! 铸造账户的冻结权限必须是分红池 PDA，首次存入时校验。登记后的代币账户由分红池冻结，
+//! 余额只能通过本程序变化：`TransferTokens` 转账前结算双方检查点并解冻，转账后更新登记
+//! 余额并重新冻结；领取时解冻、发放后重新冻结。直接调用代币程序的转账、铸造和销毁都会
+//! 因账户冻结而失败，登记余额始终等于实际余额，在账户之间来回转移余额不会多得分红。
+//!
+//! 每笔 `TransferTokens` 都必须在配置 PDA 之后附带分红池 PDA；分红池存在时还必须附带双方的
+//! 检查点 PDA（按地址校验）。代币转到未登记的账户后即可自由使用；存入分红也需使用未登记的
+//! 账户。冻结权限归分红池所有，因此分红铸造账户不能再使用回收和 `FreezeAccount`。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_token::{
+    instruction::{freeze_account, thaw_account, transfer},
+    state::{Account as SplAccount, Mint},
+};
+
+use crate::{
+    accounts::{
+        check_key, check_owner, check_program_account, check_signer, check_system_program,
+        check_token_owned_writable, check_token_program, check_writable,
+    },
+    create_pda_account, create_pda_token_account,
+    events::TokenEvent,
+    instruction,
+    stake::REWARD_PRECISION,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    TokenError, TokenInstruction,
+};
+
+/// 分红池 PDA 种子前缀
+pub const DIVIDEND_POOL_SEED: &[u8] = b"dividend_pool";
+/// 分红金库 PDA 种子前缀
+pub const DIVIDEND_VAULT_SEED: &[u8] = b"dividend_vault";
+/// 分红检查点 PDA 种子前缀
+pub const DIVIDEND_CHECKPOINT_SEED: &[u8] = b"dividend_checkpoint";
+
+/// 分红池状态
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct DividendPool {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 代币铸造账户，分红以同一代币发放
+    pub mint: Pubkey,
+    /// 每个登记代币累积的分红（放大 `REWARD_PRECISION` 倍）
+    pub reward_per_token: u128,
+    /// 所有检查点登记的余额之和
+    pub total_tracked: u64,
+    /// 累计存入的分红
+    pub total_deposited: u64,
+    /// 无人登记时存入、等待下次分配的分红
+    pub undistributed: u64,
+    /// 分红池 PDA bump
+    pub bump: u8,
+    /// 分红金库 PDA bump
+    pub vault_bump: u8,
+}
+
+impl ProgramAccount for DividendPool {
+    const ACCOUNT_TYPE: AccountType = AccountType::DividendPool;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 + 16 + 8 + 8 + 8 + 1 + 1;
+}
+
+impl DividendPool {
+    /// 把 `amount` 分红按登记余额分摊到 `reward_per_token`
+    ///
+    /// 还没有登记余额时分红暂存在 `undistributed`，随下一次存入一起分配。
+    pub fn distribute(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.total_deposited = self
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+        let amount = self
+            .undistributed
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+        if self.total_tracked == 0 {
+            self.undistributed = amount;
+            return Ok(());
+        }
+        let increment = (amount as u128)
+            .checked_mul(REWARD_PRECISION)
+            .ok_or(TokenError::Overflow)?
+            / self.total_tracked as u128;
+        self.reward_per_token = self
+            .reward_per_token
+            .checked_add(increment)
+            .ok_or(TokenError::Overflow)?;
+        self.undistributed = 0;
+        Ok(())
+    }
+
+    /// 把检查点的登记余额更新为 `balance`，同步 `total_tracked`
+    pub fn track(
+        &mut self,
+        checkpoint: &mut DividendCheckpoint,
+        balance: u64,
+    ) -> Result<(), ProgramError> {
+        self.total_tracked = self
+            .total_tracked
+            .checked_sub(checkpoint.balance)
+            .and_then(|total| total.checked_add(balance))
+            .ok_or(TokenError::Overflow)?;
+        checkpoint.balance = balance;
+        Ok(())
+    }
+}
+
+/// 代币账户的分红检查点
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct DividendCheckpoint {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 所属分红池
+    pub pool: Pubkey,
+    /// 对应的代币账户
+    pub token_account: Pubkey,
+    /// 登记的余额
+    pub balance: u64,
+    /// 上次结算时的 `reward_per_token`
+    pub reward_per_token_paid: u128,
+    /// 已结算但尚未领取的分红
+    pub pending: u64,
+    /// 检查点 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for DividendCheckpoint {
+    const ACCOUNT_TYPE: AccountType = AccountType::DividendCheckpoint;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 * 2 + 8 + 16 + 8 + 1;
+}
+
+impl DividendCheckpoint {
+    /// 按登记余额把截至 `reward_per_token` 的分红结算到 `pending`
+    pub fn settle(&mut self, reward_per_token: u128) -> Result<(), ProgramError> {
+        let earned = reward_per_token
+            .checked_sub(self.reward_per_token_paid)
+            .and_then(|delta| delta.checked_mul(self.balance as u128))
+            .ok_or(TokenError::Overflow)?
+            / REWARD_PRECISION;
+        self.pending = self
+            .pending
+            .checked_add(u64::try_from(earned).map_err(|_| TokenError::Overflow)?)
+            .ok_or(TokenError::Overflow)?;
+        self.reward_per_token_paid = reward_per_token;
+        Ok(())
+    }
+}
+
+/// 计算分红池 PDA 地址
+pub fn find_dividend_pool_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DIVIDEND_POOL_SEED, mint.as_ref()], program_id)
+}
+
+/// 计算分红金库 PDA 地址
+pub fn find_dividend_vault_address(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DIVIDEND_VAULT_SEED, pool.as_ref()], program_id)
+}
+
+/// 计算代币账户的分红检查点 PDA 地址
+pub fn find_dividend_checkpoint_address(
+    program_id: &Pubkey,
+    pool: &Pubkey,
+    token_account: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            DIVIDEND_CHECKPOINT_SEED,
+            pool.as_ref(),
+            token_account.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// `DepositDividends` 指令的账户
+pub struct DepositDividendsAccounts<'a, 'info> {
+    /// `[signer, writable]` 存入分红的账户（付费账户）
+    pub depositor: &'a AccountInfo<'info>,
+    /// `[writable]` 存入方的代币账户
+    pub source: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 分红池 PDA，首次存入时创建
+    pub pool: &'a AccountInfo<'info>,
+    /// `[writable]` 分红金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> DepositDividendsAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            depositor: next_account_info(accounts_iter)?,
+            source: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            pool: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.depositor)?;
+        check_writable(ctx.depositor)?;
+        check_token_owned_writable(ctx.source)?;
+        check_owner(ctx.mint, &spl_token::id())?;
+        check_writable(ctx.pool)?;
+        check_writable(ctx.vault)?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `ClaimDividends` 指令的账户
+pub struct ClaimDividendsAccounts<'a, 'info> {
+    /// `[signer, writable]` 代币账户所有者（付费账户）
+    pub owner: &'a AccountInfo<'info>,
+    /// `[writable]` 持有人的代币账户，同时接收分红
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[writable]` 分红池 PDA
+    pub pool: &'a AccountInfo<'info>,
+    /// `[writable]` 分红检查点 PDA，首次领取时创建
+    pub checkpoint: &'a AccountInfo<'info>,
+    /// `[writable]` 分红金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户，冻结和解冻持有人的代币账户
+    pub mint: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ClaimDividendsAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            owner: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            pool: next_account_info(accounts_iter)?,
+            checkpoint: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.owner)?;
+        check_writable(ctx.owner)?;
+        check_token_owned_writable(ctx.token_account)?;
+        check_writable(ctx.pool)?;
+        check_owner(ctx.pool, program_id)?;
+        check_writable(ctx.checkpoint)?;
+        check_token_owned_writable(ctx.vault)?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+        check_owner(ctx.mint, &spl_token::id())?;
+
+        Ok(ctx)
+    }
+}
+
+/// `TransferTokens` 的分红账户
+pub struct DividendTransferAccounts<'a, 'info> {
+    /// `[writable]` 分红池 PDA
+    pub pool: &'a AccountInfo<'info>,
+    /// `[writable]` 转出账户的分红检查点，未登记时跳过
+    pub from_checkpoint: &'a AccountInfo<'info>,
+    /// `[writable]` 转入账户的分红检查点，未登记时跳过
+    pub to_checkpoint: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> DividendTransferAccounts<'a, 'info> {
+    /// 校验必需的分红池 PDA，分红池为空时返回 `None`，否则从剩余账户中解析双方的检查点
+    pub fn parse(
+        pool: &'a AccountInfo<'info>,
+        accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+        mint: &Pubkey,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+    ) -> Result<Option<Self>, ProgramError> {
+        check_key(
+            pool,
+            &find_dividend_pool_address(&crate::id(), mint).0,
+            TokenError::InvalidProgramAddress,
+        )?;
+        check_writable(pool)?;
+        if pool.data_is_empty() {
+            return Ok(None);
+        }
+        let ctx = Self {
+            pool,
+            from_checkpoint: next_account_info(accounts_iter)?,
+            to_checkpoint: next_account_info(accounts_iter)?,
+        };
+
+        check_owner(ctx.pool, &crate::id())?;
+        for (checkpoint, token_account) in [(ctx.from_checkpoint, from), (ctx.to_checkpoint, to)] {
+            check_writable(checkpoint)?;
+            check_key(
+                checkpoint,
+                &find_dividend_checkpoint_address(&crate::id(), pool.key, token_account.key).0,
+                TokenError::InvalidProgramAddress,
+            )?;
+        }
+
+        Ok(Some(ctx))
+    }
+
+    /// 余额变化前按登记余额结算双方的检查点，并解冻已登记的账户
+    pub fn settle(
+        &self,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        mint: &AccountInfo<'info>,
+        token_program: &AccountInfo<'info>,
+    ) -> ProgramResult {
+        let pool = load_pool(self.pool)?;
+        for (checkpoint_account, token_account) in
+            [(self.from_checkpoint, from), (self.to_checkpoint, to)]
+        {
+            if let Some(mut checkpoint) =
+                load_transfer_checkpoint(&pool, self.pool, checkpoint_account, token_account)?
+            {
+                checkpoint.settle(pool.reward_per_token)?;
+                checkpoint.pack_account(&mut checkpoint_account.data.borrow_mut())?;
+                set_frozen(&pool, self.pool, token_account, mint, token_program, false)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 余额变化后把双方检查点的登记余额更新为实际余额，并重新冻结已登记的账户
+    pub fn track(
+        &self,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        mint: &AccountInfo<'info>,
+        token_program: &AccountInfo<'info>,
+    ) -> ProgramResult {
+        let mut pool = load_pool(self.pool)?;
+        for (checkpoint_account, token_account) in
+            [(self.from_checkpoint, from), (self.to_checkpoint, to)]
+        {
+            if let Some(mut checkpoint) =
+                load_transfer_checkpoint(&pool, self.pool, checkpoint_account, token_account)?
+            {
+                pool.track(&mut checkpoint, token_balance(token_account)?)?;
+                checkpoint.pack_account(&mut checkpoint_account.data.borrow_mut())?;
+                set_frozen(&pool, self.pool, token_account, mint, token_program, true)?;
+            }
+        }
+        pool.pack_account(&mut self.pool.data.borrow_mut())?;
+        Ok(())
+    }
+}
+
+/// 处理分红存入
+pub fn process_deposit_dividends(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let DepositDividendsAccounts {
+        depositor,
+        source,
+        mint,
+        pool: pool_account,
+        vault,
+        system_program: system_program_account,
+        token_program,
+    } = DepositDividendsAccounts::parse(accounts)?;
+
+    if amount == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    let (pool_address, bump) = find_dividend_pool_address(program_id, mint.key);
+    if pool_address != *pool_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    let (vault_address, vault_bump) = find_dividend_vault_address(program_id, &pool_address);
+    if vault_address != *vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+
+    // 首次存入时创建分红池与金库，分红池必须是冻结权限才能冻结登记的账户
+    let mut pool = if pool_account.data_is_empty() {
+        check_freeze_authority(mint, &pool_address)?;
+        create_pda_account(
+            depositor,
+            pool_account,
+            system_program_account,
+            program_id,
+            DividendPool::LEN,
+            &[DIVIDEND_POOL_SEED, mint.key.as_ref(), &[bump]],
+        )?;
+        create_pda_token_account(
+            depositor,
+            vault,
+            mint,
+            pool_account,
+            system_program_account,
+            token_program,
+            &[DIVIDEND_VAULT_SEED, pool_address.as_ref(), &[vault_bump]],
+        )?;
+        DividendPool {
+            is_initialized: true,
+            mint: *mint.key,
+            reward_per_token: 0,
+            total_tracked: 0,
+            total_deposited: 0,
+            undistributed: 0,
+            bump,
+            vault_bump,
+        }
+    } else {
+        check_owner(pool_account, program_id)?;
+        load_pool(pool_account)?
+    };
+
+    pool.distribute(amount)?;
+
+    invoke(
+        &transfer(
+            token_program.key,
+            source.key,
+            vault.key,
+            depositor.key,
+            &[],
+            amount,
+        )?,
+        &[source.clone(), vault.clone(), depositor.clone()],
+    )?;
+
+    pool.pack_account(&mut pool_account.data.borrow_mut())?;
+
+    TokenEvent::DividendsDeposited {
+        mint: *mint.key,
+        depositor: *depositor.key,
+        amount,
+        reward_per_token: pool.reward_per_token,
+    }
+    .emit();
+
+    log_msg!("已存入分红 {}，登记总量 {}", amount, pool.total_tracked);
+
+    Ok(())
+}
+
+/// 处理分红领取
+pub fn process_claim_dividends(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ClaimDividendsAccounts {
+        owner,
+        token_account,
+        pool: pool_account,
+        checkpoint: checkpoint_account,
+        vault,
+        system_program: system_program_account,
+        token_program,
+        mint,
+    } = ClaimDividendsAccounts::parse(program_id, accounts)?;
+
+    let mut pool = load_pool(pool_account)?;
+    let holder = SplAccount::unpack(&token_account.data.borrow())?;
+    if holder.mint != pool.mint || *mint.key != pool.mint {
+        return Err(TokenError::MintMismatch.into());
+    }
+    check_freeze_authority(mint, pool_account.key)?;
+    if holder.owner != *owner.key {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+    let vault_address = Pubkey::create_program_address(
+        &[
+            DIVIDEND_VAULT_SEED,
+            pool_account.key.as_ref(),
+            &[pool.vault_bump],
+        ],
+        program_id,
+    )?;
+    if vault_address != *vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+
+    // 首次领取时创建检查点，只参与此后的分红
+    let mut checkpoint = if checkpoint_account.data_is_empty() {
+        let (checkpoint_address, bump) =
+            find_dividend_checkpoint_address(program_id, pool_account.key, token_account.key);
+        if checkpoint_address != *checkpoint_account.key {
+            return Err(TokenError::InvalidProgramAddress.into());
+        }
+        create_pda_account(
+            owner,
+            checkpoint_account,
+            system_program_account,
+            program_id,
+            DividendCheckpoint::LEN,
+            &[
+                DIVIDEND_CHECKPOINT_SEED,
+                pool_account.key.as_ref(),
+                token_account.key.as_ref(),
+                &[bump],
+            ],
+        )?;
+        DividendCheckpoint {
+            is_initialized: true,
+            pool: *pool_account.key,
+            token_account: *token_account.key,
+            balance: 0,
+            reward_per_token_paid: pool.reward_per_token,
+            pending: 0,
+            bump,
+        }
+    } else {
+        check_owner(checkpoint_account, program_id)?;
+        load_checkpoint(pool_account, checkpoint_account, token_account)?
+    };
+
+    checkpoint.settle(pool.reward_per_token)?;
+    let amount = checkpoint.pending;
+    checkpoint.pending = 0;
+    if amount > 0 {
+        set_frozen(
+            &pool,
+            pool_account,
+            token_account,
+            mint,
+            token_program,
+            false,
+        )?;
+        invoke_signed(
+            &transfer(
+                token_program.key,
+                vault.key,
+                token_account.key,
+                pool_account.key,
+                &[],
+                amount,
+            )?,
+            &[vault.clone(), token_account.clone(), pool_account.clone()],
+            &[&[DIVIDEND_POOL_SEED, pool.mint.as_ref(), &[pool.bump]]],
+        )?;
+    }
+
+    // 领取到的分红同样计入登记余额，之后账户保持冻结
+    pool.track(&mut checkpoint, token_balance(token_account)?)?;
+    set_frozen(
+        &pool,
+        pool_account,
+        token_account,
+        mint,
+        token_program,
+        true,
+    )?;
+    checkpoint.pack_account(&mut checkpoint_account.data.borrow_mut())?;
+    pool.pack_account(&mut pool_account.data.borrow_mut())?;
+
+    TokenEvent::DividendsClaimed {
+        mint: pool.mint,
+        account: *token_account.key,
+        amount,
+    }
+    .emit();
+
+    log_msg!("已领取分红 {}，登记余额 {}", amount, checkpoint.balance);
+
+    Ok(())
+}
+
+/// 构建 `DepositDividends` 指令
+pub fn deposit_dividends(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (pool, _) = find_dividend_pool_address(program_id, mint);
+    let (vault, _) = find_dividend_vault_address(program_id, &pool);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::DepositDividends { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*depositor, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `ClaimDividends` 指令
+pub fn claim_dividends(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let (pool, _) = find_dividend_pool_address(program_id, mint);
+    let (checkpoint, _) = find_dividend_checkpoint_address(program_id, &pool, token_account);
+    let (vault, _) = find_dividend_vault_address(program_id, &pool);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::ClaimDividends.pack(),
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(checkpoint, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*mint, false),
+        ],
+    }
+}
+
+/// 构建分红池存在时的 `TransferTokens` 指令，转账前后会结算双方的分红检查点
+pub fn transfer_tokens_with_dividends(
+    program_id: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    with_dividend_checkpoints(
+        program_id,
+        instruction::transfer_tokens(program_id, from, to, authority, mint, amount),
+    )
+}
+
+/// 在转账指令末尾追加双方的分红检查点
+///
+/// 适用于 `TransferTokens` 的各个构建函数，附带配置或钩子账户时需最后调用，检查点位于它们之后。
+pub fn with_dividend_checkpoints(program_id: &Pubkey, mut ix: Instruction) -> Instruction {
+    let (pool, _) = find_dividend_pool_address(program_id, &ix.accounts[4].pubkey);
+    let (from_checkpoint, _) =
+        find_dividend_checkpoint_address(program_id, &pool, &ix.accounts[0].pubkey);
+    let (to_checkpoint, _) =
+        find_dividend_checkpoint_address(program_id, &pool, &ix.accounts[1].pubkey);
+    ix.accounts.extend([
+        AccountMeta::new(from_checkpoint, false),
+        AccountMeta::new(to_checkpoint, false),
+    ]);
+    ix
+}
+
+/// 读取分红池
+fn load_pool(pool_account: &AccountInfo) -> Result<DividendPool, ProgramError> {
+    let pool = DividendPool::unpack_account(&pool_account.data.borrow())?;
+    if !pool.is_initialized {
+        return Err(TokenError::AccountNotInitialized.into());
+    }
+    Ok(pool)
+}
+
+/// 读取检查点并确认它属于该分红池和代币账户
+fn load_checkpoint(
+    pool_account: &AccountInfo,
+    checkpoint_account: &AccountInfo,
+    token_account: &AccountInfo,
+) -> Result<DividendCheckpoint, ProgramError> {
+    let checkpoint = DividendCheckpoint::unpack_account(&checkpoint_account.data.borrow())?;
+    if !checkpoint.is_initialized {
+        return Err(TokenError::AccountNotInitialized.into());
+    }
+    if checkpoint.pool != *pool_account.key || checkpoint.token_account != *token_account.key {
+        return Err(TokenError::DividendCheckpointMismatch.into());
+    }
+    Ok(checkpoint)
+}
+
+/// 读取转账附带的检查点，未登记的代币账户返回 `None`
+fn load_transfer_checkpoint(
+    pool: &DividendPool,
+    pool_account: &AccountInfo,
+    checkpoint_account: &AccountInfo,
+    token_account: &AccountInfo,
+) -> Result<Option<DividendCheckpoint>, ProgramError> {
+    if SplAccount::unpack(&token_account.data.borrow())?.mint != pool.mint {
+        return Err(TokenError::MintMismatch.into());
+    }
+    if checkpoint_account.data_is_empty() {
+        return Ok(None);
+    }
+    check_owner(checkpoint_account, &crate::id())?;
+    load_checkpoint(pool_account, checkpoint_account, token_account).map(Some)
+}
+
+/// 读取代币账户余额
+fn token_balance(token_account: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(SplAccount::unpack(&token_account.data.borrow())?.amount)
+}
+
+/// 确认铸造账户的冻结权限是分红池
+fn check_freeze_authority(mint: &AccountInfo, pool: &Pubkey) -> ProgramResult {
+    let mint = Mint::unpack(&mint.data.borrow())?;
+    if mint.freeze_authority != COption::Some(*pool) {
+        return Err(TokenError::DividendFreezeAuthorityMismatch.into());
+    }
+    Ok(())
+}
+
+/// 由分红池签名冻结或解冻登记的代币账户，已处于目标状态时跳过
+fn set_frozen<'info>(
+    pool: &DividendPool,
+    pool_account: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    frozen: bool,
+) -> ProgramResult {
+    if SplAccount::unpack(&token_account.data.borrow())?.is_frozen() == frozen {
+        return Ok(());
+    }
+    let build = if frozen { freeze_account } else { thaw_account };
+    invoke_signed(
+        &build(
+            token_program.key,
+            token_account.key,
+            mint.key,
+            pool_account.key,
+            &[],
+        )?,
+        &[token_account.clone(), mint.clone(), pool_account.clone()],
+        &[&[DIVIDEND_POOL_SEED, pool.mint.as_ref(), &[pool.bump]]],
+    )
+}
@@ -0,0 +1,187 @@
+//! 程序错误定义
+//!
+//! 每个变体都有固定的错误码，链上以 `ProgramError::Custom(code)` 返回。
+//! 错误码一经发布不可修改，新增错误只能使用新的错误码。
+//! 客户端可通过 `TokenError::from_u32`（`num_traits::FromPrimitive`）还原错误。
+
+use num_derive::FromPrimitive;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+use thiserror::Error;
+
+/// 程序错误定义
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
+pub enum TokenError {
+    /// 无效指令
+    #[error("无效指令")]
+    InvalidInstruction = 0,
+    /// 账户未初始化
+    #[error("账户未初始化")]
+    AccountNotInitialized = 1,
+    /// 权限不足
+    #[error("权限不足")]
+    InsufficientPrivileges = 2,
+    /// 余额不足
+    #[error("余额不足")]
+    InsufficientBalance = 3,
+    /// 无效的代币数量
+    #[error("无效的代币数量")]
+    InvalidAmount = 4,
+    /// 质押仍处于锁定期
+    #[error("质押仍处于锁定期")]
+    StakeLocked = 5,
+    /// 缺少必要的签名
+    #[error("缺少必要的签名")]
+    MissingSignature = 6,
+    /// 传入的 SPL Token 程序不正确
+    #[error("传入的 SPL Token 程序不正确")]
+    IncorrectTokenProgram = 7,
+    /// 传入的系统程序不正确
+    #[error("传入的系统程序不正确")]
+    IncorrectSystemProgram = 8,
+    /// 账户地址与预期的 PDA 不一致
+    #[error("账户地址与预期的 PDA 不一致")]
+    InvalidProgramAddress = 9,
+    /// 账户已初始化
+    #[error("账户已初始化")]
+    AccountAlreadyInitialized = 10,
+    /// 账户所有者程序不正确
+    #[error("账户所有者程序不正确")]
+    IncorrectAccountOwner = 11,
+    /// 代币账户的铸造账户不匹配
+    #[error("代币账户的铸造账户不匹配")]
+    MintMismatch = 12,
+    /// 代币账户的所有者不匹配
+    #[error("代币账户的所有者不匹配")]
+    OwnerMismatch = 13,
+    /// 数值计算溢出
+    #[error("数值计算溢出")]
+    Overflow = 14,
+    /// 无效的锁定时长
+    #[error("无效的锁定时长")]
+    InvalidLockPeriod = 15,
+    /// 奖励代币的铸造权限不是质押池
+    #[error("奖励代币的铸造权限不是质押池")]
+    RewardMintAuthorityMismatch = 16,
+    /// 用户质押账户与质押池或用户不匹配
+    #[error("用户质押账户与质押池或用户不匹配")]
+    StakeAccountMismatch = 17,
+    /// 账户不可写
+    #[error("账户不可写")]
+    AccountNotWritable = 18,
+    /// 传入的租金 sysvar 不正确
+    #[error("传入的租金 sysvar 不正确")]
+    IncorrectRentSysvar = 19,
+    /// 传入的关联代币账户程序不正确
+    #[error("传入的关联代币账户程序不正确")]
+    IncorrectAssociatedTokenProgram = 20,
+    /// 释放时间必须晚于当前时间
+    #[error("释放时间必须晚于当前时间")]
+    InvalidReleaseTime = 21,
+    /// 定时转账尚未到释放时间
+    #[error("定时转账尚未到释放时间")]
+    ScheduleNotReleased = 22,
+    /// 定时转账已到释放时间，不能取消
+    #[error("定时转账已到释放时间，不能取消")]
+    ScheduleAlreadyReleased = 23,
+    /// 无效的支付流起止时间
+    #[error("无效的支付流起止时间")]
+    InvalidStreamTime = 24,
+    /// 缺少匹配的 Ed25519 验签指令
+    #[error("缺少匹配的 Ed25519 验签指令")]
+    InvalidAuthorizationSignature = 25,
+    /// 转账授权已过期
+    #[error("转账授权已过期")]
+    AuthorizationExpired = 26,
+    /// 授权 nonce 已被使用
+    #[error("授权 nonce 已被使用")]
+    NonceAlreadyUsed = 27,
+    /// 传入的 instructions sysvar 不正确
+    #[error("传入的 instructions sysvar 不正确")]
+    IncorrectInstructionsSysvar = 28,
+    /// 分红检查点与分红池或代币账户不匹配
+    #[error("分红检查点与分红池或代币账户不匹配")]
+    DividendCheckpointMismatch = 29,
+    /// 无效的发售配置
+    #[error("无效的发售配置")]
+    InvalidSaleConfig = 30,
+    /// 发售未在进行中
+    #[error("发售未在进行中")]
+    SaleNotActive = 31,
+    /// 发售尚未结束
+    #[error("发售尚未结束")]
+    SaleNotEnded = 32,
+    /// 发售已售罄
+    #[error("发售已售罄")]
+    SaleSoldOut = 33,
+    /// 尚未开盘
+    #[error("尚未开盘")]
+    TradingNotStarted = 34,
+    /// 开盘窗口内转账过于频繁
+    #[error("开盘窗口内转账过于频繁")]
+    TransferCooldown = 35,
+    /// 超过开盘窗口内的单钱包额度
+    #[error("超过开盘窗口内的单钱包额度")]
+    LaunchLimitExceeded = 36,
+    /// 代币不可转让
+    #[error("代币不可转让")]
+    NonTransferable = 37,
+    /// 铸造账户未启用回收
+    #[error("铸造账户未启用回收")]
+    ClawbackNotEnabled = 38,
+    /// 铸造账户未启用计息
+    #[error("铸造账户未启用计息")]
+    InterestNotEnabled = 39,
+    /// 转账钩子程序、校验 PDA 或额外账户与配置不匹配
+    #[error("转账钩子程序、校验 PDA 或额外账户与配置不匹配")]
+    TransferHookMismatch = 40,
+    /// 转入账户要求备注，转账未附带备注
+    #[error("转入账户要求备注，转账未附带备注")]
+    MemoRequired = 41,
+    /// NFT 未声明该集合、已验证或集合就是其自身
+    #[error("NFT 未声明该集合、已验证或集合就是其自身")]
+    CollectionMismatch = 42,
+    /// NFT 元数据的名称、符号或 URI 超出长度限制
+    #[error("NFT 元数据的名称、符号或 URI 超出长度限制")]
+    MetadataTooLong = 43,
+    /// 账户类型与预期不符
+    #[error("账户类型与预期不符")]
+    AccountTypeMismatch = 44,
+    /// 账户布局版本与程序不一致，需先执行 MigrateAccount
+    #[error("账户布局版本与程序不一致，需先执行 MigrateAccount")]
+    AccountVersionMismatch = 45,
+    /// 代币账户已冻结
+    #[error("代币账户已冻结")]
+    AccountFrozen = 46,
+    /// 分红代币的冻结权限不是分红池 PDA
+    #[error("分红代币的冻结权限不是分红池 PDA")]
+    DividendFreezeAuthorityMismatch = 47,
+}
+
+impl From<TokenError> for ProgramError {
+    fn from(e: TokenError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for TokenError {
+    fn type_of() -> &'static str {
+        "TokenError"
+    }
+}
+
+impl PrintProgramError for TokenError {
+    fn print<E>(&self)
+    where
+        E: 'static
+            + std::error::Error
+            + DecodeError<E>
+            + PrintProgramError
+            + num_traits::FromPrimitive,
+    {
+        msg!("错误 {}: {}", *self as u32, self);
+    }
+}
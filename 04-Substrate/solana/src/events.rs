@@ -0,0 +1,471 @@
+//! 结构化程序事件
+//!
+//! 每个处理函数在成功时通过 `sol_log_data` 输出一条 Borsh 编码的 [`TokenEvent`]，
+//! 日志格式为 `Program data: <base64(EVENT_TAG)> <base64(event)>`。
+//! 链下索引器可以用 [`parse_events`] 从交易日志中还原这些事件。
+//!
+//! 任何程序都可以输出相同格式的 `Program data`，因此解析时根据运行时写入的
+//! `Program <id> invoke [n]` / `success` / `failed` 日志跟踪调用栈，只接受本程序位于栈顶时的数据，
+//! 同一交易中的其他程序无法伪造本程序的事件。
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+use crate::{config::LaunchGuard, sale::Curve};
+
+/// 事件日志的标签，用于区分本程序与其他程序输出的 `Program data`
+pub const EVENT_TAG: &[u8] = b"tkn_evt";
+
+/// `sol_log_data` 输出的日志前缀
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// 日志超出长度上限时运行时写入的标记，之后的日志全部丢失
+const LOG_TRUNCATED: &str = "Log truncated";
+
+/// 程序事件
+///
+/// 变体顺序即 Borsh 编码的判别值，只能在末尾追加新变体。
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub enum TokenEvent {
+    /// 代币铸造账户已初始化
+    MintInitialized {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 代币精度
+        decimals: u8,
+        /// 铸造权限
+        mint_authority: Pubkey,
+        /// 冻结权限
+        freeze_authority: Option<Pubkey>,
+    },
+    /// 代币已铸造
+    Minted {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 接收代币的账户
+        account: Pubkey,
+        /// 铸造数量
+        amount: u64,
+    },
+    /// 代币已转移
+    Transferred {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 转出账户
+        from: Pubkey,
+        /// 转入账户
+        to: Pubkey,
+        /// 转移数量
+        amount: u64,
+    },
+    /// 代币已销毁
+    Burned {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 被销毁代币的账户
+        account: Pubkey,
+        /// 销毁数量
+        amount: u64,
+    },
+    /// 权限已变更
+    AuthorityChanged {
+        /// 铸造账户或代币账户
+        account: Pubkey,
+        /// 权限类型，取值与 `spl_token::instruction::AuthorityType` 一致
+        authority_type: u8,
+        /// 新的权限账户，`None` 表示放弃权限
+        new_authority: Option<Pubkey>,
+    },
+    /// 关联代币账户已创建
+    TokenAccountCreated {
+        /// 钱包地址
+        wallet: Pubkey,
+        /// 铸造账户
+        mint: Pubkey,
+        /// 新代币账户
+        account: Pubkey,
+    },
+    /// 质押池已创建
+    StakePoolCreated {
+        /// 质押池
+        pool: Pubkey,
+        /// 质押代币铸造账户
+        stake_mint: Pubkey,
+        /// 奖励代币铸造账户
+        reward_mint: Pubkey,
+        /// 每个 slot 发放的奖励数量
+        reward_per_slot: u64,
+        /// 质押锁定时长（秒）
+        lock_period: i64,
+    },
+    /// 已质押
+    Staked {
+        /// 质押池
+        pool: Pubkey,
+        /// 质押用户
+        owner: Pubkey,
+        /// 质押数量
+        amount: u64,
+    },
+    /// 已解除质押
+    Unstaked {
+        /// 质押池
+        pool: Pubkey,
+        /// 质押用户
+        owner: Pubkey,
+        /// 解除质押数量
+        amount: u64,
+    },
+    /// 已领取质押奖励
+    RewardsClaimed {
+        /// 质押池
+        pool: Pubkey,
+        /// 质押用户
+        owner: Pubkey,
+        /// 领取数量
+        amount: u64,
+    },
+    /// 代币账户已冻结
+    AccountFrozen {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 被冻结的代币账户
+        account: Pubkey,
+    },
+    /// SOL 已包装为 wSOL
+    SolWrapped {
+        /// 存入 lamports 的用户
+        owner: Pubkey,
+        /// wSOL 代币账户
+        account: Pubkey,
+        /// 存入的 lamports 数量
+        lamports: u64,
+    },
+    /// wSOL 已解包为 SOL
+    SolUnwrapped {
+        /// 接收 lamports 的所有者
+        owner: Pubkey,
+        /// 被关闭的 wSOL 代币账户
+        account: Pubkey,
+        /// 退还的 lamports 数量（含租金）
+        lamports: u64,
+    },
+    /// 定时转账已创建
+    TransferScheduled {
+        /// 定时转账账户
+        schedule: Pubkey,
+        /// 发送方
+        sender: Pubkey,
+        /// 接收方钱包地址
+        recipient: Pubkey,
+        /// 铸造账户
+        mint: Pubkey,
+        /// 转账数量
+        amount: u64,
+        /// 释放时间（unix 时间戳）
+        release_at: i64,
+    },
+    /// 定时转账已执行
+    ScheduledTransferExecuted {
+        /// 定时转账账户
+        schedule: Pubkey,
+        /// 接收方钱包地址
+        recipient: Pubkey,
+        /// 转账数量
+        amount: u64,
+    },
+    /// 定时转账已取消
+    ScheduledTransferCancelled {
+        /// 定时转账账户
+        schedule: Pubkey,
+        /// 发送方
+        sender: Pubkey,
+        /// 退回的数量
+        amount: u64,
+    },
+    /// 支付流已创建
+    StreamCreated {
+        /// 支付流账户
+        stream: Pubkey,
+        /// 发送方
+        sender: Pubkey,
+        /// 接收方钱包地址
+        recipient: Pubkey,
+        /// 铸造账户
+        mint: Pubkey,
+        /// 每秒释放的数量
+        rate_per_second: u64,
+        /// 开始时间（unix 时间戳）
+        start: i64,
+        /// 结束时间（unix 时间戳）
+        stop: i64,
+        /// 存入金库的总量
+        deposit: u64,
+    },
+    /// 接收方已从支付流提取代币
+    StreamWithdrawn {
+        /// 支付流账户
+        stream: Pubkey,
+        /// 接收方钱包地址
+        recipient: Pubkey,
+        /// 提取数量
+        amount: u64,
+    },
+    /// 支付流已取消
+    StreamCancelled {
+        /// 支付流账户
+        stream: Pubkey,
+        /// 退回发送方的数量
+        sender_amount: u64,
+        /// 结算给接收方的数量
+        recipient_amount: u64,
+    },
+    /// 已按链下签名授权完成转账
+    TransferAuthorized {
+        /// 签名授权的持有人
+        from: Pubkey,
+        /// 接收方钱包地址
+        to: Pubkey,
+        /// 转账数量
+        amount: u64,
+        /// 授权 nonce
+        nonce: [u8; 32],
+        /// 提交交易的中继者
+        relayer: Pubkey,
+    },
+    /// 分红已存入
+    DividendsDeposited {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 存入分红的账户
+        depositor: Pubkey,
+        /// 存入数量
+        amount: u64,
+        /// 存入后的 `reward_per_token`
+        reward_per_token: u128,
+    },
+    /// 分红已领取
+    DividendsClaimed {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 领取分红的代币账户
+        account: Pubkey,
+        /// 领取数量
+        amount: u64,
+    },
+    /// 联合曲线发售已创建
+    SaleCreated {
+        /// 发售 PDA
+        sale: Pubkey,
+        /// 铸造账户
+        mint: Pubkey,
+        /// 发起人
+        creator: Pubkey,
+        /// 价格曲线
+        curve: Curve,
+        /// 最多售出的数量
+        cap: u64,
+        /// 开始时间
+        start: i64,
+        /// 结束时间
+        end: i64,
+    },
+    /// 从发售买入代币
+    TokensBought {
+        /// 发售 PDA
+        sale: Pubkey,
+        /// 买方
+        buyer: Pubkey,
+        /// 买入数量
+        amount: u64,
+        /// 支付的 lamports
+        lamports: u64,
+    },
+    /// 向发售卖出代币
+    TokensSold {
+        /// 发售 PDA
+        sale: Pubkey,
+        /// 卖方
+        seller: Pubkey,
+        /// 卖出数量
+        amount: u64,
+        /// 退还的 lamports
+        lamports: u64,
+    },
+    /// 发售已毕业
+    SaleGraduated {
+        /// 发售 PDA
+        sale: Pubkey,
+        /// 铸造账户
+        mint: Pubkey,
+        /// 最终售出数量
+        sold: u64,
+        /// 锁定在发售 PDA 中的储备 lamports
+        reserve: u64,
+    },
+    /// 开盘防护已设置或关闭
+    LaunchGuardSet {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 开盘防护参数，`None` 表示关闭
+        guard: Option<LaunchGuard>,
+    },
+    /// 代币已被回收
+    ClawedBack {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 被回收的代币账户
+        source: Pubkey,
+        /// 接收回收代币的账户
+        destination: Pubkey,
+        /// 实际回收的数量，委托额度不足时为 0
+        amount: u64,
+        /// 执行回收的回收权限
+        authority: Pubkey,
+        /// 回收后源账户是否处于冻结状态
+        frozen: bool,
+    },
+    /// 计息代币的年利率已更新
+    InterestRateUpdated {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 新的年利率（基点）
+        rate_bps: i16,
+        /// 生效时间（unix 时间戳）
+        timestamp: i64,
+    },
+    /// 转账钩子已设置或移除
+    TransferHookSet {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 钩子程序，`None` 表示移除
+        hook_program: Option<Pubkey>,
+    },
+    /// 转账附带的备注，紧跟在对应的 `Transferred` 之后
+    TransferMemo {
+        /// 转出账户
+        from: Pubkey,
+        /// 转入账户
+        to: Pubkey,
+        /// 备注
+        memo: String,
+    },
+    /// 代币账户的转入备注要求已设置
+    MemoRequirementSet {
+        /// 代币账户
+        account: Pubkey,
+        /// 转入是否必须附带备注
+        required: bool,
+    },
+    /// NFT 已铸造，铸造权限已放弃
+    NftMinted {
+        /// NFT 铸造账户
+        mint: Pubkey,
+        /// 持有人钱包
+        owner: Pubkey,
+        /// 元数据更新权限
+        update_authority: Pubkey,
+        /// 声明的集合（未验证）
+        collection: Option<Pubkey>,
+    },
+    /// NFT 的集合已验证
+    CollectionVerified {
+        /// NFT 铸造账户
+        mint: Pubkey,
+        /// 集合 NFT 的铸造账户
+        collection: Pubkey,
+        /// 验证后的集合成员数量
+        size: u64,
+    },
+    /// 程序账户已升级到新布局
+    AccountMigrated {
+        /// 程序账户
+        account: Pubkey,
+        /// 账户类型（`state::AccountType`）
+        account_type: u8,
+        /// 原布局版本，0 表示没有头部的旧布局
+        from_version: u8,
+        /// 新布局版本
+        to_version: u8,
+    },
+    /// 账本代币账户的委托已设置，`delegate` 为 `None` 表示已撤销
+    LedgerDelegateApproved {
+        /// 账本代币账户
+        account: Pubkey,
+        /// 委托人
+        delegate: Option<Pubkey>,
+        /// 授权数量
+        amount: u64,
+    },
+    /// 代币账户已解冻
+    AccountThawed {
+        /// 代币铸造账户
+        mint: Pubkey,
+        /// 代币账户
+        account: Pubkey,
+    },
+}
+
+impl TokenEvent {
+    /// 通过 `sol_log_data` 输出事件
+    pub fn emit(&self) {
+        let data = self.try_to_vec().expect("事件序列化失败");
+        sol_log_data(&[EVENT_TAG, &data]);
+    }
+
+    /// 从单条日志解析事件，格式不符时返回 `None`
+    ///
+    /// 不检查输出该日志的程序，由 [`parse_events_for`] 根据调用栈判断。
+    fn from_log(log: &str) -> Option<Self> {
+        let mut fields = log.strip_prefix(PROGRAM_DATA_PREFIX)?.split(' ');
+        if STANDARD.decode(fields.next()?).ok()? != EVENT_TAG {
+            return None;
+        }
+        let data = STANDARD.decode(fields.next()?).ok()?;
+        if fields.next().is_some() {
+            return None;
+        }
+        Self::try_from_slice(&data).ok()
+    }
+}
+
+/// 从交易日志中解析本程序（[`crate::id()`]）输出的全部事件
+///
+/// 部署在其他地址时使用 [`parse_events_for`]。
+pub fn parse_events(logs: &[String]) -> Vec<TokenEvent> {
+    parse_events_for(&crate::id(), logs)
+}
+
+/// 从交易日志中解析 `program_id` 输出的全部事件
+///
+/// 只解析 `program_id` 是最内层调用时输出的 `Program data`，包括通过 CPI 调用本程序时输出的事件；
+/// 遇到日志截断标记时停止解析。
+pub fn parse_events_for(program_id: &Pubkey, logs: &[String]) -> Vec<TokenEvent> {
+    let program_id = program_id.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        if log == LOG_TRUNCATED {
+            break;
+        }
+        if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split(' ');
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => {
+                    stack.push(program);
+                    continue;
+                }
+                (Some(_), Some("success" | "failed:")) => {
+                    stack.pop();
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        if stack.last() == Some(&program_id.as_str()) {
+            events.extend(TokenEvent::from_log(log));
+        }
+    }
+    events
+}
@@ -0,0 +1,1569 @@
+//! 模糊测试的进程内执行环境
+//!
+//! `fuzz/` 下的目标和测试中的回归用例共用本模块：
+//!
+//! - [`check_instruction_data`] 把任意字节交给指令解析和处理函数，要求不会 panic，
+//!   且能解析的数据重新序列化后与原数据一致
+//! - [`FuzzLedger`] 是只包含原生账本模式账户的进程内账本，按顺序执行 [`LedgerOp`]，
+//!   每步之后检查供应量等于余额之和、没有正确签名时账户不会变化、冻结账户不会变化
+//! - [`FuzzToken`] 在进程内执行经 SPL Token 的指令序列 [`TokenOp`]：铸造、附带配置、备注、
+//!   分红和钩子账户的转账、销毁、冻结和变更权限，以及绕过本程序直接调用 SPL Token 的转账和
+//!   授权。每步之后检查同样的供应量、签名和冻结不变量，以及分红检查点与余额一致
+//!
+//! 账本指令不调用其他程序，可以不启动验证者直接调用处理函数，每秒执行的用例远多于
+//! `ProgramTest`。经 SPL Token 的指令需要跨程序调用，[`install_syscall_stubs`] 安装的系统调用桩
+//! 在进程内调用 SPL Token、Memo 和测试用的钩子程序，并按运行时的规则检查调用没有提升签名和
+//! 写权限、被调用程序只修改自己持有的可写账户。处理失败时丢弃本次修改，与运行时回滚交易一致。
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+};
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{self, SyscallStubs},
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_token::state::{Account as SplAccount, AccountState, Mint};
+
+use crate::{
+    config::{
+        find_launch_wallet_address, find_mint_config_address, LaunchGuard, LaunchWallet, MintConfig,
+    },
+    dividend::{
+        self, find_dividend_checkpoint_address, find_dividend_pool_address,
+        find_dividend_vault_address, DividendCheckpoint, DividendPool,
+    },
+    hook::{
+        self, find_extra_account_metas_address, ExtraAccountMeta, ExtraAccountMetas,
+        TransferHookInstruction,
+    },
+    instruction::{burn_tokens, freeze_account, mint_tokens, set_authority, transfer_tokens},
+    ledger::{self, LedgerMint},
+    memo::{self, find_memo_requirement_address, MemoRequirement},
+    state::ProgramAccount,
+    AuthorityType, TokenAccountState, TokenInstruction,
+};
+
+/// 钱包数量：铸造权限、冻结权限和三个用户
+const WALLET_COUNT: u8 = 5;
+
+/// 铸造权限的钱包序号
+const MINT_AUTHORITY: u8 = 0;
+
+/// 冻结权限的钱包序号
+const FREEZE_AUTHORITY: u8 = 1;
+
+/// 代币账户：（铸造账户序号，所有者钱包序号），最后一个属于另一个铸造账户
+const TOKEN_ACCOUNTS: [(usize, u8); 4] = [(0, 2), (0, 3), (0, 4), (1, 2)];
+
+/// SPL Token 代币账户：（铸造账户序号，所有者钱包序号，是否登记分红）
+///
+/// 铸造账户 0 设置了开盘防护、转账钩子和分红池，冻结权限为分红池 PDA；铸造账户 1 没有配置，
+/// 冻结权限为冻结权限钱包，最后一个代币账户要求转入附带备注。分红金库排在这些账户之后。
+const SPL_TOKEN_ACCOUNTS: [(usize, u8, bool); 5] = [
+    (0, 2, false),
+    (0, 3, true),
+    (0, 4, true),
+    (1, 2, false),
+    (1, 3, false),
+];
+
+/// 铸造账户 0 的开盘防护：slot 2 开盘，窗口 30 个 slot
+const LAUNCH_GUARD: LaunchGuard = LaunchGuard {
+    trading_start_slot: 2,
+    launch_slots: 30,
+    cooldown_slots: 3,
+    max_amount_per_wallet: 1_000,
+};
+
+/// 进程内的转账钩子程序，转移数量除以 7 余 3 时否决转账
+pub const HOOK_PROGRAM: Pubkey = Pubkey::new_from_array([200; 32]);
+
+/// 转账钩子否决转账时返回的错误码
+pub const HOOK_VETO: u32 = 7;
+
+/// 进程内跨程序调用的状态，每条顶层指令开始前重置
+#[derive(Default)]
+struct InvokeContext {
+    /// 正在执行的程序，最后一个为当前调用方
+    programs: Vec<Pubkey>,
+    /// 被调用程序修改过的账户
+    written: BTreeSet<Pubkey>,
+    /// 第一个失败的跨程序调用的错误，运行时中它会使整笔交易失败
+    failed: Option<ProgramError>,
+    /// 当前 slot
+    slot: u64,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<InvokeContext> = RefCell::new(InvokeContext::default());
+}
+
+/// 模糊测试的系统调用桩：不输出日志，在进程内执行跨程序调用并提供时钟
+struct FuzzStubs;
+
+impl SyscallStubs for FuzzStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let slot = CONTEXT.with(|context| context.borrow().slot);
+        // SAFETY: `Clock::get` 传入的是一个对齐的 `Clock` 的地址
+        unsafe {
+            *(var_addr as *mut Clock) = Clock {
+                slot,
+                ..Clock::default()
+            };
+        }
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = CONTEXT.with(|context| {
+            *context
+                .borrow()
+                .programs
+                .last()
+                .expect("跨程序调用时没有正在执行的程序")
+        });
+        let signers: Vec<Pubkey> = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller).expect("签名种子无效"))
+            .collect();
+
+        // 被调用方看到的权限取指令中同一账户所有声明的并集，且不能超过调用方持有的权限
+        let mut accounts = Vec::new();
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .unwrap_or_else(|| panic!("跨程序调用缺少账户 {}", meta.pubkey));
+            assert!(
+                !meta.is_writable || info.is_writable,
+                "跨程序调用提升了账户 {} 的写权限",
+                meta.pubkey
+            );
+            assert!(
+                !meta.is_signer || info.is_signer || signers.contains(&meta.pubkey),
+                "跨程序调用提升了账户 {} 的签名权限",
+                meta.pubkey
+            );
+            let (is_signer, is_writable) = instruction
+                .accounts
+                .iter()
+                .filter(|other| other.pubkey == meta.pubkey)
+                .fold((false, false), |(is_signer, is_writable), other| {
+                    (
+                        is_signer || other.is_signer,
+                        is_writable || other.is_writable,
+                    )
+                });
+            accounts.push(AccountInfo {
+                is_signer,
+                is_writable,
+                ..info.clone()
+            });
+        }
+
+        let before: Vec<Vec<u8>> = accounts
+            .iter()
+            .map(|info| info.data.borrow().to_vec())
+            .collect();
+        let result = execute(&instruction.program_id, &accounts, &instruction.data);
+        CONTEXT.with(|context| {
+            let mut context = context.borrow_mut();
+            match &result {
+                Ok(()) => {
+                    for (info, data) in accounts.iter().zip(&before) {
+                        if **info.data.borrow() != data[..] {
+                            assert!(info.is_writable, "跨程序调用修改了只读账户 {}", info.key);
+                            assert_eq!(
+                                *info.owner, instruction.program_id,
+                                "程序 {} 修改了不属于它的账户 {}",
+                                instruction.program_id, info.key
+                            );
+                            context.written.insert(*info.key);
+                        }
+                    }
+                }
+                Err(error) => {
+                    context.failed.get_or_insert_with(|| error.clone());
+                }
+            }
+        });
+        result
+    }
+}
+
+/// 安装模糊测试的系统调用桩：关闭日志和事件输出，在进程内执行跨程序调用
+///
+/// 会替换进程全局的系统调用桩，只应在模糊测试目标或单独的测试进程中调用，
+/// 不能与 `ProgramTest` 同时使用。
+pub fn install_syscall_stubs() {
+    program_stubs::set_syscall_stubs(Box::new(FuzzStubs));
+}
+
+/// 在进程内执行一个程序，记录调用栈供派生 PDA 签名
+fn execute(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    CONTEXT.with(|context| context.borrow_mut().programs.push(*program_id));
+    let result = if *program_id == crate::id() {
+        crate::process(program_id, accounts, data)
+    } else if *program_id == spl_token::id() {
+        spl_token::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == spl_memo::id() {
+        spl_memo::processor::process_instruction(program_id, accounts, data)
+    } else if *program_id == HOOK_PROGRAM {
+        process_fuzz_hook(accounts, data)
+    } else {
+        Err(ProgramError::IncorrectProgramId)
+    };
+    CONTEXT.with(|context| context.borrow_mut().programs.pop());
+    result
+}
+
+/// 测试用的转账钩子：收到带签名权限的账户视为程序错误，数量除以 7 余 3 时否决转账
+fn process_fuzz_hook(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    for account in accounts {
+        assert!(
+            !account.is_signer,
+            "转账钩子收到了带签名权限的账户 {}",
+            account.key
+        );
+    }
+    let TransferHookInstruction::Execute { amount } = TransferHookInstruction::unpack(data)?;
+    if amount % 7 == 3 {
+        return Err(ProgramError::Custom(HOOK_VETO));
+    }
+    Ok(())
+}
+
+/// 检查任意指令数据：解析和处理都不会 panic，能解析的数据序列化后与原数据一致
+///
+/// 直接调用分发函数，跳过入口处的错误日志输出。
+pub fn check_instruction_data(data: &[u8]) {
+    if let Ok(instruction) = TokenInstruction::unpack(data) {
+        assert_eq!(
+            instruction.pack(),
+            data,
+            "指令 {:?} 序列化后与原数据不一致",
+            instruction
+        );
+    }
+    assert!(
+        crate::process(&crate::id(), &[], data).is_err(),
+        "没有账户的指令处理成功"
+    );
+}
+
+/// 操作使用的权限账户
+///
+/// 直接按序号选择钱包时，随机输入很少选中有权限的钱包，因此单独提供应有的权限和当前委托人。
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub enum Authority {
+    /// 操作应有的权限：铸造权限、冻结权限或代币账户所有者
+    Expected,
+    /// 代币账户当前的委托人，没有委托人时为全零地址
+    Delegate,
+    /// 按序号选择的钱包
+    Wallet(u8),
+}
+
+/// 账本上的一步操作，钱包、铸造账户和代币账户用序号表示，超出范围时取模
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub enum LedgerOp {
+    /// `authority` 从铸造账户 `mint` 向代币账户 `account` 铸造
+    MintTo {
+        mint: u8,
+        account: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 从 `from` 向 `to` 转账
+    Transfer {
+        from: u8,
+        to: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 以所有者身份授权 `delegate`
+    Approve {
+        account: u8,
+        delegate: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 销毁 `account` 中的代币
+    Burn {
+        mint: u8,
+        account: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 冻结或解冻 `account`
+    SetFrozen {
+        mint: u8,
+        account: u8,
+        authority: Authority,
+        signed: bool,
+        frozen: bool,
+    },
+}
+
+/// 进程内账本
+#[derive(Debug, Clone)]
+pub struct FuzzLedger {
+    /// 程序持有的账户：地址 → 数据
+    accounts: BTreeMap<Pubkey, Vec<u8>>,
+    /// 账本铸造账户
+    mints: [Pubkey; 2],
+    /// 账本代币账户
+    token_accounts: Vec<Pubkey>,
+}
+
+/// 执行指令时单个账户的可变副本
+struct Entry {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+impl Default for FuzzLedger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FuzzLedger {
+    /// 创建两个账本铸造账户和四个余额为 0 的代币账户
+    pub fn new() -> Self {
+        let program_id = crate::id();
+        let mut accounts = BTreeMap::new();
+        let mints = [
+            Pubkey::new_from_array([101; 32]),
+            Pubkey::new_from_array([102; 32]),
+        ];
+        for mint in &mints {
+            let mut data = vec![0; LedgerMint::LEN];
+            LedgerMint {
+                is_initialized: true,
+                mint_authority: Some(wallet(MINT_AUTHORITY)),
+                freeze_authority: Some(wallet(FREEZE_AUTHORITY)),
+                supply: 0,
+                decimals: 0,
+            }
+            .pack_account(&mut data)
+            .unwrap();
+            accounts.insert(*mint, data);
+        }
+        let token_accounts = TOKEN_ACCOUNTS
+            .iter()
+            .map(|&(mint, owner)| {
+                let (address, _) =
+                    ledger::find_ledger_account_address(&program_id, &mints[mint], &wallet(owner));
+                let mut data = vec![0; TokenAccountState::LEN];
+                let state = TokenAccountState {
+                    is_initialized: true,
+                    mint: mints[mint],
+                    owner: wallet(owner),
+                    ..Default::default()
+                };
+                TokenAccountState::pack(state, &mut data).unwrap();
+                accounts.insert(address, data);
+                address
+            })
+            .collect();
+        Self {
+            accounts,
+            mints,
+            token_accounts,
+        }
+    }
+
+    /// 依次执行操作，失败的操作不影响后续操作
+    pub fn run(&mut self, ops: &[LedgerOp]) {
+        for op in ops {
+            let _ = self.apply(op);
+        }
+    }
+
+    /// 执行一步操作并检查不变量
+    pub fn apply(&mut self, op: &LedgerOp) -> ProgramResult {
+        let before = self.accounts.clone();
+        let (instruction, authority, signed) = self.instruction(op);
+        let result = self.process(&instruction, signed);
+        if result.is_err() {
+            self.accounts = before;
+            return result;
+        }
+
+        self.check_supply();
+        for (address, data) in &self.accounts {
+            if *data == before[address] {
+                continue;
+            }
+            assert!(
+                self.authorized(op, &authority, &before),
+                "{:?} 未经正确签名修改了账户 {}",
+                op,
+                address
+            );
+            if let Ok(state) = TokenAccountState::unpack(&before[address]) {
+                let thawing = matches!(op, LedgerOp::SetFrozen { account, .. }
+                    if self.token_account(*account) == *address);
+                assert!(
+                    !state.is_frozen || thawing,
+                    "冻结账户 {} 被 {:?} 修改",
+                    address,
+                    op
+                );
+            }
+        }
+        result
+    }
+
+    /// 代币账户的当前状态
+    pub fn token_state(&self, account: u8) -> TokenAccountState {
+        TokenAccountState::unpack(&self.accounts[&self.token_account(account)]).unwrap()
+    }
+
+    /// 铸造账户的当前供应量
+    pub fn supply(&self, mint: u8) -> u64 {
+        LedgerMint::unpack_account(&self.accounts[&self.mint(mint)])
+            .unwrap()
+            .supply
+    }
+
+    fn mint(&self, index: u8) -> Pubkey {
+        self.mints[index as usize % self.mints.len()]
+    }
+
+    fn token_account(&self, index: u8) -> Pubkey {
+        self.token_accounts[index as usize % self.token_accounts.len()]
+    }
+
+    /// 操作使用的权限钱包，`expected` 为操作应有的权限
+    fn authority(&self, authority: Authority, expected: Pubkey, account: u8) -> Pubkey {
+        match authority {
+            Authority::Expected => expected,
+            Authority::Delegate => self.token_state(account).delegate.unwrap_or_default(),
+            Authority::Wallet(index) => wallet(index),
+        }
+    }
+
+    /// 构建操作对应的指令，同时返回权限钱包和它是否签名
+    fn instruction(&self, op: &LedgerOp) -> (Instruction, Pubkey, bool) {
+        let program_id = crate::id();
+        match *op {
+            LedgerOp::MintTo {
+                mint,
+                account,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, wallet(MINT_AUTHORITY), account);
+                let instruction = ledger::ledger_mint_to(
+                    &program_id,
+                    &self.mint(mint),
+                    &self.token_account(account),
+                    &authority,
+                    amount,
+                );
+                (instruction, authority, signed)
+            }
+            LedgerOp::Transfer {
+                from,
+                to,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, self.token_state(from).owner, from);
+                let instruction = ledger::ledger_transfer(
+                    &program_id,
+                    &self.token_account(from),
+                    &self.token_account(to),
+                    &authority,
+                    amount,
+                );
+                (instruction, authority, signed)
+            }
+            LedgerOp::Approve {
+                account,
+                delegate,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, self.token_state(account).owner, account);
+                let instruction = ledger::ledger_approve(
+                    &program_id,
+                    &self.token_account(account),
+                    &wallet(delegate),
+                    &authority,
+                    amount,
+                );
+                (instruction, authority, signed)
+            }
+            LedgerOp::Burn {
+                mint,
+                account,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, self.token_state(account).owner, account);
+                let instruction = ledger::ledger_burn(
+                    &program_id,
+                    &self.token_account(account),
+                    &self.mint(mint),
+                    &authority,
+                    amount,
+                );
+                (instruction, authority, signed)
+            }
+            LedgerOp::SetFrozen {
+                mint,
+                account,
+                authority,
+                signed,
+                frozen,
+            } => {
+                let authority = self.authority(authority, wallet(FREEZE_AUTHORITY), account);
+                let instruction = ledger::ledger_set_frozen(
+                    &program_id,
+                    &self.token_account(account),
+                    &self.mint(mint),
+                    &authority,
+                    frozen,
+                );
+                (instruction, authority, signed)
+            }
+        }
+    }
+
+    /// 按运行时的方式构造账户并调用处理函数：重复出现的账户共用同一份数据，
+    /// 成功后写回，只读账户被修改视为程序错误
+    fn process(&mut self, instruction: &Instruction, signed: bool) -> ProgramResult {
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut indices = Vec::new();
+        for meta in &instruction.accounts {
+            let is_signer = meta.is_signer && signed;
+            match entries.iter().position(|entry| entry.key == meta.pubkey) {
+                Some(index) => {
+                    entries[index].is_signer |= is_signer;
+                    entries[index].is_writable |= meta.is_writable;
+                    indices.push(index);
+                }
+                None => {
+                    let (owner, data) = match self.accounts.get(&meta.pubkey) {
+                        Some(data) => (crate::id(), data.clone()),
+                        None => (system_program::id(), Vec::new()),
+                    };
+                    indices.push(entries.len());
+                    entries.push(Entry {
+                        key: meta.pubkey,
+                        owner,
+                        lamports: 1_000_000_000,
+                        data,
+                        is_signer,
+                        is_writable: meta.is_writable,
+                    });
+                }
+            }
+        }
+
+        let result = {
+            let infos: Vec<AccountInfo> = entries
+                .iter_mut()
+                .map(|entry| {
+                    AccountInfo::new(
+                        &entry.key,
+                        entry.is_signer,
+                        entry.is_writable,
+                        &mut entry.lamports,
+                        &mut entry.data,
+                        &entry.owner,
+                        false,
+                        0,
+                    )
+                })
+                .collect();
+            let accounts: Vec<AccountInfo> =
+                indices.iter().map(|&index| infos[index].clone()).collect();
+            crate::process(&instruction.program_id, &accounts, &instruction.data)
+        };
+
+        for entry in entries {
+            if let Some(data) = self.accounts.get_mut(&entry.key) {
+                if result.is_ok() && *data != entry.data {
+                    assert!(entry.is_writable, "只读账户 {} 被修改", entry.key);
+                    *data = entry.data;
+                }
+            }
+        }
+        result
+    }
+
+    /// 每个铸造账户的供应量等于其代币账户余额之和
+    fn check_supply(&self) {
+        for mint in &self.mints {
+            let supply = LedgerMint::unpack_account(&self.accounts[mint])
+                .unwrap()
+                .supply;
+            let total: u128 = self
+                .token_accounts
+                .iter()
+                .map(|address| TokenAccountState::unpack(&self.accounts[address]).unwrap())
+                .filter(|state| state.mint == *mint)
+                .map(|state| state.amount as u128)
+                .sum();
+            assert_eq!(
+                supply as u128, total,
+                "铸造账户 {} 的供应量与余额之和不一致",
+                mint
+            );
+        }
+    }
+
+    /// 操作是否由有权执行它的钱包签名，`before` 为执行前的账户
+    fn authorized(
+        &self,
+        op: &LedgerOp,
+        authority: &Pubkey,
+        before: &BTreeMap<Pubkey, Vec<u8>>,
+    ) -> bool {
+        let state =
+            |account: u8| TokenAccountState::unpack(&before[&self.token_account(account)]).unwrap();
+        match *op {
+            LedgerOp::MintTo { signed, .. } => signed && *authority == wallet(MINT_AUTHORITY),
+            LedgerOp::SetFrozen { signed, .. } => signed && *authority == wallet(FREEZE_AUTHORITY),
+            LedgerOp::Approve {
+                account, signed, ..
+            } => signed && *authority == state(account).owner,
+            LedgerOp::Transfer {
+                from: account,
+                signed,
+                amount,
+                ..
+            }
+            | LedgerOp::Burn {
+                account,
+                signed,
+                amount,
+                ..
+            } => {
+                let state = state(account);
+                signed
+                    && (*authority == state.owner
+                        || (state.delegate == Some(*authority) && state.delegated_amount >= amount))
+            }
+        }
+    }
+}
+
+/// 经 SPL Token 的一步操作，钱包、铸造账户和代币账户用序号表示，超出范围时取模
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "fuzz", derive(arbitrary::Arbitrary))]
+pub enum TokenOp {
+    /// `authority` 通过 `MintTokens` 从铸造账户 `mint` 向代币账户 `account` 铸造
+    MintTo {
+        mint: u8,
+        account: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 通过 `TransferTokens` 从 `from` 向 `to` 转账，`memo` 时附带备注
+    Transfer {
+        from: u8,
+        to: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+        memo: bool,
+    },
+    /// `authority` 通过 `BurnTokens` 销毁 `account` 中的代币
+    Burn {
+        mint: u8,
+        account: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 通过 `FreezeAccount` 冻结 `account`
+    Freeze {
+        mint: u8,
+        account: u8,
+        authority: Authority,
+        signed: bool,
+    },
+    /// `authority` 通过 `SetAuthority` 变更铸造账户的铸造权限或冻结权限（`freeze`），
+    /// `new_authority` 为 `None` 时撤销
+    SetMintAuthority {
+        mint: u8,
+        freeze: bool,
+        new_authority: Option<u8>,
+        authority: Authority,
+        signed: bool,
+    },
+    /// `authority` 通过 `SetAuthority` 变更代币账户的所有者或关闭权限（`close`）
+    SetAccountAuthority {
+        account: u8,
+        close: bool,
+        new_authority: Option<u8>,
+        authority: Authority,
+        signed: bool,
+    },
+    /// `authority` 通过 `DepositDividends` 从 `account` 存入分红
+    Deposit {
+        account: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 通过 `ClaimDividends` 领取 `account` 的分红
+    Claim {
+        account: u8,
+        authority: Authority,
+        signed: bool,
+    },
+    /// `authority` 绕过本程序直接调用 SPL Token 从 `from` 向 `to` 转账
+    SplTransfer {
+        from: u8,
+        to: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// `authority` 绕过本程序直接调用 SPL Token 授权 `delegate`
+    SplApprove {
+        account: u8,
+        delegate: u8,
+        authority: Authority,
+        signed: bool,
+        amount: u64,
+    },
+    /// 时钟前进 `slots` 个 slot
+    Warp { slots: u8 },
+}
+
+/// 经 SPL Token 的进程内执行环境
+#[derive(Debug, Clone)]
+pub struct FuzzToken {
+    /// 所有已存在的账户：地址 → （所有者，数据）
+    accounts: BTreeMap<Pubkey, (Pubkey, Vec<u8>)>,
+    /// SPL Token 铸造账户
+    mints: [Pubkey; 2],
+    /// SPL Token 代币账户，最后一个为分红金库
+    token_accounts: Vec<Pubkey>,
+    /// 登记分红的代币账户和它的检查点
+    checkpoints: Vec<(Pubkey, Pubkey)>,
+    /// 铸造账户 0 的分红池
+    pool: Pubkey,
+    /// 当前 slot
+    slot: u64,
+}
+
+impl Default for FuzzToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FuzzToken {
+    /// 创建两个铸造账户、五个余额为 0 的代币账户，以及铸造账户 0 的配置、开盘记录、
+    /// 钩子校验 PDA、分红池和检查点
+    pub fn new() -> Self {
+        let program_id = crate::id();
+        let mints = [
+            Pubkey::new_from_array([111; 32]),
+            Pubkey::new_from_array([112; 32]),
+        ];
+        let (pool, bump) = find_dividend_pool_address(&program_id, &mints[0]);
+        let (vault, vault_bump) = find_dividend_vault_address(&program_id, &pool);
+        let mut env = Self {
+            accounts: BTreeMap::new(),
+            mints,
+            token_accounts: Vec::new(),
+            checkpoints: Vec::new(),
+            pool,
+            slot: 0,
+        };
+
+        for (mint, freeze_authority) in mints.iter().zip([pool, wallet(FREEZE_AUTHORITY)]) {
+            let state = Mint {
+                mint_authority: COption::Some(wallet(MINT_AUTHORITY)),
+                freeze_authority: COption::Some(freeze_authority),
+                is_initialized: true,
+                ..Mint::default()
+            };
+            env.insert_spl(*mint, state);
+        }
+        for (index, &(mint, owner, registered)) in SPL_TOKEN_ACCOUNTS.iter().enumerate() {
+            let address = Pubkey::new_from_array([121 + index as u8; 32]);
+            let state = SplAccount {
+                mint: mints[mint],
+                owner: wallet(owner),
+                state: if registered {
+                    AccountState::Frozen
+                } else {
+                    AccountState::Initialized
+                },
+                ..SplAccount::default()
+            };
+            env.insert_spl(address, state);
+            if registered {
+                let (checkpoint, bump) =
+                    find_dividend_checkpoint_address(&program_id, &pool, &address);
+                env.insert_program(
+                    checkpoint,
+                    &DividendCheckpoint {
+                        is_initialized: true,
+                        pool,
+                        token_account: address,
+                        balance: 0,
+                        reward_per_token_paid: 0,
+                        pending: 0,
+                        bump,
+                    },
+                );
+                env.checkpoints.push((address, checkpoint));
+            }
+            env.token_accounts.push(address);
+        }
+        let vault_state = SplAccount {
+            mint: mints[0],
+            owner: pool,
+            state: AccountState::Initialized,
+            ..SplAccount::default()
+        };
+        env.insert_spl(vault, vault_state);
+        env.token_accounts.push(vault);
+        env.insert_program(
+            pool,
+            &DividendPool {
+                is_initialized: true,
+                mint: mints[0],
+                reward_per_token: 0,
+                total_tracked: 0,
+                total_deposited: 0,
+                undistributed: 0,
+                bump,
+                vault_bump,
+            },
+        );
+
+        let (config, bump) = find_mint_config_address(&program_id, &mints[0]);
+        env.insert_program(
+            config,
+            &MintConfig {
+                is_initialized: true,
+                mint: mints[0],
+                non_transferable: false,
+                launch_guard: Some(LAUNCH_GUARD),
+                clawback_authority: None,
+                interest_rate: None,
+                transfer_hook_program: Some(HOOK_PROGRAM),
+                bump,
+            },
+        );
+        for index in 0..WALLET_COUNT {
+            let (record, bump) = find_launch_wallet_address(&program_id, &mints[0], &wallet(index));
+            env.insert_program(
+                record,
+                &LaunchWallet {
+                    is_initialized: true,
+                    mint: mints[0],
+                    wallet: wallet(index),
+                    last_transfer_slot: 0,
+                    received: 0,
+                    bump,
+                },
+            );
+        }
+        let (validation, _) = find_extra_account_metas_address(&HOOK_PROGRAM, &mints[0]);
+        let metas = ExtraAccountMetas {
+            accounts: hook_extra_accounts(),
+        };
+        env.accounts
+            .insert(validation, (HOOK_PROGRAM, metas.try_to_vec().unwrap()));
+
+        let memo_account = env.token_accounts[SPL_TOKEN_ACCOUNTS.len() - 1];
+        let (requirement, bump) = find_memo_requirement_address(&program_id, &memo_account);
+        env.insert_program(
+            requirement,
+            &MemoRequirement {
+                is_initialized: true,
+                token_account: memo_account,
+                require_incoming_memo: true,
+                bump,
+            },
+        );
+        env
+    }
+
+    /// 依次执行操作，失败的操作不影响后续操作
+    pub fn run(&mut self, ops: &[TokenOp]) {
+        for op in ops {
+            let _ = self.apply(op);
+        }
+    }
+
+    /// 执行一步操作并检查不变量
+    pub fn apply(&mut self, op: &TokenOp) -> ProgramResult {
+        if let TokenOp::Warp { slots } = *op {
+            self.slot += u64::from(slots);
+            return Ok(());
+        }
+        let before = self.accounts.clone();
+        let (instruction, authority, signed) = self.instruction(op);
+        let result = self.process(&instruction, signed);
+        if result.is_err() {
+            self.accounts = before;
+            return result;
+        }
+
+        self.check_supply();
+        self.check_dividends();
+        self.check_transfer_amounts(op, &before);
+        let (touched_accounts, touched_mints) = self.touched(op);
+        for address in self.token_accounts.iter().chain(&self.mints) {
+            if self.accounts[address] == before[address] {
+                continue;
+            }
+            assert!(
+                touched_accounts.contains(address) || touched_mints.contains(address),
+                "{:?} 修改了无关的账户 {}",
+                op,
+                address
+            );
+            assert!(
+                signed && self.authorized(op, &authority, &before),
+                "{:?} 未经正确签名修改了账户 {}",
+                op,
+                address
+            );
+            if let Ok(state) = SplAccount::unpack(&before[address].1) {
+                let mediated = self.is_registered(address)
+                    && matches!(op, TokenOp::Transfer { .. } | TokenOp::Claim { .. });
+                assert!(
+                    !state.is_frozen() || mediated,
+                    "冻结账户 {} 被 {:?} 修改",
+                    address,
+                    op
+                );
+            }
+        }
+        result
+    }
+
+    /// 代币账户的当前状态
+    pub fn token_state(&self, account: u8) -> SplAccount {
+        self.spl_account(&self.token_account(account))
+    }
+
+    /// 铸造账户的当前供应量
+    pub fn supply(&self, mint: u8) -> u64 {
+        Mint::unpack(&self.accounts[&self.mint(mint)].1)
+            .unwrap()
+            .supply
+    }
+
+    /// 代币账户登记的分红检查点
+    pub fn checkpoint(&self, account: u8) -> Option<DividendCheckpoint> {
+        let address = self.token_account(account);
+        self.checkpoints
+            .iter()
+            .find(|(token_account, _)| *token_account == address)
+            .map(|(_, checkpoint)| {
+                DividendCheckpoint::unpack_account(&self.accounts[checkpoint].1).unwrap()
+            })
+    }
+
+    fn mint(&self, index: u8) -> Pubkey {
+        self.mints[index as usize % self.mints.len()]
+    }
+
+    fn token_account(&self, index: u8) -> Pubkey {
+        self.token_accounts[index as usize % self.token_accounts.len()]
+    }
+
+    fn spl_account(&self, address: &Pubkey) -> SplAccount {
+        SplAccount::unpack(&self.accounts[address].1).unwrap()
+    }
+
+    fn mint_state(&self, index: u8) -> Mint {
+        Mint::unpack(&self.accounts[&self.mint(index)].1).unwrap()
+    }
+
+    fn vault(&self) -> Pubkey {
+        self.token_accounts[self.token_accounts.len() - 1]
+    }
+
+    fn is_registered(&self, address: &Pubkey) -> bool {
+        self.checkpoints
+            .iter()
+            .any(|(token_account, _)| token_account == address)
+    }
+
+    fn insert_spl<T: Pack>(&mut self, address: Pubkey, state: T) {
+        let mut data = vec![0; T::LEN];
+        T::pack(state, &mut data).unwrap();
+        self.accounts.insert(address, (spl_token::id(), data));
+    }
+
+    fn insert_program<T: ProgramAccount>(&mut self, address: Pubkey, state: &T) {
+        let mut data = vec![0; T::LEN];
+        state.pack_account(&mut data).unwrap();
+        self.accounts.insert(address, (crate::id(), data));
+    }
+
+    /// 操作使用的权限钱包，`expected` 为操作应有的权限
+    fn authority(&self, authority: Authority, expected: Pubkey, account: u8) -> Pubkey {
+        match authority {
+            Authority::Expected => expected,
+            Authority::Delegate => self.token_state(account).delegate.unwrap_or_default(),
+            Authority::Wallet(index) => wallet(index),
+        }
+    }
+
+    /// 构建操作对应的指令，同时返回权限和它是否签名；只有钱包能在顶层签名
+    fn instruction(&self, op: &TokenOp) -> (Instruction, Pubkey, bool) {
+        let program_id = crate::id();
+        let (instruction, authority, signed) = match *op {
+            TokenOp::MintTo {
+                mint,
+                account,
+                authority,
+                signed,
+                amount,
+            } => {
+                let expected = self.mint_state(mint).mint_authority.unwrap_or_default();
+                let authority = self.authority(authority, expected, account);
+                let instruction = mint_tokens(
+                    &program_id,
+                    &self.mint(mint),
+                    &self.token_account(account),
+                    &authority,
+                    &authority,
+                    amount,
+                );
+                (instruction, authority, signed)
+            }
+            TokenOp::Transfer {
+                from,
+                to,
+                authority,
+                signed,
+                amount,
+                memo,
+            } => {
+                let authority = self.authority(authority, self.token_state(from).owner, from);
+                let instruction = self.transfer_instruction(from, to, &authority, amount, memo);
+                (instruction, authority, signed)
+            }
+            TokenOp::Burn {
+                mint,
+                account,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, self.token_state(account).owner, account);
+                let instruction = burn_tokens(
+                    &program_id,
+                    &self.token_account(account),
+                    &self.mint(mint),
+                    &authority,
+                    amount,
+                );
+                (instruction, authority, signed)
+            }
+            TokenOp::Freeze {
+                mint,
+                account,
+                authority,
+                signed,
+            } => {
+                let expected = self.mint_state(mint).freeze_authority.unwrap_or_default();
+                let authority = self.authority(authority, expected, account);
+                let instruction = freeze_account(
+                    &program_id,
+                    &self.token_account(account),
+                    &self.mint(mint),
+                    &authority,
+                );
+                (instruction, authority, signed)
+            }
+            TokenOp::SetMintAuthority {
+                mint,
+                freeze,
+                new_authority,
+                authority,
+                signed,
+            } => {
+                let state = self.mint_state(mint);
+                let (expected, authority_type) = if freeze {
+                    (state.freeze_authority, AuthorityType::FreezeAccount)
+                } else {
+                    (state.mint_authority, AuthorityType::MintTokens)
+                };
+                let authority = self.authority(authority, expected.unwrap_or_default(), 0);
+                let instruction = set_authority(
+                    &program_id,
+                    &self.mint(mint),
+                    &authority,
+                    authority_type,
+                    new_authority.map(wallet).as_ref(),
+                );
+                (instruction, authority, signed)
+            }
+            TokenOp::SetAccountAuthority {
+                account,
+                close,
+                new_authority,
+                authority,
+                signed,
+            } => {
+                let state = self.token_state(account);
+                let (expected, authority_type) = if close {
+                    (
+                        state.close_authority.unwrap_or(state.owner),
+                        AuthorityType::CloseAccount,
+                    )
+                } else {
+                    (state.owner, AuthorityType::AccountOwner)
+                };
+                let authority = self.authority(authority, expected, account);
+                let instruction = set_authority(
+                    &program_id,
+                    &self.token_account(account),
+                    &authority,
+                    authority_type,
+                    new_authority.map(wallet).as_ref(),
+                );
+                (instruction, authority, signed)
+            }
+            TokenOp::Deposit {
+                account,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, self.token_state(account).owner, account);
+                let instruction = dividend::deposit_dividends(
+                    &program_id,
+                    &authority,
+                    &self.token_account(account),
+                    &self.token_state(account).mint,
+                    amount,
+                );
+                (instruction, authority, signed)
+            }
+            TokenOp::Claim {
+                account,
+                authority,
+                signed,
+            } => {
+                let authority = self.authority(authority, self.token_state(account).owner, account);
+                let instruction = dividend::claim_dividends(
+                    &program_id,
+                    &authority,
+                    &self.token_account(account),
+                    &self.token_state(account).mint,
+                );
+                (instruction, authority, signed)
+            }
+            TokenOp::SplTransfer {
+                from,
+                to,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, self.token_state(from).owner, from);
+                let instruction = spl_token::instruction::transfer(
+                    &spl_token::id(),
+                    &self.token_account(from),
+                    &self.token_account(to),
+                    &authority,
+                    &[],
+                    amount,
+                )
+                .unwrap();
+                (instruction, authority, signed)
+            }
+            TokenOp::SplApprove {
+                account,
+                delegate,
+                authority,
+                signed,
+                amount,
+            } => {
+                let authority = self.authority(authority, self.token_state(account).owner, account);
+                let instruction = spl_token::instruction::approve(
+                    &spl_token::id(),
+                    &self.token_account(account),
+                    &wallet(delegate),
+                    &authority,
+                    &[],
+                    amount,
+                )
+                .unwrap();
+                (instruction, authority, signed)
+            }
+            TokenOp::Warp { .. } => unreachable!("时钟前进不构建指令"),
+        };
+        (instruction, authority, signed && is_wallet(&authority))
+    }
+
+    /// 构建 `TransferTokens`：铸造账户 0 附带开盘记录、钩子账户和分红检查点
+    fn transfer_instruction(
+        &self,
+        from: u8,
+        to: u8,
+        authority: &Pubkey,
+        amount: u64,
+        memo: bool,
+    ) -> Instruction {
+        let program_id = crate::id();
+        let (from, to) = (self.token_account(from), self.token_account(to));
+        let from_state = self.spl_account(&from);
+        let instruction = if from_state.mint == self.mints[0] {
+            let ix = hook::transfer_tokens_with_hook(
+                &program_id,
+                &from,
+                &to,
+                authority,
+                &from_state.mint,
+                &from_state.owner,
+                &self.spl_account(&to).owner,
+                &HOOK_PROGRAM,
+                &hook_extra_accounts(),
+                amount,
+            );
+            dividend::with_dividend_checkpoints(&program_id, ix)
+        } else {
+            transfer_tokens(&program_id, &from, &to, authority, &from_state.mint, amount)
+        };
+        if memo {
+            memo::with_memo(instruction, amount, "fuzz")
+        } else {
+            instruction
+        }
+    }
+
+    /// 按运行时的方式构造账户并执行指令：重复出现的账户共用同一份数据，不存在的账户为
+    /// 系统程序持有的空账户，成功后写回；只读账户或不属于顶层程序、也没有经跨程序调用修改的
+    /// 账户被修改视为程序错误
+    fn process(&mut self, instruction: &Instruction, signed: bool) -> ProgramResult {
+        let programs = [
+            crate::id(),
+            spl_token::id(),
+            spl_memo::id(),
+            HOOK_PROGRAM,
+            system_program::id(),
+        ];
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut indices = Vec::new();
+        for meta in &instruction.accounts {
+            let is_signer = meta.is_signer && signed;
+            match entries.iter().position(|entry| entry.key == meta.pubkey) {
+                Some(index) => {
+                    entries[index].is_signer |= is_signer;
+                    entries[index].is_writable |= meta.is_writable;
+                    indices.push(index);
+                }
+                None => {
+                    let (owner, data) = self
+                        .accounts
+                        .get(&meta.pubkey)
+                        .cloned()
+                        .unwrap_or((system_program::id(), Vec::new()));
+                    indices.push(entries.len());
+                    entries.push(Entry {
+                        key: meta.pubkey,
+                        owner,
+                        lamports: 1_000_000_000,
+                        data,
+                        is_signer,
+                        is_writable: meta.is_writable,
+                    });
+                }
+            }
+        }
+
+        CONTEXT.with(|context| {
+            *context.borrow_mut() = InvokeContext {
+                slot: self.slot,
+                ..InvokeContext::default()
+            }
+        });
+        let result = {
+            let infos: Vec<AccountInfo> = entries
+                .iter_mut()
+                .map(|entry| {
+                    AccountInfo::new(
+                        &entry.key,
+                        entry.is_signer,
+                        entry.is_writable,
+                        &mut entry.lamports,
+                        &mut entry.data,
+                        &entry.owner,
+                        programs.contains(&entry.key),
+                        0,
+                    )
+                })
+                .collect();
+            let accounts: Vec<AccountInfo> =
+                indices.iter().map(|&index| infos[index].clone()).collect();
+            execute(&instruction.program_id, &accounts, &instruction.data)
+        };
+        let (written, failed) = CONTEXT.with(|context| {
+            let mut context = context.borrow_mut();
+            (std::mem::take(&mut context.written), context.failed.take())
+        });
+        let result = result.and(failed.map_or(Ok(()), Err));
+
+        for entry in entries {
+            if let Some((owner, data)) = self.accounts.get_mut(&entry.key) {
+                if result.is_ok() && *data != entry.data {
+                    assert!(entry.is_writable, "只读账户 {} 被修改", entry.key);
+                    assert!(
+                        *owner == instruction.program_id || written.contains(&entry.key),
+                        "程序 {} 修改了不属于它的账户 {}",
+                        instruction.program_id,
+                        entry.key
+                    );
+                    *data = entry.data;
+                }
+            }
+        }
+        result
+    }
+
+    /// 每个铸造账户的供应量等于其代币账户余额之和
+    fn check_supply(&self) {
+        for mint in &self.mints {
+            let supply = Mint::unpack(&self.accounts[mint].1).unwrap().supply;
+            let total: u128 = self
+                .token_accounts
+                .iter()
+                .map(|address| self.spl_account(address))
+                .filter(|state| state.mint == *mint)
+                .map(|state| state.amount as u128)
+                .sum();
+            assert_eq!(
+                supply as u128, total,
+                "铸造账户 {} 的供应量与余额之和不一致",
+                mint
+            );
+        }
+    }
+
+    /// 登记分红的账户保持冻结且登记余额等于实际余额，登记总量等于检查点之和，
+    /// 金库余额足以支付所有已结算和待结算的分红
+    fn check_dividends(&self) {
+        let pool = DividendPool::unpack_account(&self.accounts[&self.pool].1).unwrap();
+        let mut tracked = 0u128;
+        let mut owed = pool.undistributed as u128;
+        for (address, checkpoint) in &self.checkpoints {
+            let state = self.spl_account(address);
+            let mut checkpoint =
+                DividendCheckpoint::unpack_account(&self.accounts[checkpoint].1).unwrap();
+            assert!(state.is_frozen(), "登记分红的代币账户 {} 没有冻结", address);
+            assert_eq!(
+                checkpoint.balance, state.amount,
+                "代币账户 {} 的登记余额与实际余额不一致",
+                address
+            );
+            tracked += checkpoint.balance as u128;
+            checkpoint.settle(pool.reward_per_token).unwrap();
+            owed += checkpoint.pending as u128;
+        }
+        assert_eq!(
+            tracked, pool.total_tracked as u128,
+            "分红池登记总量与检查点之和不一致"
+        );
+        let vault = self.spl_account(&self.vault()).amount;
+        assert!(
+            vault as u128 >= owed,
+            "分红金库余额 {} 不足以支付应付的分红 {}",
+            vault,
+            owed
+        );
+    }
+
+    /// 转账和存入分红成功时双方余额按数量变化
+    fn check_transfer_amounts(&self, op: &TokenOp, before: &BTreeMap<Pubkey, (Pubkey, Vec<u8>)>) {
+        let (from, to, amount) = match *op {
+            TokenOp::Transfer {
+                from, to, amount, ..
+            }
+            | TokenOp::SplTransfer {
+                from, to, amount, ..
+            } => (self.token_account(from), self.token_account(to), amount),
+            TokenOp::Deposit {
+                account, amount, ..
+            } => (self.token_account(account), self.vault(), amount),
+            _ => return,
+        };
+        if from == to {
+            return;
+        }
+        let balance = |accounts: &BTreeMap<Pubkey, (Pubkey, Vec<u8>)>, address: &Pubkey| {
+            SplAccount::unpack(&accounts[address].1).unwrap().amount as u128
+        };
+        assert_eq!(
+            balance(&self.accounts, &from) + amount as u128,
+            balance(before, &from),
+            "{:?} 转出账户的余额变化与数量不一致",
+            op
+        );
+        assert_eq!(
+            balance(&self.accounts, &to),
+            balance(before, &to) + amount as u128,
+            "{:?} 转入账户的余额变化与数量不一致",
+            op
+        );
+    }
+
+    /// 操作可能修改的代币账户和铸造账户
+    fn touched(&self, op: &TokenOp) -> (Vec<Pubkey>, Vec<Pubkey>) {
+        match *op {
+            TokenOp::MintTo { mint, account, .. } | TokenOp::Burn { mint, account, .. } => {
+                (vec![self.token_account(account)], vec![self.mint(mint)])
+            }
+            TokenOp::Transfer { from, to, .. } | TokenOp::SplTransfer { from, to, .. } => (
+                vec![self.token_account(from), self.token_account(to)],
+                Vec::new(),
+            ),
+            TokenOp::Freeze { account, .. }
+            | TokenOp::SetAccountAuthority { account, .. }
+            | TokenOp::SplApprove { account, .. } => {
+                (vec![self.token_account(account)], Vec::new())
+            }
+            TokenOp::SetMintAuthority { mint, .. } => (Vec::new(), vec![self.mint(mint)]),
+            TokenOp::Deposit { account, .. } | TokenOp::Claim { account, .. } => {
+                (vec![self.token_account(account), self.vault()], Vec::new())
+            }
+            TokenOp::Warp { .. } => (Vec::new(), Vec::new()),
+        }
+    }
+
+    /// 权限是否有权执行操作，`before` 为执行前的账户
+    fn authorized(
+        &self,
+        op: &TokenOp,
+        authority: &Pubkey,
+        before: &BTreeMap<Pubkey, (Pubkey, Vec<u8>)>,
+    ) -> bool {
+        let account =
+            |index: u8| SplAccount::unpack(&before[&self.token_account(index)].1).unwrap();
+        let mint = |index: u8| Mint::unpack(&before[&self.mint(index)].1).unwrap();
+        let authority = COption::Some(*authority);
+        match *op {
+            TokenOp::MintTo { mint: index, .. } => mint(index).mint_authority == authority,
+            TokenOp::Freeze { mint: index, .. } => mint(index).freeze_authority == authority,
+            TokenOp::SetMintAuthority {
+                mint: index,
+                freeze,
+                ..
+            } => {
+                let state = mint(index);
+                authority
+                    == if freeze {
+                        state.freeze_authority
+                    } else {
+                        state.mint_authority
+                    }
+            }
+            TokenOp::SetAccountAuthority {
+                account: index,
+                close,
+                ..
+            } => {
+                let state = account(index);
+                authority
+                    == COption::Some(if close {
+                        state.close_authority.unwrap_or(state.owner)
+                    } else {
+                        state.owner
+                    })
+            }
+            TokenOp::SplApprove { account: index, .. } | TokenOp::Claim { account: index, .. } => {
+                authority == COption::Some(account(index).owner)
+            }
+            TokenOp::Transfer {
+                from: index,
+                amount,
+                ..
+            }
+            | TokenOp::SplTransfer {
+                from: index,
+                amount,
+                ..
+            }
+            | TokenOp::Burn {
+                account: index,
+                amount,
+                ..
+            }
+            | TokenOp::Deposit {
+                account: index,
+                amount,
+                ..
+            } => {
+                let state = account(index);
+                authority == COption::Some(state.owner)
+                    || (state.delegate == authority && state.delegated_amount >= amount)
+            }
+            TokenOp::Warp { .. } => false,
+        }
+    }
+}
+
+/// 转账钩子校验 PDA 声明的额外账户：要求签名的铸造权限钱包，调用钩子时不能带签名权限
+fn hook_extra_accounts() -> Vec<ExtraAccountMeta> {
+    vec![ExtraAccountMeta {
+        pubkey: wallet(MINT_AUTHORITY),
+        is_signer: true,
+        is_writable: false,
+    }]
+}
+
+/// 地址是否为钱包，只有钱包能在顶层签名
+fn is_wallet(address: &Pubkey) -> bool {
+    (0..WALLET_COUNT).any(|index| wallet(index) == *address)
+}
+
+/// 序号对应的钱包地址
+fn wallet(index: u8) -> Pubkey {
+    Pubkey::new_from_array([index % WALLET_COUNT + 1; 32])
+}
@@ -0,0 +1,230 @@
+//! 转账钩子
+//!
+//! 铸造账户配置可以指定一个转账钩子程序（`transfer_hook_program`），由铸造权限通过
+//! `SetTransferHook` 设置。设置后每笔 `TransferTokens` 都必须附带钩子账户，缺少钩子程序、
+//! 校验 PDA 或声明的额外账户时拒绝；代币转移后以固定接口 [`TransferHookInstruction::Execute`]
+//! 调用钩子程序，钩子返回错误时整笔转账回滚，第三方合规程序可以借此否决转账。
+//!
+//! 钩子需要的额外账户由钩子程序自己维护的校验 PDA 声明（钩子程序下的
+//! `extra-account-metas` + 铸造账户），数据为 Borsh 编码的 [`ExtraAccountMetas`]。
+//! 转账时这些账户按声明顺序附带在钩子程序和校验 PDA 之后，地址不一致时拒绝转账。
+//! 额外账户不能与转出账户、转入账户、转出权限或校验 PDA 重复，否则拒绝转账；
+//! 调用钩子时所有账户都不带签名权限，钩子无法借用转出权限的签名转移代币。
+//!
+//! `Execute` 的账户：
+//!
+//! 0. `[]` 转出账户
+//! 1. `[]` 转入账户
+//! 2. `[]` 转出账户的所有者或委托人
+//! 3. `[]` 校验 PDA
+//! 4. 校验 PDA 声明的额外账户
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{config::transfer_tokens_with_config, TokenError};
+
+/// 校验 PDA 种子前缀，地址由钩子程序派生
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+/// 钩子程序需要实现的指令接口
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub enum TransferHookInstruction {
+    /// 代币转移后调用，返回错误即否决转账
+    Execute {
+        /// 转移数量
+        amount: u64,
+    },
+}
+
+impl TransferHookInstruction {
+    /// 从指令数据解析
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        Self::try_from_slice(input).map_err(|_| ProgramError::InvalidInstructionData)
+    }
+
+    /// 序列化为指令数据
+    pub fn pack(&self) -> Vec<u8> {
+        self.try_to_vec().expect("钩子指令序列化失败")
+    }
+}
+
+/// 钩子需要的一个额外账户
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtraAccountMeta {
+    /// 账户地址
+    pub pubkey: Pubkey,
+    /// 是否需要签名；仅为兼容布局保留，调用钩子时一律不带签名权限
+    pub is_signer: bool,
+    /// 是否可写
+    pub is_writable: bool,
+}
+
+impl From<&ExtraAccountMeta> for AccountMeta {
+    fn from(meta: &ExtraAccountMeta) -> Self {
+        Self {
+            pubkey: meta.pubkey,
+            is_signer: false,
+            is_writable: meta.is_writable,
+        }
+    }
+}
+
+/// 校验 PDA 的数据：钩子需要的额外账户列表
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtraAccountMetas {
+    /// 额外账户，按 `Execute` 中的顺序排列
+    pub accounts: Vec<ExtraAccountMeta>,
+}
+
+impl ExtraAccountMetas {
+    /// 从账户数据读取，末尾允许有未使用的字节
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        Ok(Self::deserialize(&mut &data[..])?)
+    }
+}
+
+/// 计算钩子程序下的校验 PDA 地址
+pub fn find_extra_account_metas_address(hook_program: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], hook_program)
+}
+
+/// 配置设置了钩子时 `TransferTokens` 必需的钩子账户，跟在系统程序之后
+pub struct TransferHookAccounts<'a, 'info> {
+    /// `[]` 转账钩子程序
+    pub program: &'a AccountInfo<'info>,
+    /// `[]` 校验 PDA
+    pub validation: &'a AccountInfo<'info>,
+    /// 校验 PDA 声明的额外账户
+    pub extra_accounts: &'a [AccountInfo<'info>],
+    /// 额外账户的声明
+    pub extra_metas: Vec<ExtraAccountMeta>,
+}
+
+impl<'a, 'info> TransferHookAccounts<'a, 'info> {
+    /// 解析钩子程序、校验 PDA 和声明的额外账户
+    pub fn parse(
+        accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'info>>,
+        hook_program: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<Self, ProgramError> {
+        let program = next_account_info(accounts_iter)?;
+        let validation = next_account_info(accounts_iter)?;
+        if program.key != hook_program || !program.executable {
+            return Err(TokenError::TransferHookMismatch.into());
+        }
+        let (address, _) = find_extra_account_metas_address(hook_program, mint);
+        if *validation.key != address {
+            return Err(TokenError::TransferHookMismatch.into());
+        }
+
+        // 钩子没有创建校验 PDA 时视为不需要额外账户
+        let extra_metas = if validation.data_is_empty() {
+            Vec::new()
+        } else {
+            if validation.owner != hook_program {
+                return Err(TokenError::TransferHookMismatch.into());
+            }
+            ExtraAccountMetas::unpack_from(&validation.data.borrow())?.accounts
+        };
+
+        let remaining = accounts_iter.as_slice();
+        let extra_accounts = remaining
+            .get(..extra_metas.len())
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        for (account, meta) in extra_accounts.iter().zip(&extra_metas) {
+            if *account.key != meta.pubkey {
+                return Err(TokenError::TransferHookMismatch.into());
+            }
+        }
+        for _ in 0..extra_metas.len() {
+            accounts_iter.next();
+        }
+
+        Ok(Self {
+            program,
+            validation,
+            extra_accounts,
+            extra_metas,
+        })
+    }
+
+    /// 调用钩子程序的 `Execute`，钩子返回错误时转账随之失败
+    ///
+    /// 额外账户与转出账户、转入账户、转出权限或校验 PDA 重复时返回 `TransferHookMismatch`。
+    pub fn execute(
+        &self,
+        from: &AccountInfo<'info>,
+        to: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+        amount: u64,
+    ) -> ProgramResult {
+        let reserved = [from.key, to.key, authority.key, self.validation.key];
+        if self
+            .extra_metas
+            .iter()
+            .any(|meta| reserved.contains(&&meta.pubkey))
+        {
+            return Err(TokenError::TransferHookMismatch.into());
+        }
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(*from.key, false),
+            AccountMeta::new_readonly(*to.key, false),
+            AccountMeta::new_readonly(*authority.key, false),
+            AccountMeta::new_readonly(*self.validation.key, false),
+        ];
+        accounts.extend(self.extra_metas.iter().map(AccountMeta::from));
+        let execute_ix = Instruction {
+            program_id: *self.program.key,
+            accounts,
+            data: TransferHookInstruction::Execute { amount }.pack(),
+        };
+
+        let mut account_infos = vec![
+            from.clone(),
+            to.clone(),
+            authority.clone(),
+            self.validation.clone(),
+        ];
+        account_infos.extend(self.extra_accounts.iter().cloned());
+        account_infos.push(self.program.clone());
+        invoke(&execute_ix, &account_infos)
+    }
+}
+
+/// 构建附带配置和钩子账户的 `TransferTokens` 指令
+///
+/// `extra_accounts` 需与钩子校验 PDA 中声明的额外账户一致。
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_tokens_with_hook(
+    program_id: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    from_owner: &Pubkey,
+    to_owner: &Pubkey,
+    hook_program: &Pubkey,
+    extra_accounts: &[ExtraAccountMeta],
+    amount: u64,
+) -> Instruction {
+    let (validation, _) = find_extra_account_metas_address(hook_program, mint);
+    let mut ix = transfer_tokens_with_config(
+        program_id, from, to, authority, mint, from_owner, to_owner, amount,
+    );
+    ix.accounts.extend([
+        AccountMeta::new_readonly(*hook_program, false),
+        AccountMeta::new_readonly(validation, false),
+    ]);
+    ix.accounts
+        .extend(extra_accounts.iter().map(AccountMeta::from));
+    ix
+}
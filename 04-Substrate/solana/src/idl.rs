@@ -0,0 +1,789 @@
+//! Anchor 兼容的 JSON IDL 生成
+//!
+//! IDL 直接从源码生成，客户端据此编解码指令、账户和事件，无需手写布局：
+//! - 指令：[`TokenInstruction`] 的变体及字段，账户取自变体文档中的“账户：”列表；
+//! - 程序账户：实现 [`ProgramAccount`] 的结构体，判别值为 2 字节账户头部；
+//! - 事件：[`TokenEvent`] 的变体，判别值为 Borsh 变体序号；
+//! - 错误：[`TokenError`] 的全部错误码和错误信息。
+//!
+//! 账本代币账户（[`TokenAccountState`](crate::TokenAccountState)）使用 `Pack` 定长布局，
+//! 不是 Borsh 编码，不包含在 IDL 中。
+//!
+//! 运行 `cargo run --bin idl` 输出 IDL，仓库中的 `idl/solana_spl_token.json` 由测试保持同步。
+//!
+//! [`TokenInstruction`]: crate::TokenInstruction
+//! [`TokenEvent`]: crate::events::TokenEvent
+
+use std::collections::VecDeque;
+
+use num_traits::FromPrimitive;
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    config::{LaunchWallet, MintConfig},
+    dividend::{DividendCheckpoint, DividendPool},
+    ledger::LedgerMint,
+    memo::MemoRequirement,
+    nft::{Collection, Metadata},
+    permit::PermitNonce,
+    sale::Sale,
+    schedule::ScheduledTransfer,
+    stake::{StakePool, UserStake},
+    state::ProgramAccount,
+    stream::Stream,
+    TokenError,
+};
+
+/// IDL 规范版本
+pub const IDL_SPEC: &str = "0.1.0";
+
+/// 解析类型定义时搜索的源文件
+const SOURCES: &[(&str, &str)] = &[
+    ("lib.rs", include_str!("lib.rs")),
+    ("config.rs", include_str!("config.rs")),
+    ("dividend.rs", include_str!("dividend.rs")),
+    ("events.rs", include_str!("events.rs")),
+    ("interest.rs", include_str!("interest.rs")),
+    ("ledger.rs", include_str!("ledger.rs")),
+    ("memo.rs", include_str!("memo.rs")),
+    ("nft.rs", include_str!("nft.rs")),
+    ("permit.rs", include_str!("permit.rs")),
+    ("sale.rs", include_str!("sale.rs")),
+    ("schedule.rs", include_str!("schedule.rs")),
+    ("stake.rs", include_str!("stake.rs")),
+    ("state.rs", include_str!("state.rs")),
+    ("stream.rs", include_str!("stream.rs")),
+];
+
+/// 程序 IDL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Idl {
+    /// 程序 ID
+    pub address: Pubkey,
+    /// 指令，按判别值排列
+    pub instructions: Vec<IdlInstruction>,
+    /// 程序账户，按账户类型排列
+    pub accounts: Vec<IdlProgramAccount>,
+    /// 事件，按判别值排列
+    pub events: Vec<IdlEvent>,
+    /// 错误，按错误码排列
+    pub errors: Vec<IdlError>,
+    /// 程序账户、事件及其引用的类型定义
+    pub types: Vec<IdlTypeDef>,
+}
+
+/// 指令
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlInstruction {
+    /// 指令名（snake_case）
+    pub name: String,
+    /// 文档
+    pub docs: Vec<String>,
+    /// 判别值，即指令数据的第一个字节
+    pub discriminator: u8,
+    /// 账户列表
+    pub accounts: Vec<IdlInstructionAccount>,
+    /// 参数
+    pub args: Vec<IdlField>,
+}
+
+/// 指令账户
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlInstructionAccount {
+    /// 账户名
+    pub name: String,
+    /// 文档
+    pub docs: Vec<String>,
+    /// 是否可写
+    pub writable: bool,
+    /// 是否需要签名
+    pub signer: bool,
+    /// 是否可以省略
+    pub optional: bool,
+}
+
+/// 程序账户
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlProgramAccount {
+    /// 账户结构体名
+    pub name: String,
+    /// 判别值：账户类型和布局版本
+    pub discriminator: [u8; 2],
+}
+
+/// 事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlEvent {
+    /// 事件名
+    pub name: String,
+    /// 判别值，即 Borsh 编码的变体序号
+    pub discriminator: u8,
+}
+
+/// 错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlError {
+    /// 错误码
+    pub code: u32,
+    /// 错误名
+    pub name: String,
+    /// 错误信息
+    pub msg: String,
+}
+
+/// 命名字段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlField {
+    /// 字段名
+    pub name: String,
+    /// 文档
+    pub docs: Vec<String>,
+    /// 字段类型
+    pub ty: IdlType,
+}
+
+/// 字段类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlType {
+    /// 基本类型，如 `u64`、`bool`、`string`、`pubkey`、`bytes`
+    Primitive(&'static str),
+    /// `Option<T>`
+    Option(Box<IdlType>),
+    /// `Vec<T>`
+    Vec(Box<IdlType>),
+    /// `[T; N]`
+    Array(Box<IdlType>, usize),
+    /// `types` 中定义的类型
+    Defined(String),
+}
+
+/// 类型定义
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlTypeDef {
+    /// 类型名
+    pub name: String,
+    /// 文档
+    pub docs: Vec<String>,
+    /// 结构体字段或枚举变体
+    pub kind: IdlTypeDefKind,
+}
+
+/// 类型定义的种类
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdlTypeDefKind {
+    /// 结构体
+    Struct(Vec<IdlField>),
+    /// 枚举
+    Enum(Vec<IdlVariant>),
+}
+
+/// 枚举变体
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlVariant {
+    /// 变体名
+    pub name: String,
+    /// 文档
+    pub docs: Vec<String>,
+    /// 命名字段，单元变体为空
+    pub fields: Vec<IdlField>,
+}
+
+impl Idl {
+    /// 从源码生成 IDL
+    ///
+    /// 注释格式错误或引用了找不到的类型时 panic。
+    pub fn generate() -> Self {
+        let instructions = parse_instructions();
+        let accounts = vec![
+            program_account::<StakePool>(),
+            program_account::<UserStake>(),
+            program_account::<ScheduledTransfer>(),
+            program_account::<Stream>(),
+            program_account::<PermitNonce>(),
+            program_account::<DividendPool>(),
+            program_account::<DividendCheckpoint>(),
+            program_account::<Sale>(),
+            program_account::<MintConfig>(),
+            program_account::<LaunchWallet>(),
+            program_account::<MemoRequirement>(),
+            program_account::<Metadata>(),
+            program_account::<Collection>(),
+            program_account::<LedgerMint>(),
+        ];
+        let event_variants = parse_enum(find_item("enum", "TokenEvent").body);
+        let events: Vec<IdlEvent> = event_variants
+            .iter()
+            .enumerate()
+            .map(|(index, variant)| IdlEvent {
+                name: variant.name.clone(),
+                discriminator: index as u8,
+            })
+            .collect();
+        let errors = (0..)
+            .map_while(TokenError::from_u32)
+            .map(|error| IdlError {
+                code: error as u32,
+                name: format!("{:?}", error),
+                msg: error.to_string(),
+            })
+            .collect();
+
+        let mut types = Vec::new();
+        let mut pending = VecDeque::new();
+        for account in &accounts {
+            pending.push_back(account.name.clone());
+        }
+        for variant in event_variants {
+            for field in &variant.fields {
+                collect_defined(&field.ty, &mut pending);
+            }
+            types.push(IdlTypeDef {
+                name: variant.name,
+                docs: variant.docs,
+                kind: IdlTypeDefKind::Struct(variant.fields),
+            });
+        }
+        for instruction in &instructions {
+            for arg in &instruction.args {
+                collect_defined(&arg.ty, &mut pending);
+            }
+        }
+        while let Some(name) = pending.pop_front() {
+            assert!(
+                !events.iter().any(|event: &IdlEvent| event.name == name),
+                "类型名与事件名重复：{}",
+                name
+            );
+            if types.iter().any(|def: &IdlTypeDef| def.name == name) {
+                continue;
+            }
+            let def = parse_type_def(&name);
+            match &def.kind {
+                IdlTypeDefKind::Struct(fields) => {
+                    for field in fields {
+                        collect_defined(&field.ty, &mut pending);
+                    }
+                }
+                IdlTypeDefKind::Enum(variants) => {
+                    for field in variants.iter().flat_map(|variant| &variant.fields) {
+                        collect_defined(&field.ty, &mut pending);
+                    }
+                }
+            }
+            types.push(def);
+        }
+
+        Self {
+            address: crate::id(),
+            instructions,
+            accounts,
+            events,
+            errors,
+            types,
+        }
+    }
+
+    /// 输出 Anchor 格式的 JSON，末尾带换行
+    pub fn to_json(&self) -> String {
+        let metadata = Json::Object(vec![
+            (
+                "name",
+                Json::string(env!("CARGO_PKG_NAME").replace('-', "_")),
+            ),
+            ("version", Json::string(env!("CARGO_PKG_VERSION"))),
+            ("spec", Json::string(IDL_SPEC)),
+            ("description", Json::string(env!("CARGO_PKG_DESCRIPTION"))),
+        ]);
+        let instructions = self.instructions.iter().map(|instruction| {
+            let mut object = vec![("name", Json::string(&instruction.name))];
+            push_docs(&mut object, &instruction.docs);
+            object.push(("discriminator", Json::bytes(&[instruction.discriminator])));
+            let accounts = instruction.accounts.iter().map(|account| {
+                let mut object = vec![("name", Json::string(&account.name))];
+                push_docs(&mut object, &account.docs);
+                for (key, value) in [
+                    ("writable", account.writable),
+                    ("signer", account.signer),
+                    ("optional", account.optional),
+                ] {
+                    if value {
+                        object.push((key, Json::Bool(true)));
+                    }
+                }
+                Json::Object(object)
+            });
+            object.push(("accounts", Json::Array(accounts.collect())));
+            object.push(("args", fields_json(&instruction.args)));
+            Json::Object(object)
+        });
+        let accounts = self.accounts.iter().map(|account| {
+            Json::Object(vec![
+                ("name", Json::string(&account.name)),
+                ("discriminator", Json::bytes(&account.discriminator)),
+            ])
+        });
+        let events = self.events.iter().map(|event| {
+            Json::Object(vec![
+                ("name", Json::string(&event.name)),
+                ("discriminator", Json::bytes(&[event.discriminator])),
+            ])
+        });
+        let errors = self.errors.iter().map(|error| {
+            Json::Object(vec![
+                ("code", Json::Number(error.code as u64)),
+                ("name", Json::string(&error.name)),
+                ("msg", Json::string(&error.msg)),
+            ])
+        });
+        let types = self.types.iter().map(|def| {
+            let mut object = vec![("name", Json::string(&def.name))];
+            push_docs(&mut object, &def.docs);
+            let ty = match &def.kind {
+                IdlTypeDefKind::Struct(fields) => Json::Object(vec![
+                    ("kind", Json::string("struct")),
+                    ("fields", fields_json(fields)),
+                ]),
+                IdlTypeDefKind::Enum(variants) => {
+                    let variants = variants.iter().map(|variant| {
+                        let mut object = vec![("name", Json::string(&variant.name))];
+                        push_docs(&mut object, &variant.docs);
+                        if !variant.fields.is_empty() {
+                            object.push(("fields", fields_json(&variant.fields)));
+                        }
+                        Json::Object(object)
+                    });
+                    Json::Object(vec![
+                        ("kind", Json::string("enum")),
+                        ("variants", Json::Array(variants.collect())),
+                    ])
+                }
+            };
+            object.push(("type", ty));
+            Json::Object(object)
+        });
+
+        let idl = Json::Object(vec![
+            ("address", Json::string(self.address.to_string())),
+            ("metadata", metadata),
+            ("instructions", Json::Array(instructions.collect())),
+            ("accounts", Json::Array(accounts.collect())),
+            ("events", Json::Array(events.collect())),
+            ("errors", Json::Array(errors.collect())),
+            ("types", Json::Array(types.collect())),
+        ]);
+        let mut out = String::new();
+        idl.write(&mut out, 0);
+        out.push('\n');
+        out
+    }
+}
+
+impl IdlInstruction {
+    /// 按名称查找账户
+    pub fn account(&self, name: &str) -> Option<&IdlInstructionAccount> {
+        self.accounts.iter().find(|account| account.name == name)
+    }
+}
+
+/// 程序账户的名称和判别值
+fn program_account<T: ProgramAccount>() -> IdlProgramAccount {
+    let path = std::any::type_name::<T>();
+    IdlProgramAccount {
+        name: path.rsplit("::").next().unwrap_or(path).to_string(),
+        discriminator: [T::ACCOUNT_TYPE as u8, T::VERSION],
+    }
+}
+
+/// 源码中的类型定义
+struct Item<'a> {
+    /// 定义前的文档
+    docs: Vec<String>,
+    /// 花括号内的各行
+    body: Vec<&'a str>,
+}
+
+/// 在 [`SOURCES`] 中查找 `pub struct` 或 `pub enum` 定义
+fn find_item(keyword: &str, name: &str) -> Item<'static> {
+    let header = format!("pub {} {} {{", keyword, name);
+    for (_, source) in SOURCES {
+        let lines: Vec<&str> = source.lines().collect();
+        let Some(start) = lines.iter().position(|line| *line == header) else {
+            continue;
+        };
+        let docs = lines[..start]
+            .iter()
+            .rev()
+            .skip_while(|line| line.starts_with("#["))
+            .map_while(|line| doc_text(line))
+            .collect::<Vec<_>>();
+        let body = lines[start + 1..]
+            .iter()
+            .take_while(|line| **line != "}")
+            .copied()
+            .collect();
+        return Item {
+            docs: tidy_docs(docs.into_iter().rev().collect()),
+            body,
+        };
+    }
+    panic!("找不到类型定义：pub {} {}", keyword, name);
+}
+
+/// 查找并解析结构体或枚举的类型定义
+fn parse_type_def(name: &str) -> IdlTypeDef {
+    let is_struct = SOURCES
+        .iter()
+        .any(|(_, source)| source.contains(&format!("pub struct {} {{", name)));
+    let (item, kind) = if is_struct {
+        let item = find_item("struct", name);
+        let fields = parse_fields(&item.body, "pub ");
+        (item, IdlTypeDefKind::Struct(fields))
+    } else {
+        let item = find_item("enum", name);
+        let variants = parse_enum(item.body.clone());
+        (item, IdlTypeDefKind::Enum(variants))
+    };
+    IdlTypeDef {
+        name: name.to_string(),
+        docs: item.docs,
+        kind,
+    }
+}
+
+/// 解析结构体的字段行 `[pub ]name: Type,`
+fn parse_fields(lines: &[&str], prefix: &str) -> Vec<IdlField> {
+    let mut fields = Vec::new();
+    let mut docs = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if let Some(doc) = doc_text(line) {
+            docs.push(doc);
+        } else if line.starts_with("#[") || line.is_empty() {
+            continue;
+        } else {
+            let field = line
+                .strip_prefix(prefix)
+                .and_then(|line| line.strip_suffix(','))
+                .and_then(|line| line.split_once(": "))
+                .unwrap_or_else(|| panic!("无法解析字段：{}", line));
+            fields.push(IdlField {
+                name: field.0.to_string(),
+                docs: tidy_docs(std::mem::take(&mut docs)),
+                ty: parse_type(field.1),
+            });
+        }
+    }
+    fields
+}
+
+/// 解析枚举的变体，支持单元变体和命名字段变体
+fn parse_enum(lines: Vec<&str>) -> Vec<IdlVariant> {
+    let mut variants = Vec::new();
+    let mut docs = Vec::new();
+    let mut lines = lines.into_iter();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(doc) = doc_text(line) {
+            docs.push(doc);
+        } else if line.starts_with("#[") || line.is_empty() {
+            continue;
+        } else if let Some(name) = line.strip_suffix(" {") {
+            let body: Vec<&str> = lines
+                .by_ref()
+                .take_while(|line| line.trim() != "},")
+                .collect();
+            variants.push(IdlVariant {
+                name: name.to_string(),
+                docs: tidy_docs(std::mem::take(&mut docs)),
+                fields: parse_fields(&body, ""),
+            });
+        } else if let Some(name) = line.strip_suffix(',').filter(|name| is_ident(name)) {
+            variants.push(IdlVariant {
+                name: name.to_string(),
+                docs: tidy_docs(std::mem::take(&mut docs)),
+                fields: Vec::new(),
+            });
+        } else {
+            panic!("无法解析枚举变体：{}", line);
+        }
+    }
+    variants
+}
+
+/// 解析 Rust 类型
+fn parse_type(ty: &str) -> IdlType {
+    let ty = ty.trim();
+    if let Some(inner) = ty
+        .strip_prefix("Option<")
+        .and_then(|ty| ty.strip_suffix('>'))
+    {
+        return IdlType::Option(Box::new(parse_type(inner)));
+    }
+    if let Some(inner) = ty.strip_prefix("Vec<").and_then(|ty| ty.strip_suffix('>')) {
+        return match parse_type(inner) {
+            IdlType::Primitive("u8") => IdlType::Primitive("bytes"),
+            inner => IdlType::Vec(Box::new(inner)),
+        };
+    }
+    if let Some(inner) = ty.strip_prefix('[').and_then(|ty| ty.strip_suffix(']')) {
+        let (element, len) = inner
+            .rsplit_once(';')
+            .unwrap_or_else(|| panic!("无法解析数组类型：{}", ty));
+        let len = len
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| panic!("数组长度必须是字面量：{}", ty));
+        return IdlType::Array(Box::new(parse_type(element)), len);
+    }
+    const PRIMITIVES: &[&str] = &[
+        "bool", "u8", "i8", "u16", "i16", "u32", "i32", "u64", "i64", "u128", "i128", "f32", "f64",
+    ];
+    if let Some(primitive) = PRIMITIVES.iter().find(|primitive| **primitive == ty) {
+        return IdlType::Primitive(primitive);
+    }
+    match ty {
+        "String" => IdlType::Primitive("string"),
+        "Pubkey" => IdlType::Primitive("pubkey"),
+        _ => {
+            let name = ty.rsplit("::").next().unwrap_or(ty);
+            assert!(is_ident(name), "无法解析类型：{}", ty);
+            IdlType::Defined(name.to_string())
+        }
+    }
+}
+
+/// 解析 [`TokenInstruction`](crate::TokenInstruction) 的变体及其账户注释
+fn parse_instructions() -> Vec<IdlInstruction> {
+    parse_enum(find_item("enum", "TokenInstruction").body)
+        .into_iter()
+        .enumerate()
+        .map(|(index, variant)| {
+            let mut docs = Vec::new();
+            let mut accounts = Vec::new();
+            for line in variant.docs {
+                match parse_account_line(&line) {
+                    Some((position, account)) => {
+                        assert_eq!(
+                            position,
+                            accounts.len(),
+                            "{} 的账户序号不连续：{}",
+                            variant.name,
+                            line
+                        );
+                        accounts.push(account);
+                    }
+                    None if line == "账户：" => {}
+                    None => docs.push(line),
+                }
+            }
+            assert!(!accounts.is_empty(), "{} 缺少“账户：”注释", variant.name);
+            assert!(
+                accounts
+                    .iter()
+                    .skip_while(|account| !account.optional)
+                    .all(|account| account.optional),
+                "{} 的可选账户必须位于末尾",
+                variant.name
+            );
+            IdlInstruction {
+                name: snake_case(&variant.name),
+                docs: tidy_docs(docs),
+                discriminator: index as u8,
+                accounts,
+                args: variant.fields,
+            }
+        })
+        .collect()
+}
+
+/// 解析账户注释行 ``N. `[flags]` `name` 说明``
+fn parse_account_line(line: &str) -> Option<(usize, IdlInstructionAccount)> {
+    let (position, rest) = line.split_once(". `[")?;
+    let position = position.parse().ok()?;
+    let (flags, rest) = rest
+        .split_once("]` `")
+        .unwrap_or_else(|| panic!("账户注释缺少名称：{}", line));
+    let (name, description) = rest
+        .split_once('`')
+        .unwrap_or_else(|| panic!("账户注释缺少名称：{}", line));
+    let mut account = IdlInstructionAccount {
+        name: name.to_string(),
+        docs: vec![description.trim().to_string()],
+        writable: false,
+        signer: false,
+        optional: false,
+    };
+    for flag in flags.split(", ").filter(|flag| !flag.is_empty()) {
+        match flag {
+            "signer" => account.signer = true,
+            "writable" => account.writable = true,
+            "optional" => account.optional = true,
+            _ => panic!("未知的账户标志 {}：{}", flag, line),
+        }
+    }
+    Some((position, account))
+}
+
+/// 把类型中引用的自定义类型加入待解析队列
+fn collect_defined(ty: &IdlType, pending: &mut VecDeque<String>) {
+    match ty {
+        IdlType::Primitive(_) => {}
+        IdlType::Option(inner) | IdlType::Vec(inner) | IdlType::Array(inner, _) => {
+            collect_defined(inner, pending)
+        }
+        IdlType::Defined(name) => pending.push_back(name.clone()),
+    }
+}
+
+/// 文档注释行的文本，非文档行返回 `None`
+fn doc_text(line: &str) -> Option<String> {
+    let text = line.trim().strip_prefix("///")?;
+    Some(text.strip_prefix(' ').unwrap_or(text).to_string())
+}
+
+/// 去掉首尾空行并合并连续空行
+fn tidy_docs(docs: Vec<String>) -> Vec<String> {
+    let mut tidy: Vec<String> = Vec::new();
+    for line in docs {
+        if line.is_empty() && tidy.last().map_or(true, |last| last.is_empty()) {
+            continue;
+        }
+        tidy.push(line);
+    }
+    if tidy.last().is_some_and(|last| last.is_empty()) {
+        tidy.pop();
+    }
+    tidy
+}
+
+/// 是否为 Rust 标识符
+fn is_ident(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// `PascalCase` 转为 `snake_case`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+fn push_docs(object: &mut Vec<(&'static str, Json)>, docs: &[String]) {
+    if !docs.is_empty() {
+        object.push(("docs", Json::Array(docs.iter().map(Json::string).collect())));
+    }
+}
+
+fn fields_json(fields: &[IdlField]) -> Json {
+    Json::Array(
+        fields
+            .iter()
+            .map(|field| {
+                let mut object = vec![("name", Json::string(&field.name))];
+                push_docs(&mut object, &field.docs);
+                object.push(("type", type_json(&field.ty)));
+                Json::Object(object)
+            })
+            .collect(),
+    )
+}
+
+fn type_json(ty: &IdlType) -> Json {
+    match ty {
+        IdlType::Primitive(name) => Json::string(*name),
+        IdlType::Option(inner) => Json::Object(vec![("option", type_json(inner))]),
+        IdlType::Vec(inner) => Json::Object(vec![("vec", type_json(inner))]),
+        IdlType::Array(inner, len) => Json::Object(vec![(
+            "array",
+            Json::Array(vec![type_json(inner), Json::Number(*len as u64)]),
+        )]),
+        IdlType::Defined(name) => Json::Object(vec![(
+            "defined",
+            Json::Object(vec![("name", Json::string(name))]),
+        )]),
+    }
+}
+
+/// 输出 IDL 所需的最小 JSON 值
+enum Json {
+    Bool(bool),
+    Number(u64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn string(value: impl AsRef<str>) -> Self {
+        Self::String(value.as_ref().to_string())
+    }
+
+    fn bytes(bytes: &[u8]) -> Self {
+        Self::Array(bytes.iter().map(|b| Self::Number(*b as u64)).collect())
+    }
+
+    /// 以两个空格缩进输出
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Self::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Self::Number(value) => out.push_str(&value.to_string()),
+            Self::String(value) => write_string(out, value),
+            Self::Array(items) if items.is_empty() => out.push_str("[]"),
+            Self::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    push_indent(out, indent + 1);
+                    item.write(out, indent + 1);
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push(']');
+            }
+            Self::Object(entries) if entries.is_empty() => out.push_str("{}"),
+            Self::Object(entries) => {
+                out.push('{');
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    out.push_str(if i == 0 { "\n" } else { ",\n" });
+                    push_indent(out, indent + 1);
+                    write_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,314 @@
+//! 指令构建
+//!
+//! 为核心代币指令构建完整的 [`Instruction`]，账户顺序与 `accounts.rs` 中的
+//! 解析顺序一致。质押相关指令的构建函数位于 [`crate::stake`]。
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::{
+    config::find_mint_config_address, dividend::find_dividend_pool_address,
+    memo::find_memo_requirement_address, AuthorityType, TokenInstruction,
+};
+
+/// 构建 `InitializeMint` 指令，`mint` 与 `payer` 都需要签名
+pub fn initialize_mint(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::InitializeMint {
+            decimals,
+            mint_authority: *mint_authority,
+            freeze_authority: freeze_authority.copied(),
+            non_transferable: false,
+            clawback_authority: None,
+            interest_rate: None,
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*mint, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// 构建创建不可转让代币的 `InitializeMint` 指令
+///
+/// 不可转让代币只能使用 Token-2022 程序，铸造账户启用 NonTransferable 扩展。
+pub fn initialize_non_transferable_mint(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    let mut ix = with_token_program(
+        initialize_mint(
+            program_id,
+            mint,
+            payer,
+            mint_authority,
+            freeze_authority,
+            decimals,
+        ),
+        &spl_token_2022::id(),
+    );
+    ix.data = TokenInstruction::InitializeMint {
+        decimals,
+        mint_authority: *mint_authority,
+        freeze_authority: freeze_authority.copied(),
+        non_transferable: true,
+        clawback_authority: None,
+        interest_rate: None,
+    }
+    .pack();
+    ix.accounts.push(AccountMeta::new(config, false));
+    ix
+}
+
+/// 构建创建可回收代币的 `InitializeMint` 指令
+///
+/// 冻结权限固定为配置 PDA；`token_program` 为 Token-2022 时铸造账户启用 PermanentDelegate 扩展。
+pub fn initialize_clawback_mint(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    mint_authority: &Pubkey,
+    clawback_authority: &Pubkey,
+    decimals: u8,
+) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    let mut ix = with_token_program(
+        initialize_mint(program_id, mint, payer, mint_authority, None, decimals),
+        token_program,
+    );
+    ix.data = TokenInstruction::InitializeMint {
+        decimals,
+        mint_authority: *mint_authority,
+        freeze_authority: None,
+        non_transferable: false,
+        clawback_authority: Some(*clawback_authority),
+        interest_rate: None,
+    }
+    .pack();
+    ix.accounts.push(AccountMeta::new(config, false));
+    ix
+}
+
+/// 构建创建计息代币的 `InitializeMint` 指令，利率权限为 `mint_authority`
+///
+/// `token_program` 为 Token-2022 时铸造账户启用 InterestBearingMint 扩展。
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_interest_bearing_mint(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+    rate_bps: i16,
+) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    let mut ix = with_token_program(
+        initialize_mint(
+            program_id,
+            mint,
+            payer,
+            mint_authority,
+            freeze_authority,
+            decimals,
+        ),
+        token_program,
+    );
+    ix.data = TokenInstruction::InitializeMint {
+        decimals,
+        mint_authority: *mint_authority,
+        freeze_authority: freeze_authority.copied(),
+        non_transferable: false,
+        clawback_authority: None,
+        interest_rate: Some(rate_bps),
+    }
+    .pack();
+    ix.accounts.push(AccountMeta::new(config, false));
+    ix
+}
+
+/// 把指令中的 SPL Token 程序替换为 `token_program`（如 Token-2022）
+///
+/// 适用于 `InitializeMint`、`MintTokens`、`TransferTokens`、`BurnTokens`、`SetAuthority`
+/// 和 `FreezeAccount`；`CreateTokenAccount`
+/// 的关联代币账户地址与代币程序有关，请使用 [`create_token_account_with_program`]。
+pub fn with_token_program(mut ix: Instruction, token_program: &Pubkey) -> Instruction {
+    for meta in ix.accounts.iter_mut() {
+        if meta.pubkey == spl_token::id() {
+            meta.pubkey = *token_program;
+        }
+    }
+    ix
+}
+
+/// 构建 `MintTokens` 指令
+pub fn mint_tokens(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    mint_authority: &Pubkey,
+    payer: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::MintTokens { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new_readonly(*payer, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `TransferTokens` 指令，附带转入账户的备注要求 PDA、铸造账户配置 PDA 和分红池 PDA
+///
+/// 铸造账户配置存在时改用 [`crate::config::transfer_tokens_with_config`]，
+/// 分红池存在时再用 [`crate::dividend::with_dividend_checkpoints`] 追加检查点。
+pub fn transfer_tokens(
+    program_id: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::TransferTokens { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*from, false),
+            AccountMeta::new(*to, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(find_memo_requirement_address(program_id, to).0, false),
+            AccountMeta::new_readonly(find_mint_config_address(program_id, mint).0, false),
+            AccountMeta::new(find_dividend_pool_address(program_id, mint).0, false),
+        ],
+    }
+}
+
+/// 构建 `CreateTokenAccount` 指令，为 `wallet` 创建 `mint` 的关联代币账户
+pub fn create_token_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    create_token_account_with_program(program_id, payer, wallet, mint, &spl_token::id())
+}
+
+/// 构建 `CreateTokenAccount` 指令，为由 `token_program` 持有的 `mint` 创建关联代币账户
+pub fn create_token_account_with_program(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::CreateTokenAccount.pack(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(wallet, mint, token_program),
+                false,
+            ),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+    }
+}
+
+/// 构建 `BurnTokens` 指令
+pub fn burn_tokens(
+    program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::BurnTokens { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `SetAuthority` 指令，`new_authority` 为 `None` 时放弃权限
+pub fn set_authority(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    current_authority: &Pubkey,
+    authority_type: AuthorityType,
+    new_authority: Option<&Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::SetAuthority {
+            authority_type,
+            new_authority: new_authority.copied(),
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*current_authority, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `FreezeAccount` 指令
+pub fn freeze_account(
+    program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    freeze_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::FreezeAccount.pack(),
+        accounts: vec![
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*freeze_authority, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
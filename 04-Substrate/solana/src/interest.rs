@@ -0,0 +1,408 @@
+//! 计息代币
+//!
+//! 计息代币的原始余额不变，只在显示时按连续复利折算利息：
+//! `ui_amount = amount × exp(∑ 利率 × 时长 / 一年) / 10^decimals`。
+//! 年利率以基点表示，在 `InitializeMint` 时写入铸造账户配置，之后由利率权限通过
+//! `SetInterestRate` 修改；配置保留最近 [`MAX_RATE_HISTORY`] 次利率变更，更早的区间
+//! 折算为累计的「基点 × 秒」，计算结果与完整历史一致。
+//!
+//! Token-2022 铸造账户另外启用 InterestBearingMint 扩展，扩展的利率权限为配置 PDA，
+//! `SetInterestRate` 同时更新扩展，钱包和浏览器可以直接按扩展显示利息。
+//!
+//! `GetBalance` 按上述规则计算代币账户的 UI 余额，通过返回数据返回 [`TokenBalance`]。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke_signed, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+use spl_token_2022::{
+    extension::{
+        interest_bearing_mint::{instruction::update_rate, InterestBearingConfig},
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account, Mint},
+};
+
+use crate::{
+    accounts::{
+        check_any_token_program, check_owned_writable_by, check_owner, check_program_account,
+        check_signer, check_writable,
+    },
+    config::{find_mint_config_address, MintConfig, MINT_CONFIG_SEED},
+    events::TokenEvent,
+    state::ProgramAccount,
+    TokenError, TokenInstruction,
+};
+
+/// 一年的秒数，与 Token-2022 InterestBearingMint 扩展一致
+pub const SECONDS_PER_YEAR: f64 = 60. * 60. * 24. * 365.24;
+/// 1 对应的基点数
+pub const ONE_IN_BASIS_POINTS: f64 = 10_000.;
+/// 配置中保留的利率变更条数
+pub const MAX_RATE_HISTORY: usize = 16;
+
+/// 一次利率变更
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateChange {
+    /// 生效时间（unix 时间戳）
+    pub timestamp: i64,
+    /// 年利率（基点），可以为负
+    pub rate_bps: i16,
+}
+
+impl RateChange {
+    /// 序列化后的长度
+    pub const LEN: usize = 8 + 2;
+}
+
+/// 计息参数与利率变更历史
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct InterestRate {
+    /// 利率权限，可以通过 `SetInterestRate` 修改利率
+    pub rate_authority: Pubkey,
+    /// 已移出历史的区间累计的「基点 × 秒」
+    pub folded_bps_seconds: i128,
+    /// 利率变更历史，按时间升序，最多 [`MAX_RATE_HISTORY`] 条
+    pub history: Vec<RateChange>,
+}
+
+impl InterestRate {
+    /// 序列化后的长度，按历史已满计算
+    pub const LEN: usize = 32 + 16 + 4 + MAX_RATE_HISTORY * RateChange::LEN;
+
+    /// 从 `now` 开始按 `rate_bps` 计息
+    pub fn new(rate_authority: Pubkey, rate_bps: i16, now: i64) -> Self {
+        Self {
+            rate_authority,
+            folded_bps_seconds: 0,
+            history: vec![RateChange {
+                timestamp: now,
+                rate_bps,
+            }],
+        }
+    }
+
+    /// 当前年利率（基点）
+    pub fn current_rate(&self) -> i16 {
+        self.history.last().map_or(0, |change| change.rate_bps)
+    }
+
+    /// 记录从 `now` 开始生效的新利率，历史已满时把最早的区间折算进累计值
+    pub fn record(&mut self, rate_bps: i16, now: i64) -> Result<(), ProgramError> {
+        // 同一时刻多次修改只保留最后一次
+        if let Some(last) = self.history.last_mut().filter(|last| now <= last.timestamp) {
+            last.rate_bps = rate_bps;
+            return Ok(());
+        }
+        self.history.push(RateChange {
+            timestamp: now,
+            rate_bps,
+        });
+        if self.history.len() > MAX_RATE_HISTORY {
+            let oldest = self.history.remove(0);
+            let span = self.history[0].timestamp - oldest.timestamp;
+            self.folded_bps_seconds = (oldest.rate_bps as i128)
+                .checked_mul(span as i128)
+                .and_then(|bps_seconds| self.folded_bps_seconds.checked_add(bps_seconds))
+                .ok_or(TokenError::Overflow)?;
+        }
+        Ok(())
+    }
+
+    /// 截至 `now` 累计的「基点 × 秒」
+    fn bps_seconds(&self, now: i64) -> Option<i128> {
+        let mut total = self.folded_bps_seconds;
+        for (index, change) in self.history.iter().enumerate() {
+            let end = self
+                .history
+                .get(index + 1)
+                .map_or(now, |next| next.timestamp.min(now));
+            let span = end.saturating_sub(change.timestamp).max(0);
+            total = total.checked_add((change.rate_bps as i128).checked_mul(span as i128)?)?;
+        }
+        Some(total)
+    }
+
+    /// 原始数量到 UI 数量的换算系数
+    fn total_scale(&self, decimals: u8, now: i64) -> Option<f64> {
+        let exponent = self.bps_seconds(now)? as f64 / SECONDS_PER_YEAR / ONE_IN_BASIS_POINTS;
+        Some(exponent.exp() / 10_f64.powi(decimals as i32))
+    }
+
+    /// 把原始数量换算为 `now` 时含利息的 UI 数量
+    pub fn amount_to_ui_amount(&self, amount: u64, decimals: u8, now: i64) -> Option<String> {
+        Some(((amount as f64) * self.total_scale(decimals, now)?).to_string())
+    }
+
+    /// 把 `now` 时含利息的 UI 数量换算回原始数量
+    pub fn ui_amount_to_amount(
+        &self,
+        ui_amount: &str,
+        decimals: u8,
+        now: i64,
+    ) -> Result<u64, ProgramError> {
+        let ui_amount = ui_amount
+            .parse::<f64>()
+            .map_err(|_| ProgramError::InvalidArgument)?;
+        let amount = ui_amount
+            / self
+                .total_scale(decimals, now)
+                .ok_or(ProgramError::InvalidArgument)?;
+        if amount.is_nan() || amount < 0. || amount > u64::MAX as f64 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(amount.round() as u64)
+    }
+}
+
+/// `GetBalance` 的返回数据
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct TokenBalance {
+    /// 原始余额（最小单位）
+    pub amount: u64,
+    /// 代币精度
+    pub decimals: u8,
+    /// 含利息的 UI 余额
+    pub ui_amount: String,
+}
+
+/// 按铸造账户计算 UI 余额
+///
+/// Token-2022 铸造账户优先使用 InterestBearingMint 扩展，其次使用配置中的利率，
+/// 都没有时按精度换算。
+pub fn ui_amount_for_mint(
+    mint_data: &[u8],
+    config: Option<&MintConfig>,
+    amount: u64,
+    now: i64,
+) -> Result<String, ProgramError> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+    let decimals = mint.base.decimals;
+    let ui_amount = match (
+        mint.get_extension::<InterestBearingConfig>(),
+        config.and_then(|config| config.interest_rate.as_ref()),
+    ) {
+        (Ok(extension), _) => extension.amount_to_ui_amount(amount, decimals, now),
+        (Err(_), Some(interest)) => interest.amount_to_ui_amount(amount, decimals, now),
+        (Err(_), None) => Some(spl_token::amount_to_ui_amount_string_trimmed(
+            amount, decimals,
+        )),
+    };
+    ui_amount.ok_or_else(|| TokenError::Overflow.into())
+}
+
+/// `SetInterestRate` 指令的账户
+pub struct SetInterestRateAccounts<'a, 'info> {
+    /// `[signer]` 利率权限
+    pub authority: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户配置 PDA
+    pub config: &'a AccountInfo<'info>,
+    /// `[]` 代币程序（SPL Token 或 Token-2022）
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetInterestRateAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            authority: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            config: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.authority)?;
+        check_any_token_program(ctx.token_program)?;
+        check_owned_writable_by(ctx.mint, ctx.token_program)?;
+        check_writable(ctx.config)?;
+        check_owner(ctx.config, program_id)?;
+        let (config, _) = find_mint_config_address(program_id, ctx.mint.key);
+        if config != *ctx.config.key {
+            return Err(TokenError::InvalidProgramAddress.into());
+        }
+
+        Ok(ctx)
+    }
+}
+
+/// `GetBalance` 指令的账户
+pub struct GetBalanceAccounts<'a, 'info> {
+    /// `[]` 代币账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户配置 PDA（可选），不存在时按精度换算
+    pub config: Option<&'a AccountInfo<'info>>,
+}
+
+impl<'a, 'info> GetBalanceAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let token_account = next_account_info(accounts_iter)?;
+        let mint = next_account_info(accounts_iter)?;
+        let (config_address, _) = find_mint_config_address(program_id, mint.key);
+        let config = accounts_iter
+            .next()
+            .filter(|config| *config.key == config_address && config.owner == program_id);
+
+        if *token_account.owner != spl_token::id() && *token_account.owner != spl_token_2022::id() {
+            return Err(TokenError::IncorrectAccountOwner.into());
+        }
+        check_owner(mint, token_account.owner)?;
+
+        Ok(Self {
+            token_account,
+            mint,
+            config,
+        })
+    }
+}
+
+/// 处理 `SetInterestRate`：记录新利率，Token-2022 铸造账户同时更新 InterestBearingMint 扩展
+pub fn process_set_interest_rate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rate_bps: i16,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let SetInterestRateAccounts {
+        authority,
+        mint,
+        config: config_account,
+        token_program,
+    } = SetInterestRateAccounts::parse(program_id, accounts)?;
+
+    let mut config = MintConfig::unpack_account(&config_account.data.borrow())?;
+    let interest = config
+        .interest_rate
+        .as_mut()
+        .ok_or(TokenError::InterestNotEnabled)?;
+    if interest.rate_authority != *authority.key {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    interest.record(rate_bps, now)?;
+
+    let has_extension = StateWithExtensions::<Mint>::unpack(&mint.data.borrow())?
+        .get_extension::<InterestBearingConfig>()
+        .is_ok();
+    if has_extension {
+        invoke_signed(
+            &update_rate(
+                token_program.key,
+                mint.key,
+                config_account.key,
+                &[],
+                rate_bps,
+            )?,
+            &[mint.clone(), config_account.clone()],
+            &[&[MINT_CONFIG_SEED, mint.key.as_ref(), &[config.bump]]],
+        )?;
+    }
+
+    config.pack_account(&mut config_account.data.borrow_mut())?;
+
+    TokenEvent::InterestRateUpdated {
+        mint: *mint.key,
+        rate_bps,
+        timestamp: now,
+    }
+    .emit();
+
+    log_msg!("年利率已更新为 {} 个基点", rate_bps);
+
+    Ok(())
+}
+
+/// 处理 `GetBalance`：计算含利息的 UI 余额并通过返回数据返回 [`TokenBalance`]
+pub fn process_get_balance(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let GetBalanceAccounts {
+        token_account,
+        mint,
+        config: config_account,
+    } = GetBalanceAccounts::parse(program_id, accounts)?;
+
+    let account = StateWithExtensions::<Account>::unpack(&token_account.data.borrow())?.base;
+    if account.mint != *mint.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+    let config = match config_account {
+        Some(config_account) => Some(MintConfig::unpack_account(&config_account.data.borrow())?),
+        None => None,
+    };
+
+    let mint_data = mint.data.borrow();
+    let balance = TokenBalance {
+        amount: account.amount,
+        decimals: StateWithExtensions::<Mint>::unpack(&mint_data)?
+            .base
+            .decimals,
+        ui_amount: ui_amount_for_mint(
+            &mint_data,
+            config.as_ref(),
+            account.amount,
+            Clock::get()?.unix_timestamp,
+        )?,
+    };
+    set_return_data(&balance.try_to_vec()?);
+
+    log_msg!("余额: {}", balance.ui_amount);
+
+    Ok(())
+}
+
+/// 构建 `SetInterestRate` 指令，`authority` 需为利率权限
+pub fn set_interest_rate(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    rate_bps: i16,
+) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::SetInterestRate { rate_bps }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+    }
+}
+
+/// 构建 `GetBalance` 指令，附带铸造账户配置 PDA
+pub fn get_balance(program_id: &Pubkey, token_account: &Pubkey, mint: &Pubkey) -> Instruction {
+    let (config, _) = find_mint_config_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::GetBalance.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(config, false),
+        ],
+    }
+}
@@ -0,0 +1,837 @@
+//! 原生账本模式
+//!
+//! 内部积分等场景需要数百万个小额余额，SPL 代币账户的租金成本过高。原生账本模式把余额保存在
+//! 本程序持有的 PDA 中，铸造、转账、授权、冻结和销毁都在本程序内完成，不调用 SPL Token：
+//!
+//! - 账本铸造账户（[`LedgerMint`]）由新的密钥对创建，记录铸造权限、冻结权限、供应量和精度
+//! - 账本代币账户（`ledger_account` + 铸造账户 + 所有者）使用 [`TokenAccountState`]，
+//!   以 `Pack` 的定长布局存储，与其他程序账户一样带有账户类型和版本头部
+//!
+//! 各指令的语义与 SPL 路径一致（测试中用同一组场景校验两种路径），
+//! 区别是授权数量为 0 时撤销委托，冻结和解冻共用 `LedgerSetFrozen`。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    accounts::{
+        check_owner, check_program_account, check_signer, check_system_program, check_writable,
+    },
+    create_pda_account,
+    events::TokenEvent,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    TokenAccountState, TokenError, TokenInstruction,
+};
+
+/// 账本代币账户 PDA 种子前缀
+pub const LEDGER_ACCOUNT_SEED: &[u8] = b"ledger_account";
+
+/// 账本铸造账户
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct LedgerMint {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 铸造权限，`None` 表示供应量已固定
+    pub mint_authority: Option<Pubkey>,
+    /// 冻结权限
+    pub freeze_authority: Option<Pubkey>,
+    /// 供应量
+    pub supply: u64,
+    /// 精度
+    pub decimals: u8,
+}
+
+impl ProgramAccount for LedgerMint {
+    const ACCOUNT_TYPE: AccountType = AccountType::LedgerMint;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + (1 + 32) + (1 + 32) + 8 + 1;
+}
+
+/// 账本代币账户的布局版本
+const LEDGER_ACCOUNT_VERSION: u8 = 1;
+
+impl Sealed for TokenAccountState {}
+
+impl Pack for TokenAccountState {
+    /// 头部 2 字节，其后依次为 is_initialized、mint、owner、amount、delegate（1 字节标记 + 32 字节）、
+    /// delegated_amount、is_frozen、is_native 和 rent_exempt_reserve
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 + 32 + 8 + 33 + 8 + 1 + 1 + 8;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut offset = 0;
+        let mut put = |bytes: &[u8]| {
+            dst[offset..offset + bytes.len()].copy_from_slice(bytes);
+            offset += bytes.len();
+        };
+        put(&[AccountType::LedgerAccount as u8, LEDGER_ACCOUNT_VERSION]);
+        put(&[self.is_initialized as u8]);
+        put(self.mint.as_ref());
+        put(self.owner.as_ref());
+        put(&self.amount.to_le_bytes());
+        match &self.delegate {
+            Some(delegate) => {
+                put(&[1]);
+                put(delegate.as_ref());
+            }
+            None => put(&[0; 33]),
+        }
+        put(&self.delegated_amount.to_le_bytes());
+        put(&[self.is_frozen as u8, self.is_native as u8]);
+        put(&self.rent_exempt_reserve.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN || src[0] != AccountType::LedgerAccount as u8 {
+            return Err(TokenError::AccountTypeMismatch.into());
+        }
+        if src[1] != LEDGER_ACCOUNT_VERSION {
+            return Err(TokenError::AccountVersionMismatch.into());
+        }
+        let mut offset = ACCOUNT_HEADER_LEN;
+        let mut take = |len: usize| {
+            let bytes = &src[offset..offset + len];
+            offset += len;
+            bytes
+        };
+        let bool_at = |byte: u8| match byte {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ProgramError::InvalidAccountData),
+        };
+        let u64_at = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().expect("长度固定"));
+        let pubkey_at = |bytes: &[u8]| Pubkey::try_from(bytes).expect("长度固定");
+
+        let is_initialized = bool_at(take(1)[0])?;
+        let mint = pubkey_at(take(32));
+        let owner = pubkey_at(take(32));
+        let amount = u64_at(take(8));
+        let delegate = match take(1)[0] {
+            0 => {
+                take(32);
+                None
+            }
+            1 => Some(pubkey_at(take(32))),
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(Self {
+            is_initialized,
+            mint,
+            owner,
+            amount,
+            delegate,
+            delegated_amount: u64_at(take(8)),
+            is_frozen: bool_at(take(1)[0])?,
+            is_native: bool_at(take(1)[0])?,
+            rent_exempt_reserve: u64_at(take(8)),
+        })
+    }
+}
+
+/// 计算账本代币账户 PDA 地址
+pub fn find_ledger_account_address(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LEDGER_ACCOUNT_SEED, mint.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// 读取属于 `mint` 的账本代币账户，账户所有者由调用方校验
+fn unpack_ledger_account(
+    account: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<TokenAccountState, ProgramError> {
+    let state = TokenAccountState::unpack(&account.data.borrow())?;
+    if state.mint != *mint {
+        return Err(TokenError::MintMismatch.into());
+    }
+    Ok(state)
+}
+
+/// 校验 `authority` 可以动用 `state` 中的 `amount` 个代币：所有者直接通过，委托人扣减授权额度
+fn spend(state: &mut TokenAccountState, authority: &AccountInfo, amount: u64) -> ProgramResult {
+    if state.is_frozen {
+        return Err(TokenError::AccountFrozen.into());
+    }
+    if state.amount < amount {
+        return Err(TokenError::InsufficientBalance.into());
+    }
+    if *authority.key == state.owner {
+        return Ok(());
+    }
+    if state.delegate != Some(*authority.key) {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    state.delegated_amount = state
+        .delegated_amount
+        .checked_sub(amount)
+        .ok_or(TokenError::InsufficientPrivileges)?;
+    if state.delegated_amount == 0 {
+        state.delegate = None;
+    }
+    Ok(())
+}
+
+/// `InitializeLedgerMint` 指令的账户
+pub struct InitializeLedgerMintAccounts<'a, 'info> {
+    /// `[signer, writable]` 新的账本铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[signer, writable]` 付费账户
+    pub payer: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> InitializeLedgerMintAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            mint: next_account_info(accounts_iter)?,
+            payer: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.mint)?;
+        check_writable(ctx.mint)?;
+        check_owner(ctx.mint, &system_program::id())?;
+        check_signer(ctx.payer)?;
+        check_writable(ctx.payer)?;
+        check_system_program(ctx.system_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `InitializeLedgerMint`：创建本程序持有的账本铸造账户
+pub fn process_initialize_ledger_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    decimals: u8,
+    mint_authority: Pubkey,
+    freeze_authority: Option<Pubkey>,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let InitializeLedgerMintAccounts {
+        mint,
+        payer,
+        system_program,
+    } = InitializeLedgerMintAccounts::parse(accounts)?;
+
+    invoke(
+        &system_instruction::create_account(
+            payer.key,
+            mint.key,
+            Rent::get()?.minimum_balance(LedgerMint::LEN),
+            LedgerMint::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), mint.clone(), system_program.clone()],
+    )?;
+    LedgerMint {
+        is_initialized: true,
+        mint_authority: Some(mint_authority),
+        freeze_authority,
+        supply: 0,
+        decimals,
+    }
+    .pack_account(&mut mint.data.borrow_mut())?;
+
+    TokenEvent::MintInitialized {
+        mint: *mint.key,
+        decimals,
+        mint_authority,
+        freeze_authority,
+    }
+    .emit();
+
+    log_msg!("账本铸造账户初始化完成，精度: {}", decimals);
+
+    Ok(())
+}
+
+/// `CreateLedgerAccount` 指令的账户
+pub struct CreateLedgerAccountAccounts<'a, 'info> {
+    /// `[signer, writable]` 付费账户
+    pub payer: &'a AccountInfo<'info>,
+    /// `[]` 所有者钱包
+    pub owner: &'a AccountInfo<'info>,
+    /// `[]` 账本铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 账本代币账户 PDA
+    pub account: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateLedgerAccountAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            payer: next_account_info(accounts_iter)?,
+            owner: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            account: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.payer)?;
+        check_writable(ctx.payer)?;
+        check_owner(ctx.mint, program_id)?;
+        check_writable(ctx.account)?;
+        check_system_program(ctx.system_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `CreateLedgerAccount`：为所有者创建账本代币账户
+pub fn process_create_ledger_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let CreateLedgerAccountAccounts {
+        payer,
+        owner,
+        mint,
+        account,
+        system_program,
+    } = CreateLedgerAccountAccounts::parse(program_id, accounts)?;
+
+    LedgerMint::unpack_account(&mint.data.borrow())?;
+    let (address, bump) = find_ledger_account_address(program_id, mint.key, owner.key);
+    if address != *account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if !account.data_is_empty() {
+        return Err(TokenError::AccountAlreadyInitialized.into());
+    }
+    create_pda_account(
+        payer,
+        account,
+        system_program,
+        program_id,
+        TokenAccountState::LEN,
+        &[
+            LEDGER_ACCOUNT_SEED,
+            mint.key.as_ref(),
+            owner.key.as_ref(),
+            &[bump],
+        ],
+    )?;
+    let state = TokenAccountState {
+        is_initialized: true,
+        mint: *mint.key,
+        owner: *owner.key,
+        ..Default::default()
+    };
+    TokenAccountState::pack(state, &mut account.data.borrow_mut())?;
+
+    TokenEvent::TokenAccountCreated {
+        wallet: *owner.key,
+        mint: *mint.key,
+        account: *account.key,
+    }
+    .emit();
+
+    log_msg!("创建账本代币账户: {}", account.key);
+
+    Ok(())
+}
+
+/// `LedgerMintTo` 和 `LedgerSetFrozen` 指令的账户：由铸造账户上的权限操作代币账户
+pub struct LedgerMintAuthorityAccounts<'a, 'info> {
+    /// `[writable]` 账本铸造账户（`LedgerSetFrozen` 中只读）
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 账本代币账户
+    pub account: &'a AccountInfo<'info>,
+    /// `[signer]` 铸造权限或冻结权限
+    pub authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> LedgerMintAuthorityAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            mint: next_account_info(accounts_iter)?,
+            account: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+        };
+
+        check_owner(ctx.mint, program_id)?;
+        check_owner(ctx.account, program_id)?;
+        check_writable(ctx.account)?;
+        check_signer(ctx.authority)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `LedgerMintTo`：铸造权限向账本代币账户铸造代币
+pub fn process_ledger_mint_to(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let LedgerMintAuthorityAccounts {
+        mint,
+        account,
+        authority,
+    } = LedgerMintAuthorityAccounts::parse(program_id, accounts)?;
+    check_writable(mint)?;
+
+    let mut mint_state = LedgerMint::unpack_account(&mint.data.borrow())?;
+    let mut state = unpack_ledger_account(account, mint.key)?;
+    if mint_state.mint_authority != Some(*authority.key) {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    if state.is_frozen {
+        return Err(TokenError::AccountFrozen.into());
+    }
+    mint_state.supply = mint_state
+        .supply
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    state.amount = state
+        .amount
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    mint_state.pack_account(&mut mint.data.borrow_mut())?;
+    TokenAccountState::pack(state, &mut account.data.borrow_mut())?;
+
+    TokenEvent::Minted {
+        mint: *mint.key,
+        account: *account.key,
+        amount,
+    }
+    .emit();
+
+    log_msg!("账本铸造 {} 个代币到账户 {}", amount, account.key);
+
+    Ok(())
+}
+
+/// 处理 `LedgerSetFrozen`：冻结权限冻结或解冻账本代币账户
+pub fn process_ledger_set_frozen(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    frozen: bool,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let LedgerMintAuthorityAccounts {
+        mint,
+        account,
+        authority,
+    } = LedgerMintAuthorityAccounts::parse(program_id, accounts)?;
+
+    let mint_state = LedgerMint::unpack_account(&mint.data.borrow())?;
+    let mut state = unpack_ledger_account(account, mint.key)?;
+    if mint_state.freeze_authority != Some(*authority.key) {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    state.is_frozen = frozen;
+    TokenAccountState::pack(state, &mut account.data.borrow_mut())?;
+
+    if frozen {
+        TokenEvent::AccountFrozen {
+            mint: *mint.key,
+            account: *account.key,
+        }
+        .emit();
+        log_msg!("账本账户 {} 已冻结", account.key);
+    } else {
+        TokenEvent::AccountThawed {
+            mint: *mint.key,
+            account: *account.key,
+        }
+        .emit();
+        log_msg!("账本账户 {} 已解冻", account.key);
+    }
+
+    Ok(())
+}
+
+/// `LedgerTransfer` 指令的账户
+pub struct LedgerTransferAccounts<'a, 'info> {
+    /// `[writable]` 转出账本代币账户
+    pub from: &'a AccountInfo<'info>,
+    /// `[writable]` 转入账本代币账户
+    pub to: &'a AccountInfo<'info>,
+    /// `[signer]` 转出账户的所有者或委托人
+    pub authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> LedgerTransferAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            from: next_account_info(accounts_iter)?,
+            to: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+        };
+
+        check_owner(ctx.from, program_id)?;
+        check_writable(ctx.from)?;
+        check_owner(ctx.to, program_id)?;
+        check_writable(ctx.to)?;
+        check_signer(ctx.authority)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `LedgerTransfer`：在同一铸造账户的两个账本代币账户之间转账
+pub fn process_ledger_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let LedgerTransferAccounts {
+        from,
+        to,
+        authority,
+    } = LedgerTransferAccounts::parse(program_id, accounts)?;
+
+    let mut from_state = TokenAccountState::unpack(&from.data.borrow())?;
+    let mut to_state = unpack_ledger_account(to, &from_state.mint)?;
+    spend(&mut from_state, authority, amount)?;
+    if to_state.is_frozen {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    // 转给自己时余额不变，只扣减委托额度
+    if from.key != to.key {
+        from_state.amount -= amount;
+        to_state.amount = to_state
+            .amount
+            .checked_add(amount)
+            .ok_or(TokenError::Overflow)?;
+        TokenAccountState::pack(to_state, &mut to.data.borrow_mut())?;
+    }
+    TokenAccountState::pack(from_state, &mut from.data.borrow_mut())?;
+
+    TokenEvent::Transferred {
+        mint: from_state.mint,
+        from: *from.key,
+        to: *to.key,
+        amount,
+    }
+    .emit();
+
+    log_msg!("账本转移 {} 个代币", amount);
+
+    Ok(())
+}
+
+/// `LedgerApprove` 指令的账户
+pub struct LedgerApproveAccounts<'a, 'info> {
+    /// `[writable]` 账本代币账户
+    pub account: &'a AccountInfo<'info>,
+    /// `[]` 委托人
+    pub delegate: &'a AccountInfo<'info>,
+    /// `[signer]` 账户所有者
+    pub owner: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> LedgerApproveAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            account: next_account_info(accounts_iter)?,
+            delegate: next_account_info(accounts_iter)?,
+            owner: next_account_info(accounts_iter)?,
+        };
+
+        check_owner(ctx.account, program_id)?;
+        check_writable(ctx.account)?;
+        check_signer(ctx.owner)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `LedgerApprove`：所有者授权委托人动用最多 `amount` 个代币，数量为 0 时撤销委托
+pub fn process_ledger_approve(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let LedgerApproveAccounts {
+        account,
+        delegate,
+        owner,
+    } = LedgerApproveAccounts::parse(program_id, accounts)?;
+
+    let mut state = TokenAccountState::unpack(&account.data.borrow())?;
+    if state.owner != *owner.key {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+    if state.is_frozen {
+        return Err(TokenError::AccountFrozen.into());
+    }
+    state.delegate = (amount > 0).then_some(*delegate.key);
+    state.delegated_amount = amount;
+    TokenAccountState::pack(state, &mut account.data.borrow_mut())?;
+
+    TokenEvent::LedgerDelegateApproved {
+        account: *account.key,
+        delegate: state.delegate,
+        amount,
+    }
+    .emit();
+
+    log_msg!("账本账户 {} 授权 {} 个代币", account.key, amount);
+
+    Ok(())
+}
+
+/// `LedgerBurn` 指令的账户
+pub struct LedgerBurnAccounts<'a, 'info> {
+    /// `[writable]` 账本代币账户
+    pub account: &'a AccountInfo<'info>,
+    /// `[writable]` 账本铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[signer]` 账户所有者或委托人
+    pub authority: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> LedgerBurnAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            account: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            authority: next_account_info(accounts_iter)?,
+        };
+
+        check_owner(ctx.account, program_id)?;
+        check_writable(ctx.account)?;
+        check_owner(ctx.mint, program_id)?;
+        check_writable(ctx.mint)?;
+        check_signer(ctx.authority)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `LedgerBurn`：销毁账本代币账户中的代币并减少供应量
+pub fn process_ledger_burn(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let LedgerBurnAccounts {
+        account,
+        mint,
+        authority,
+    } = LedgerBurnAccounts::parse(program_id, accounts)?;
+
+    let mut mint_state = LedgerMint::unpack_account(&mint.data.borrow())?;
+    let mut state = unpack_ledger_account(account, mint.key)?;
+    spend(&mut state, authority, amount)?;
+    state.amount -= amount;
+    mint_state.supply = mint_state
+        .supply
+        .checked_sub(amount)
+        .ok_or(TokenError::Overflow)?;
+    TokenAccountState::pack(state, &mut account.data.borrow_mut())?;
+    mint_state.pack_account(&mut mint.data.borrow_mut())?;
+
+    TokenEvent::Burned {
+        mint: *mint.key,
+        account: *account.key,
+        amount,
+    }
+    .emit();
+
+    log_msg!("账本销毁 {} 个代币", amount);
+
+    Ok(())
+}
+
+/// 构建 `InitializeLedgerMint` 指令，`mint` 为新的密钥对
+pub fn initialize_ledger_mint(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    mint_authority: &Pubkey,
+    freeze_authority: Option<&Pubkey>,
+    decimals: u8,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::InitializeLedgerMint {
+            decimals,
+            mint_authority: *mint_authority,
+            freeze_authority: freeze_authority.copied(),
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*mint, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// 构建 `CreateLedgerAccount` 指令
+pub fn create_ledger_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let (account, _) = find_ledger_account_address(program_id, mint, owner);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::CreateLedgerAccount.pack(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// 构建 `LedgerMintTo` 指令
+pub fn ledger_mint_to(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    account: &Pubkey,
+    mint_authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::LedgerMintTo { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+        ],
+    }
+}
+
+/// 构建 `LedgerTransfer` 指令
+pub fn ledger_transfer(
+    program_id: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::LedgerTransfer { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*from, false),
+            AccountMeta::new(*to, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    }
+}
+
+/// 构建 `LedgerApprove` 指令，`amount` 为 0 时撤销委托
+pub fn ledger_approve(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    delegate: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::LedgerApprove { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*delegate, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+    }
+}
+
+/// 构建 `LedgerSetFrozen` 指令
+pub fn ledger_set_frozen(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    mint: &Pubkey,
+    freeze_authority: &Pubkey,
+    frozen: bool,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::LedgerSetFrozen { frozen }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(*freeze_authority, true),
+        ],
+    }
+}
+
+/// 构建 `LedgerBurn` 指令
+pub fn ledger_burn(
+    program_id: &Pubkey,
+    account: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::LedgerBurn { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    }
+}
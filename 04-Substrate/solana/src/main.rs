@@ -1,7 +1,573 @@
-// 这个文件仅用于库项目的构建
-// 实际的程序逻辑在 lib.rs 中
+//! spl-token-admin：代币管理命令行工具
+//!
+//! 使用本程序的指令构建函数组装交易，用密钥文件签名后发送到指定的 RPC 节点，
+//! 可连接开发网、主网或本地的 `solana-test-validator`。
+//!
+//! 需要启用 `cli` feature 构建：`cargo run --features cli --bin spl-token-admin -- --help`
+
+use std::{error::Error, path::PathBuf, process};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+use solana_spl_token::{
+    config, dividend, get_associated_token_address, hook, instruction, interest, memo,
+    state::ProgramAccount, AuthorityType,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token::state::{Account as SplAccount, AccountState};
+use spl_token_2022::{
+    extension::{non_transferable::NonTransferable, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint as Mint2022,
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+/// SPL 代币管理工具
+#[derive(Parser)]
+#[command(name = "spl-token-admin", version, about)]
+struct Cli {
+    /// RPC 节点地址
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// 签名并支付手续费的密钥文件，默认为 ~/.config/solana/id.json
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+
+    /// 代币程序 ID，默认为构建时所选集群的程序 ID
+    #[arg(long, global = true, default_value_t = solana_spl_token::id())]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 创建新的代币铸造账户
+    CreateMint {
+        /// 代币精度
+        #[arg(long, default_value_t = 9)]
+        decimals: u8,
+        /// 铸造权限，默认为签名账户
+        #[arg(long)]
+        mint_authority: Option<Pubkey>,
+        /// 冻结权限，默认不设置
+        #[arg(long)]
+        freeze_authority: Option<Pubkey>,
+        /// 铸造账户的密钥文件，默认随机生成
+        #[arg(long)]
+        mint_keypair: Option<PathBuf>,
+        /// 创建不可转让（灵魂绑定）代币：使用 Token-2022 程序并启用 NonTransferable 扩展，只能铸造和销毁
+        #[arg(long)]
+        non_transferable: bool,
+    },
+    /// 创建关联代币账户
+    CreateAccount {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 钱包地址，默认为签名账户
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// 铸造代币，签名账户需为铸造权限
+    Mint {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 铸造数量（最小单位）
+        amount: u64,
+        /// 接收代币的钱包地址，默认为签名账户
+        #[arg(long)]
+        recipient: Option<Pubkey>,
+    },
+    /// 从签名账户转出代币
+    Transfer {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 转移数量（最小单位）
+        amount: u64,
+        /// 接收代币的钱包地址
+        recipient: Pubkey,
+        /// 转账备注，接收账户要求备注时必须提供
+        #[arg(long)]
+        memo: Option<String>,
+    },
+    /// 销毁签名账户持有的代币
+    Burn {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 销毁数量（最小单位）
+        amount: u64,
+    },
+    /// 查询代币余额
+    Balance {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 钱包地址，默认为签名账户
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// 变更铸造账户或代币账户的权限，签名账户需为当前权限
+    SetAuthority {
+        /// 铸造账户或代币账户
+        account: Pubkey,
+        /// 权限类型
+        authority_type: AuthorityTypeArg,
+        /// 新的权限账户
+        #[arg(long, required_unless_present = "revoke")]
+        new_authority: Option<Pubkey>,
+        /// 永久放弃该权限
+        #[arg(long, conflicts_with = "new_authority")]
+        revoke: bool,
+    },
+    /// 冻结钱包的关联代币账户，签名账户需为冻结权限
+    Freeze {
+        /// 铸造账户
+        mint: Pubkey,
+        /// 被冻结的钱包地址
+        owner: Pubkey,
+    },
+}
+
+/// 命令行中的权限类型
+#[derive(Clone, Copy, ValueEnum)]
+enum AuthorityTypeArg {
+    /// 铸造权限
+    Mint,
+    /// 冻结权限
+    Freeze,
+    /// 代币账户所有者
+    Owner,
+    /// 关闭代币账户的权限
+    Close,
+}
+
+impl From<AuthorityTypeArg> for AuthorityType {
+    fn from(arg: AuthorityTypeArg) -> Self {
+        match arg {
+            AuthorityTypeArg::Mint => AuthorityType::MintTokens,
+            AuthorityTypeArg::Freeze => AuthorityType::FreezeAccount,
+            AuthorityTypeArg::Owner => AuthorityType::AccountOwner,
+            AuthorityTypeArg::Close => AuthorityType::CloseAccount,
+        }
+    }
+}
+
+/// 命令执行所需的 RPC 连接、签名账户和程序 ID
+struct Admin {
+    rpc: RpcClient,
+    payer: Keypair,
+    program_id: Pubkey,
+}
+
+impl Admin {
+    /// 签名并发送交易，等待确认
+    fn send(
+        &self,
+        instructions: &[Instruction],
+        extra_signers: &[&Keypair],
+    ) -> CliResult<Signature> {
+        let mut signers = vec![&self.payer];
+        signers.extend_from_slice(extra_signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &signers,
+            self.rpc.get_latest_blockhash()?,
+        );
+        Ok(self.rpc.send_and_confirm_transaction(&transaction)?)
+    }
+
+    /// 读取铸造账户原始数据
+    fn get_mint_account(&self, mint: &Pubkey) -> CliResult<Account> {
+        Ok(self
+            .rpc
+            .get_account_with_commitment(mint, self.rpc.commitment())?
+            .value
+            .ok_or_else(|| format!("铸造账户 {} 不存在", mint))?)
+    }
+
+    /// 返回持有该账户的代币程序（SPL Token 或 Token-2022）
+    fn token_program_of(&self, address: &Pubkey) -> CliResult<Pubkey> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())?
+            .value
+            .ok_or_else(|| format!("账户 {} 不存在", address))?;
+        Ok(account.owner)
+    }
+
+    /// 读取代币账户，不存在时返回 `None`
+    fn get_token_account(&self, address: &Pubkey) -> CliResult<Option<SplAccount>> {
+        match self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())?
+            .value
+        {
+            Some(account) => Ok(Some(unpack_base(&account.data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 读取铸造账户配置，不存在时返回 `None`
+    fn get_mint_config(&self, mint: &Pubkey) -> CliResult<Option<config::MintConfig>> {
+        let (address, _) = config::find_mint_config_address(&self.program_id, mint);
+        match self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())?
+            .value
+        {
+            Some(account) => Ok(Some(config::MintConfig::unpack_account(&account.data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 铸造账户是否已有分红池
+    fn has_dividend_pool(&self, mint: &Pubkey) -> CliResult<bool> {
+        let (address, _) = dividend::find_dividend_pool_address(&self.program_id, mint);
+        Ok(self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())?
+            .value
+            .is_some())
+    }
+
+    /// 读取钩子校验 PDA 声明的额外账户，校验 PDA 不存在时为空
+    fn get_hook_extra_accounts(
+        &self,
+        hook_program: &Pubkey,
+        mint: &Pubkey,
+    ) -> CliResult<Vec<hook::ExtraAccountMeta>> {
+        let (address, _) = hook::find_extra_account_metas_address(hook_program, mint);
+        match self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())?
+            .value
+        {
+            Some(account) => Ok(hook::ExtraAccountMetas::unpack_from(&account.data)?.accounts),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// 代币是否不可转让：Token-2022 看 NonTransferable 扩展，否则看铸造账户配置
+    fn is_non_transferable(
+        &self,
+        mint_account: &Account,
+        mint_config: Option<&config::MintConfig>,
+    ) -> CliResult<bool> {
+        if mint_account.owner == spl_token_2022::id() {
+            let state = StateWithExtensions::<Mint2022>::unpack(&mint_account.data)?;
+            return Ok(state.get_extension::<NonTransferable>().is_ok());
+        }
+        Ok(mint_config.is_some_and(|mint_config| mint_config.non_transferable))
+    }
+
+    /// 读取链上 Clock sysvar 的 unix 时间戳，用于计算利息
+    fn unix_timestamp(&self) -> CliResult<i64> {
+        let account = self.rpc.get_account(&sysvar::clock::id())?;
+        let clock: Clock = from_account(&account).ok_or("无法解析 Clock sysvar")?;
+        Ok(clock.unix_timestamp)
+    }
+
+    /// 返回钱包的关联代币账户，账户不存在时追加创建指令
+    fn token_account_or_create(
+        &self,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+        instructions: &mut Vec<Instruction>,
+    ) -> CliResult<Pubkey> {
+        let address = get_associated_token_address_with_program_id(wallet, mint, token_program);
+        if self.get_token_account(&address)?.is_none() {
+            instructions.push(instruction::create_token_account_with_program(
+                &self.program_id,
+                &self.payer.pubkey(),
+                wallet,
+                mint,
+                token_program,
+            ));
+        }
+        Ok(address)
+    }
+}
+
+/// 解析账户数据的基础部分，Token-2022 账户的扩展数据位于基础布局之后
+fn unpack_base<T: Pack + IsInitialized>(data: &[u8]) -> CliResult<T> {
+    Ok(T::unpack(data.get(..T::LEN).ok_or("账户数据长度不足")?)?)
+}
 
 fn main() {
-    println!("Solana SPL Token Program Library");
-    println!("这是一个 Solana SPL 代币程序库");
+    if let Err(error) = run(Cli::parse()) {
+        eprintln!("错误: {}", error);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    let keypair_path = match cli.keypair {
+        Some(path) => path,
+        None => PathBuf::from(std::env::var("HOME")?).join(".config/solana/id.json"),
+    };
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|error| format!("无法读取密钥文件 {}: {}", keypair_path.display(), error))?;
+    let admin = Admin {
+        rpc: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        payer,
+        program_id: cli.program_id,
+    };
+    let signer = admin.payer.pubkey();
+
+    match cli.command {
+        Command::CreateMint {
+            decimals,
+            mint_authority,
+            freeze_authority,
+            mint_keypair,
+            non_transferable,
+        } => {
+            let mint = match mint_keypair {
+                Some(path) => read_keypair_file(&path)
+                    .map_err(|error| format!("无法读取密钥文件 {}: {}", path.display(), error))?,
+                None => Keypair::new(),
+            };
+            let mint_authority = mint_authority.unwrap_or(signer);
+            let initialize_ix = if non_transferable {
+                instruction::initialize_non_transferable_mint(
+                    &admin.program_id,
+                    &mint.pubkey(),
+                    &signer,
+                    &mint_authority,
+                    freeze_authority.as_ref(),
+                    decimals,
+                )
+            } else {
+                instruction::initialize_mint(
+                    &admin.program_id,
+                    &mint.pubkey(),
+                    &signer,
+                    &mint_authority,
+                    freeze_authority.as_ref(),
+                    decimals,
+                )
+            };
+            let signature = admin.send(&[initialize_ix], &[&mint])?;
+            println!("铸造账户: {}", mint.pubkey());
+            if non_transferable {
+                println!("类型: 不可转让（灵魂绑定）");
+            }
+            println!("交易签名: {}", signature);
+        }
+        Command::CreateAccount { mint, owner } => {
+            let owner = owner.unwrap_or(signer);
+            let signature = admin.send(
+                &[instruction::create_token_account(
+                    &admin.program_id,
+                    &signer,
+                    &owner,
+                    &mint,
+                )],
+                &[],
+            )?;
+            println!("代币账户: {}", get_associated_token_address(&owner, &mint));
+            println!("交易签名: {}", signature);
+        }
+        Command::Mint {
+            mint,
+            amount,
+            recipient,
+        } => {
+            let mut instructions = Vec::new();
+            let token_program = admin.token_program_of(&mint)?;
+            let destination = admin.token_account_or_create(
+                &recipient.unwrap_or(signer),
+                &mint,
+                &token_program,
+                &mut instructions,
+            )?;
+            instructions.push(instruction::with_token_program(
+                instruction::mint_tokens(
+                    &admin.program_id,
+                    &mint,
+                    &destination,
+                    &signer,
+                    &signer,
+                    amount,
+                ),
+                &token_program,
+            ));
+            let signature = admin.send(&instructions, &[])?;
+            println!("已铸造 {} 到 {}", amount, destination);
+            println!("交易签名: {}", signature);
+        }
+        Command::Transfer {
+            mint,
+            amount,
+            recipient,
+            memo,
+        } => {
+            let mut instructions = Vec::new();
+            let token_program = admin.token_program_of(&mint)?;
+            let source =
+                get_associated_token_address_with_program_id(&signer, &mint, &token_program);
+            let destination = admin.token_account_or_create(
+                &recipient,
+                &mint,
+                &token_program,
+                &mut instructions,
+            )?;
+            let mut transfer_ix = match admin.get_mint_config(&mint)? {
+                Some(config::MintConfig {
+                    transfer_hook_program: Some(hook_program),
+                    ..
+                }) => hook::transfer_tokens_with_hook(
+                    &admin.program_id,
+                    &source,
+                    &destination,
+                    &signer,
+                    &mint,
+                    &signer,
+                    &recipient,
+                    &hook_program,
+                    &admin.get_hook_extra_accounts(&hook_program, &mint)?,
+                    amount,
+                ),
+                Some(_) => config::transfer_tokens_with_config(
+                    &admin.program_id,
+                    &source,
+                    &destination,
+                    &signer,
+                    &mint,
+                    &signer,
+                    &recipient,
+                    amount,
+                ),
+                None => instruction::transfer_tokens(
+                    &admin.program_id,
+                    &source,
+                    &destination,
+                    &signer,
+                    &mint,
+                    amount,
+                ),
+            };
+            if admin.has_dividend_pool(&mint)? {
+                transfer_ix = dividend::with_dividend_checkpoints(&admin.program_id, transfer_ix);
+            }
+            if let Some(memo) = memo {
+                transfer_ix = memo::with_memo(transfer_ix, amount, &memo);
+            }
+            instructions.push(instruction::with_token_program(transfer_ix, &token_program));
+            let signature = admin.send(&instructions, &[])?;
+            println!("已转移 {} 从 {} 到 {}", amount, source, destination);
+            println!("交易签名: {}", signature);
+        }
+        Command::Burn { mint, amount } => {
+            let source = get_associated_token_address(&signer, &mint);
+            let signature = admin.send(
+                &[instruction::burn_tokens(
+                    &admin.program_id,
+                    &source,
+                    &mint,
+                    &signer,
+                    amount,
+                )],
+                &[],
+            )?;
+            println!("已从 {} 销毁 {}", source, amount);
+            println!("交易签名: {}", signature);
+        }
+        Command::Balance { mint, owner } => {
+            let mint_account = admin.get_mint_account(&mint)?;
+            let address = get_associated_token_address_with_program_id(
+                &owner.unwrap_or(signer),
+                &mint,
+                &mint_account.owner,
+            );
+            let account = admin
+                .get_token_account(&address)?
+                .ok_or_else(|| format!("代币账户 {} 不存在", address))?;
+            let mint_config = admin.get_mint_config(&mint)?;
+            let ui_amount = interest::ui_amount_for_mint(
+                &mint_account.data,
+                mint_config.as_ref(),
+                account.amount,
+                admin.unix_timestamp()?,
+            )?;
+            println!("代币账户: {}", address);
+            println!("余额: {} （最小单位 {}）", ui_amount, account.amount);
+            if let Some(interest_rate) = mint_config
+                .as_ref()
+                .and_then(|mint_config| mint_config.interest_rate.as_ref())
+            {
+                println!(
+                    "年利率: {} 个基点（余额含利息）",
+                    interest_rate.current_rate()
+                );
+            }
+            if account.state == AccountState::Frozen {
+                println!("状态: 已冻结");
+            }
+            if admin.is_non_transferable(&mint_account, mint_config.as_ref())? {
+                println!("类型: 不可转让（灵魂绑定）");
+            }
+        }
+        Command::SetAuthority {
+            account,
+            authority_type,
+            new_authority,
+            revoke: _,
+        } => {
+            let token_program = admin.token_program_of(&account)?;
+            let signature = admin.send(
+                &[instruction::with_token_program(
+                    instruction::set_authority(
+                        &admin.program_id,
+                        &account,
+                        &signer,
+                        authority_type.into(),
+                        new_authority.as_ref(),
+                    ),
+                    &token_program,
+                )],
+                &[],
+            )?;
+            match new_authority {
+                Some(new_authority) => println!("新权限: {}", new_authority),
+                None => println!("权限已放弃"),
+            }
+            println!("交易签名: {}", signature);
+        }
+        Command::Freeze { mint, owner } => {
+            let token_program = admin.token_program_of(&mint)?;
+            let address =
+                get_associated_token_address_with_program_id(&owner, &mint, &token_program);
+            let signature = admin.send(
+                &[instruction::with_token_program(
+                    instruction::freeze_account(&admin.program_id, &address, &mint, &signer),
+                    &token_program,
+                )],
+                &[],
+            )?;
+            println!("已冻结代币账户: {}", address);
+            println!("交易签名: {}", signature);
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,215 @@
+//! 转账备注
+//!
+//! `TransferWithMemo { amount, memo }` 在转账前通过 CPI 调用 SPL Memo 程序记录备注，
+//! 备注由转出权限签名，交易所可以按备注识别充值用户。
+//!
+//! 代币账户所有者可以通过 `SetMemoRequired` 为账户开启 `require_incoming_memo`，
+//! 标记保存在代币账户对应的备注要求 PDA 中。每笔转账都必须在铸造账户之后附带转入账户的
+//! 备注要求 PDA（账户为空表示不要求备注），转入账户要求备注时没有备注的 `TransferTokens`
+//! 会被拒绝。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use spl_token_2022::{extension::StateWithExtensions, state::Account as SplAccount};
+
+use crate::{
+    accounts::{
+        check_any_token_owner, check_owner, check_program_account, check_signer,
+        check_system_program, check_writable,
+    },
+    create_pda_account,
+    events::TokenEvent,
+    instruction,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    TokenError, TokenInstruction,
+};
+
+/// 备注要求 PDA 种子前缀
+pub const MEMO_REQUIREMENT_SEED: &[u8] = b"memo_requirement";
+
+/// 代币账户的备注要求
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemoRequirement {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 代币账户
+    pub token_account: Pubkey,
+    /// 转入是否必须附带备注
+    pub require_incoming_memo: bool,
+    /// 备注要求 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for MemoRequirement {
+    const ACCOUNT_TYPE: AccountType = AccountType::MemoRequirement;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 + 1 + 1;
+}
+
+/// 计算代币账户的备注要求 PDA 地址
+pub fn find_memo_requirement_address(program_id: &Pubkey, token_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MEMO_REQUIREMENT_SEED, token_account.as_ref()], program_id)
+}
+
+/// 读取备注要求 PDA，账户不存在时视为不要求备注
+pub(crate) fn is_memo_required(requirement: &AccountInfo) -> Result<bool, ProgramError> {
+    if requirement.data_is_empty() {
+        return Ok(false);
+    }
+    check_owner(requirement, &crate::id())?;
+    Ok(MemoRequirement::unpack_account(&requirement.data.borrow())?.require_incoming_memo)
+}
+
+/// `SetMemoRequired` 指令的账户
+pub struct SetMemoRequiredAccounts<'a, 'info> {
+    /// `[signer, writable]` 代币账户所有者（付费账户）
+    pub owner: &'a AccountInfo<'info>,
+    /// `[]` 代币账户（SPL Token 或 Token-2022）
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[writable]` 备注要求 PDA，不存在时创建
+    pub requirement: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetMemoRequiredAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            owner: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            requirement: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.owner)?;
+        check_writable(ctx.owner)?;
+        check_any_token_owner(ctx.token_account)?;
+        check_writable(ctx.requirement)?;
+        check_system_program(ctx.system_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `SetMemoRequired`：开启或关闭代币账户的转入备注要求
+pub fn process_set_memo_required(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    required: bool,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let SetMemoRequiredAccounts {
+        owner,
+        token_account,
+        requirement: requirement_account,
+        system_program,
+    } = SetMemoRequiredAccounts::parse(accounts)?;
+
+    if StateWithExtensions::<SplAccount>::unpack(&token_account.data.borrow())?
+        .base
+        .owner
+        != *owner.key
+    {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+
+    let (address, bump) = find_memo_requirement_address(program_id, token_account.key);
+    if address != *requirement_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if requirement_account.data_is_empty() {
+        create_pda_account(
+            owner,
+            requirement_account,
+            system_program,
+            program_id,
+            MemoRequirement::LEN,
+            &[MEMO_REQUIREMENT_SEED, token_account.key.as_ref(), &[bump]],
+        )?;
+    } else {
+        check_owner(requirement_account, program_id)?;
+    }
+
+    let requirement = MemoRequirement {
+        is_initialized: true,
+        token_account: *token_account.key,
+        require_incoming_memo: required,
+        bump,
+    };
+    requirement.pack_account(&mut requirement_account.data.borrow_mut())?;
+
+    TokenEvent::MemoRequirementSet {
+        account: *token_account.key,
+        required,
+    }
+    .emit();
+
+    if required {
+        log_msg!("转入需附带备注: {}", token_account.key);
+    } else {
+        log_msg!("已取消转入备注要求: {}", token_account.key);
+    }
+
+    Ok(())
+}
+
+/// 构建 `SetMemoRequired` 指令，`owner` 需为代币账户所有者
+pub fn set_memo_required(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    token_account: &Pubkey,
+    required: bool,
+) -> Instruction {
+    let (requirement, _) = find_memo_requirement_address(program_id, token_account);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::SetMemoRequired { required }.pack(),
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new_readonly(*token_account, false),
+            AccountMeta::new(requirement, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
+
+/// 构建 `TransferWithMemo` 指令，在铸造账户之后插入 Memo 程序
+pub fn transfer_with_memo(
+    program_id: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    memo: &str,
+) -> Instruction {
+    with_memo(
+        instruction::transfer_tokens(program_id, from, to, authority, mint, amount),
+        amount,
+        memo,
+    )
+}
+
+/// 把 `TransferTokens` 指令改为 `TransferWithMemo`，在铸造账户之后插入 Memo 程序
+///
+/// 适用于 `TransferTokens` 的各个构建函数（包括附带配置或分红账户的版本）。
+pub fn with_memo(mut ix: Instruction, amount: u64, memo: &str) -> Instruction {
+    ix.data = TokenInstruction::TransferWithMemo {
+        amount,
+        memo: memo.to_string(),
+    }
+    .pack();
+    ix.accounts
+        .insert(5, AccountMeta::new_readonly(spl_memo::id(), false));
+    ix
+}
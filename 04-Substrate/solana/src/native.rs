@@ -0,0 +1,255 @@
+//! 原生 SOL 包装（wSOL）
+//!
+//! `WrapSol` 把 lamports 存入用户以 `spl_token::native_mint` 为铸造账户的关联代币账户，
+//! 并通过 `SyncNative` 同步代币余额；`UnwrapSol` 关闭该账户，把 lamports 连同租金退还给用户。
+//! 包装后的 wSOL 与其他代币一样可以通过 `TransferTokens` 转移。
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+use spl_associated_token_account::instruction::create_associated_token_account;
+use spl_token::{
+    instruction::{close_account, sync_native},
+    native_mint,
+    state::Account as SplAccount,
+};
+
+use crate::{
+    accounts::{
+        check_associated_token_program, check_key, check_program_account, check_signer,
+        check_system_program, check_token_owned_writable, check_token_program, check_writable,
+    },
+    events::TokenEvent,
+    get_associated_token_address, TokenError, TokenInstruction,
+};
+
+/// `WrapSol` 指令的账户
+pub struct WrapSolAccounts<'a, 'info> {
+    /// `[signer, writable]` 存入 lamports 的用户（付费账户）
+    pub owner: &'a AccountInfo<'info>,
+    /// `[writable]` 用户的 wSOL 关联代币账户，不存在时创建
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[]` 原生代币铸造账户
+    pub native_mint: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+    /// `[]` 关联代币账户程序
+    pub associated_token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> WrapSolAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            owner: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            native_mint: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+            associated_token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.owner)?;
+        check_writable(ctx.owner)?;
+        check_writable(ctx.token_account)?;
+        check_key(
+            ctx.native_mint,
+            &native_mint::id(),
+            TokenError::MintMismatch,
+        )?;
+        check_key(
+            ctx.token_account,
+            &get_associated_token_address(ctx.owner.key, &native_mint::id()),
+            TokenError::InvalidProgramAddress,
+        )?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+        check_associated_token_program(ctx.associated_token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `UnwrapSol` 指令的账户
+pub struct UnwrapSolAccounts<'a, 'info> {
+    /// `[signer, writable]` wSOL 账户的所有者，接收退还的 lamports
+    pub owner: &'a AccountInfo<'info>,
+    /// `[writable]` 被关闭的 wSOL 代币账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> UnwrapSolAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            owner: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.owner)?;
+        check_writable(ctx.owner)?;
+        check_token_owned_writable(ctx.token_account)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 SOL 包装
+pub fn process_wrap_sol(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lamports: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let WrapSolAccounts {
+        owner,
+        token_account,
+        native_mint,
+        system_program: system_program_account,
+        token_program,
+        associated_token_program,
+    } = WrapSolAccounts::parse(accounts)?;
+
+    if lamports == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    // 首次包装时创建 wSOL 关联代币账户
+    if token_account.data_is_empty() {
+        invoke(
+            &create_associated_token_account(
+                owner.key,
+                owner.key,
+                native_mint.key,
+                token_program.key,
+            ),
+            &[
+                owner.clone(),
+                token_account.clone(),
+                native_mint.clone(),
+                system_program_account.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+    }
+
+    // 存入 lamports 后同步代币余额
+    invoke(
+        &system_instruction::transfer(owner.key, token_account.key, lamports),
+        &[
+            owner.clone(),
+            token_account.clone(),
+            system_program_account.clone(),
+        ],
+    )?;
+    invoke(
+        &sync_native(token_program.key, token_account.key)?,
+        std::slice::from_ref(token_account),
+    )?;
+
+    TokenEvent::SolWrapped {
+        owner: *owner.key,
+        account: *token_account.key,
+        lamports,
+    }
+    .emit();
+
+    log_msg!("已包装 {} lamports 到 {}", lamports, token_account.key);
+
+    Ok(())
+}
+
+/// 处理 SOL 解包
+pub fn process_unwrap_sol(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let UnwrapSolAccounts {
+        owner,
+        token_account,
+        token_program,
+    } = UnwrapSolAccounts::parse(accounts)?;
+
+    let account = SplAccount::unpack(&token_account.data.borrow())?;
+    if account.mint != native_mint::id() {
+        return Err(TokenError::MintMismatch.into());
+    }
+    if account.owner != *owner.key {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+
+    // 关闭账户时余额与租金一并退还给所有者
+    let lamports = token_account.lamports();
+    invoke(
+        &close_account(
+            token_program.key,
+            token_account.key,
+            owner.key,
+            owner.key,
+            &[],
+        )?,
+        &[token_account.clone(), owner.clone()],
+    )?;
+
+    TokenEvent::SolUnwrapped {
+        owner: *owner.key,
+        account: *token_account.key,
+        lamports,
+    }
+    .emit();
+
+    log_msg!("已解包 {} lamports 到 {}", lamports, owner.key);
+
+    Ok(())
+}
+
+/// 构建 `WrapSol` 指令
+pub fn wrap_sol(program_id: &Pubkey, owner: &Pubkey, lamports: u64) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::WrapSol { lamports }.pack(),
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(
+                get_associated_token_address(owner, &native_mint::id()),
+                false,
+            ),
+            AccountMeta::new_readonly(native_mint::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        ],
+    }
+}
+
+/// 构建 `UnwrapSol` 指令，关闭 `owner` 的 wSOL 关联代币账户
+pub fn unwrap_sol(program_id: &Pubkey, owner: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::UnwrapSol.pack(),
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(
+                get_associated_token_address(owner, &native_mint::id()),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
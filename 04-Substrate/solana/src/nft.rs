@@ -0,0 +1,466 @@
+//! NFT
+//!
+//! `MintNft { metadata }` 发行一枚独一无二的 NFT：沿用 `InitializeMint`、`CreateTokenAccount` 和
+//! `MintTokens` 的处理流程创建精度为 0 的铸造账户、接收者的关联代币账户并铸造 1 个代币，
+//! 随后放弃铸造权限，使供应量永久固定为 1（相当于 master edition 不允许再印刷副本）。
+//! 名称、符号和 URI 保存在元数据 PDA（`metadata` + 铸造账户）中，付费账户为元数据的更新权限。
+//!
+//! NFT 可以在元数据中声明所属集合（集合 NFT 的铸造账户），声明在集合权限（集合 NFT 的元数据
+//! 更新权限）调用 `VerifyCollection` 之前不被承认。验证后集合 PDA（`collection` + 集合铸造账户）
+//! 的 `size` 加一，链下可以按已验证的集合对 NFT 分组。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::instruction::{set_authority, AuthorityType as SplAuthorityType};
+
+use crate::{
+    accounts::{
+        check_owner, check_program_account, check_signer, check_system_program, check_writable,
+    },
+    create_pda_account,
+    events::TokenEvent,
+    process_create_token_account, process_initialize_mint, process_mint_tokens,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    AuthorityType, TokenError, TokenInstruction,
+};
+
+/// 元数据 PDA 种子前缀
+pub const METADATA_SEED: &[u8] = b"metadata";
+
+/// 集合 PDA 种子前缀
+pub const COLLECTION_SEED: &[u8] = b"collection";
+
+/// 名称的最大字节数
+pub const MAX_NAME_LEN: usize = 32;
+
+/// 符号的最大字节数
+pub const MAX_SYMBOL_LEN: usize = 10;
+
+/// URI 的最大字节数
+pub const MAX_URI_LEN: usize = 200;
+
+/// `MintNft` 携带的元数据
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct NftMetadata {
+    /// 名称
+    pub name: String,
+    /// 符号
+    pub symbol: String,
+    /// 链下 JSON 的 URI
+    pub uri: String,
+    /// 声明所属的集合（集合 NFT 的铸造账户），需通过 `VerifyCollection` 验证
+    pub collection: Option<Pubkey>,
+}
+
+impl NftMetadata {
+    /// 校验各字段长度
+    pub fn validate(&self) -> ProgramResult {
+        if self.name.len() > MAX_NAME_LEN
+            || self.symbol.len() > MAX_SYMBOL_LEN
+            || self.uri.len() > MAX_URI_LEN
+        {
+            return Err(TokenError::MetadataTooLong.into());
+        }
+        Ok(())
+    }
+}
+
+/// 元数据 PDA 的数据
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// NFT 铸造账户
+    pub mint: Pubkey,
+    /// 元数据更新权限，同时是以该 NFT 为集合时的集合权限
+    pub update_authority: Pubkey,
+    /// 名称、符号、URI 和声明的集合
+    pub data: NftMetadata,
+    /// 所属集合是否已验证
+    pub collection_verified: bool,
+    /// 元数据 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for Metadata {
+    const ACCOUNT_TYPE: AccountType = AccountType::NftMetadata;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN
+        + 1
+        + 32
+        + 32
+        + (4 + MAX_NAME_LEN)
+        + (4 + MAX_SYMBOL_LEN)
+        + (4 + MAX_URI_LEN)
+        + (1 + 32)
+        + 1
+        + 1;
+}
+
+/// 集合 PDA 的数据
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Collection {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 集合 NFT 的铸造账户
+    pub mint: Pubkey,
+    /// 已验证的成员数量
+    pub size: u64,
+    /// 集合 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for Collection {
+    const ACCOUNT_TYPE: AccountType = AccountType::NftCollection;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 + 8 + 1;
+}
+
+/// 计算 NFT 的元数据 PDA 地址
+pub fn find_metadata_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[METADATA_SEED, mint.as_ref()], program_id)
+}
+
+/// 计算集合 PDA 地址
+pub fn find_collection_address(program_id: &Pubkey, collection_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLLECTION_SEED, collection_mint.as_ref()], program_id)
+}
+
+/// 读取本程序所有、地址与记录的铸造账户一致的元数据 PDA
+fn load_metadata(program_id: &Pubkey, account: &AccountInfo) -> Result<Metadata, ProgramError> {
+    check_owner(account, program_id)?;
+    let metadata = Metadata::unpack_account(&account.data.borrow())?;
+    if find_metadata_address(program_id, &metadata.mint).0 != *account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    Ok(metadata)
+}
+
+/// `MintNft` 指令的账户
+pub struct MintNftAccounts<'a, 'info> {
+    /// `[signer, writable]` 新的铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[signer, writable]` 付费账户，铸造期间的铸造权限和元数据更新权限
+    pub payer: &'a AccountInfo<'info>,
+    /// `[]` 接收者钱包
+    pub recipient: &'a AccountInfo<'info>,
+    /// `[writable]` 接收者的关联代币账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[writable]` 元数据 PDA
+    pub metadata: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 或 Token-2022 程序
+    pub token_program: &'a AccountInfo<'info>,
+    /// `[]` 关联代币账户程序
+    pub associated_token_program: &'a AccountInfo<'info>,
+    /// `[]` 租金 sysvar
+    pub rent: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> MintNftAccounts<'a, 'info> {
+    /// 解析账户，铸造账户和代币账户的校验由沿用的处理流程完成
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            mint: next_account_info(accounts_iter)?,
+            payer: next_account_info(accounts_iter)?,
+            recipient: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            metadata: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+            associated_token_program: next_account_info(accounts_iter)?,
+            rent: next_account_info(accounts_iter)?,
+        };
+
+        check_writable(ctx.metadata)?;
+        check_owner(ctx.metadata, &system_program::id())?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `MintNft`：创建精度为 0 的铸造账户，向接收者铸造 1 个代币，写入元数据并放弃铸造权限
+pub fn process_mint_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: NftMetadata,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let MintNftAccounts {
+        mint,
+        payer,
+        recipient,
+        token_account,
+        metadata: metadata_account,
+        system_program,
+        token_program,
+        associated_token_program,
+        rent,
+    } = MintNftAccounts::parse(accounts)?;
+
+    metadata.validate()?;
+    let (metadata_address, bump) = find_metadata_address(program_id, mint.key);
+    if metadata_address != *metadata_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+
+    // 沿用普通代币的处理流程：付费账户暂时作为铸造权限，不设冻结权限
+    process_initialize_mint(
+        program_id,
+        &[
+            mint.clone(),
+            payer.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            rent.clone(),
+        ],
+        0,
+        *payer.key,
+        None,
+        false,
+        None,
+        None,
+    )?;
+    process_create_token_account(
+        program_id,
+        &[
+            payer.clone(),
+            recipient.clone(),
+            token_account.clone(),
+            mint.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )?;
+    process_mint_tokens(
+        program_id,
+        &[
+            mint.clone(),
+            token_account.clone(),
+            payer.clone(),
+            payer.clone(),
+            token_program.clone(),
+        ],
+        1,
+    )?;
+
+    // 放弃铸造权限，供应量永久固定为 1
+    invoke(
+        &set_authority(
+            token_program.key,
+            mint.key,
+            None,
+            SplAuthorityType::MintTokens,
+            payer.key,
+            &[],
+        )?,
+        &[mint.clone(), payer.clone()],
+    )?;
+    TokenEvent::AuthorityChanged {
+        account: *mint.key,
+        authority_type: AuthorityType::MintTokens as u8,
+        new_authority: None,
+    }
+    .emit();
+
+    create_pda_account(
+        payer,
+        metadata_account,
+        system_program,
+        program_id,
+        Metadata::LEN,
+        &[METADATA_SEED, mint.key.as_ref(), &[bump]],
+    )?;
+    let collection = metadata.collection;
+    let metadata = Metadata {
+        is_initialized: true,
+        mint: *mint.key,
+        update_authority: *payer.key,
+        data: metadata,
+        collection_verified: false,
+        bump,
+    };
+    metadata.pack_account(&mut metadata_account.data.borrow_mut())?;
+
+    TokenEvent::NftMinted {
+        mint: *mint.key,
+        owner: *recipient.key,
+        update_authority: *payer.key,
+        collection,
+    }
+    .emit();
+
+    log_msg!("NFT 已铸造: {}", mint.key);
+    log_msg!("名称: {}", metadata.data.name);
+    log_msg!("持有人: {}", recipient.key);
+
+    Ok(())
+}
+
+/// `VerifyCollection` 指令的账户
+pub struct VerifyCollectionAccounts<'a, 'info> {
+    /// `[signer, writable]` 集合权限（集合 NFT 的元数据更新权限，付费账户）
+    pub authority: &'a AccountInfo<'info>,
+    /// `[writable]` 待验证 NFT 的元数据 PDA
+    pub metadata: &'a AccountInfo<'info>,
+    /// `[]` 集合 NFT 的元数据 PDA
+    pub collection_metadata: &'a AccountInfo<'info>,
+    /// `[writable]` 集合 PDA，不存在时创建
+    pub collection: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> VerifyCollectionAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            authority: next_account_info(accounts_iter)?,
+            metadata: next_account_info(accounts_iter)?,
+            collection_metadata: next_account_info(accounts_iter)?,
+            collection: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.authority)?;
+        check_writable(ctx.authority)?;
+        check_writable(ctx.metadata)?;
+        check_writable(ctx.collection)?;
+        check_system_program(ctx.system_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `VerifyCollection`：集合权限确认 NFT 声明的集合，集合成员数量加一
+pub fn process_verify_collection(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let VerifyCollectionAccounts {
+        authority,
+        metadata: metadata_account,
+        collection_metadata: collection_metadata_account,
+        collection: collection_account,
+        system_program,
+    } = VerifyCollectionAccounts::parse(accounts)?;
+
+    let mut metadata = load_metadata(program_id, metadata_account)?;
+    let collection_metadata = load_metadata(program_id, collection_metadata_account)?;
+    if metadata.data.collection != Some(collection_metadata.mint)
+        || metadata.collection_verified
+        || metadata.mint == collection_metadata.mint
+    {
+        return Err(TokenError::CollectionMismatch.into());
+    }
+    if collection_metadata.update_authority != *authority.key {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+
+    let (collection_address, bump) = find_collection_address(program_id, &collection_metadata.mint);
+    if collection_address != *collection_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    let mut collection = if collection_account.data_is_empty() {
+        create_pda_account(
+            authority,
+            collection_account,
+            system_program,
+            program_id,
+            Collection::LEN,
+            &[COLLECTION_SEED, collection_metadata.mint.as_ref(), &[bump]],
+        )?;
+        Collection {
+            is_initialized: true,
+            mint: collection_metadata.mint,
+            size: 0,
+            bump,
+        }
+    } else {
+        check_owner(collection_account, program_id)?;
+        Collection::unpack_account(&collection_account.data.borrow())?
+    };
+    collection.size = collection.size.checked_add(1).ok_or(TokenError::Overflow)?;
+    collection.pack_account(&mut collection_account.data.borrow_mut())?;
+
+    metadata.collection_verified = true;
+    metadata.pack_account(&mut metadata_account.data.borrow_mut())?;
+
+    TokenEvent::CollectionVerified {
+        mint: metadata.mint,
+        collection: collection.mint,
+        size: collection.size,
+    }
+    .emit();
+
+    log_msg!("NFT {} 已加入集合 {}", metadata.mint, collection.mint);
+    log_msg!("集合成员数量: {}", collection.size);
+
+    Ok(())
+}
+
+/// 构建 `MintNft` 指令，`mint` 为新的铸造账户（需签名），`payer` 为元数据更新权限
+pub fn mint_nft(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    recipient: &Pubkey,
+    metadata: NftMetadata,
+) -> Instruction {
+    let (metadata_address, _) = find_metadata_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::MintNft { metadata }.pack(),
+        accounts: vec![
+            AccountMeta::new(*mint, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*recipient, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(recipient, mint, token_program),
+                false,
+            ),
+            AccountMeta::new(metadata_address, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+    }
+}
+
+/// 构建 `VerifyCollection` 指令，`authority` 需为集合 NFT 的元数据更新权限
+pub fn verify_collection(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    collection_mint: &Pubkey,
+) -> Instruction {
+    let (metadata, _) = find_metadata_address(program_id, mint);
+    let (collection_metadata, _) = find_metadata_address(program_id, collection_mint);
+    let (collection, _) = find_collection_address(program_id, collection_mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::VerifyCollection.pack(),
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(metadata, false),
+            AccountMeta::new_readonly(collection_metadata, false),
+            AccountMeta::new(collection, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
@@ -0,0 +1,383 @@
+//! 链下签名授权转账（permit）
+//!
+//! 代币持有人先用 `approve_permit_delegate` 把代币账户授权给本程序的委托 PDA（只需一次），
+//! 之后在链下对 [`permit_message`] 签名。中继者提交交易并支付手续费，交易中紧挨着
+//! `TransferWithAuthorization` 之前放一条 Ed25519 程序的验签指令（[`ed25519_verify_instruction`]），
+//! 本程序通过 instructions sysvar 确认该验签指令的公钥与消息，随后由委托 PDA 签名完成转账。
+//! 每个 nonce 使用后都会创建一个 PDA 记录，防止签名被重放。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::{
+        self,
+        instructions::{load_current_index_checked, load_instruction_at_checked},
+        Sysvar,
+    },
+};
+use spl_token::{instruction::transfer, state::Account as SplAccount};
+
+use crate::{
+    accounts::{
+        check_instructions_sysvar, check_program_account, check_signer, check_system_program,
+        check_token_owned_writable, check_token_program, check_writable,
+    },
+    create_pda_account,
+    events::TokenEvent,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    validate_token_account, TokenError, TokenInstruction,
+};
+
+/// 委托 PDA 种子前缀
+pub const PERMIT_DELEGATE_SEED: &[u8] = b"permit_delegate";
+/// nonce 记录 PDA 种子前缀
+pub const PERMIT_NONCE_SEED: &[u8] = b"permit_nonce";
+/// 签名消息的域前缀，避免与其他用途的签名混淆
+pub const PERMIT_MESSAGE_PREFIX: &[u8] = b"spl-token-permit";
+
+/// Ed25519 验签指令中单个签名的偏移量结构长度
+const ED25519_OFFSETS_LEN: usize = 14;
+/// Ed25519 验签指令中签名数据的起始位置（签名数量、填充字节与一组偏移量之后）
+const ED25519_DATA_START: usize = 2 + ED25519_OFFSETS_LEN;
+/// 偏移量中表示“数据位于本条指令内”的指令索引
+const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// 已使用的 nonce 记录
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct PermitNonce {
+    /// 是否已初始化
+    pub is_initialized: bool,
+}
+
+impl ProgramAccount for PermitNonce {
+    const ACCOUNT_TYPE: AccountType = AccountType::PermitNonce;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1;
+}
+
+/// 计算持有人的委托 PDA 地址
+pub fn find_permit_delegate_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PERMIT_DELEGATE_SEED, owner.as_ref()], program_id)
+}
+
+/// 计算 nonce 记录 PDA 地址
+pub fn find_permit_nonce_address(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    nonce: &[u8; 32],
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PERMIT_NONCE_SEED, owner.as_ref(), nonce], program_id)
+}
+
+/// 持有人需要签名的消息
+///
+/// 消息包含程序 ID 与铸造账户，签名只能用于本程序的这一种代币。
+pub fn permit_message(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    amount: u64,
+    nonce: &[u8; 32],
+    expiry: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(PERMIT_MESSAGE_PREFIX.len() + 32 * 5 + 8 + 8);
+    message.extend_from_slice(PERMIT_MESSAGE_PREFIX);
+    message.extend_from_slice(program_id.as_ref());
+    message.extend_from_slice(mint.as_ref());
+    message.extend_from_slice(from.as_ref());
+    message.extend_from_slice(to.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(nonce);
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+/// `TransferWithAuthorization` 指令的账户
+pub struct TransferWithAuthorizationAccounts<'a, 'info> {
+    /// `[signer, writable]` 中继者（付费账户）
+    pub relayer: &'a AccountInfo<'info>,
+    /// `[writable]` 持有人的代币账户
+    pub source: &'a AccountInfo<'info>,
+    /// `[writable]` 接收方的代币账户
+    pub destination: &'a AccountInfo<'info>,
+    /// `[]` 持有人的委托 PDA
+    pub delegate: &'a AccountInfo<'info>,
+    /// `[writable]` nonce 记录 PDA
+    pub nonce_account: &'a AccountInfo<'info>,
+    /// `[]` instructions sysvar
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> TransferWithAuthorizationAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            relayer: next_account_info(accounts_iter)?,
+            source: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            delegate: next_account_info(accounts_iter)?,
+            nonce_account: next_account_info(accounts_iter)?,
+            instructions_sysvar: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.relayer)?;
+        check_writable(ctx.relayer)?;
+        check_token_owned_writable(ctx.source)?;
+        check_token_owned_writable(ctx.destination)?;
+        check_writable(ctx.nonce_account)?;
+        check_instructions_sysvar(ctx.instructions_sysvar)?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理签名授权转账
+pub fn process_transfer_with_authorization(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    from: Pubkey,
+    to: Pubkey,
+    amount: u64,
+    nonce: [u8; 32],
+    expiry: i64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let TransferWithAuthorizationAccounts {
+        relayer,
+        source,
+        destination,
+        delegate,
+        nonce_account,
+        instructions_sysvar,
+        system_program: system_program_account,
+        token_program,
+    } = TransferWithAuthorizationAccounts::parse(accounts)?;
+
+    if amount == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+    if Clock::get()?.unix_timestamp > expiry {
+        return Err(TokenError::AuthorizationExpired.into());
+    }
+
+    let mint = SplAccount::unpack(&source.data.borrow())?.mint;
+    validate_token_account(source, &mint, &from)?;
+    validate_token_account(destination, &mint, &to)?;
+
+    // 持有人的签名由紧挨着的 Ed25519 验签指令校验
+    let message = permit_message(program_id, &mint, &from, &to, amount, &nonce, expiry);
+    verify_ed25519_instruction(instructions_sysvar, &from, &message)?;
+
+    let (delegate_address, delegate_bump) = find_permit_delegate_address(program_id, &from);
+    if delegate_address != *delegate.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+
+    // 创建 nonce 记录，同一 nonce 不能再次使用
+    let (nonce_address, nonce_bump) = find_permit_nonce_address(program_id, &from, &nonce);
+    if nonce_address != *nonce_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if !nonce_account.data_is_empty() {
+        return Err(TokenError::NonceAlreadyUsed.into());
+    }
+    create_pda_account(
+        relayer,
+        nonce_account,
+        system_program_account,
+        program_id,
+        PermitNonce::LEN,
+        &[PERMIT_NONCE_SEED, from.as_ref(), &nonce, &[nonce_bump]],
+    )?;
+    PermitNonce {
+        is_initialized: true,
+    }
+    .pack_account(&mut nonce_account.data.borrow_mut())?;
+
+    invoke_signed(
+        &transfer(
+            token_program.key,
+            source.key,
+            destination.key,
+            delegate.key,
+            &[],
+            amount,
+        )?,
+        &[source.clone(), destination.clone(), delegate.clone()],
+        &[&[PERMIT_DELEGATE_SEED, from.as_ref(), &[delegate_bump]]],
+    )?;
+
+    TokenEvent::TransferAuthorized {
+        from,
+        to,
+        amount,
+        nonce,
+        relayer: *relayer.key,
+    }
+    .emit();
+
+    log_msg!("签名授权转账: {} 从 {} 到 {}", amount, from, to);
+
+    Ok(())
+}
+
+/// 构建 `TransferWithAuthorization` 指令
+///
+/// `source` 与 `destination` 分别为 `from` 和 `to` 在同一铸造账户下的代币账户。
+/// 交易中需要紧挨着在它之前放置 [`ed25519_verify_instruction`]。
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_with_authorization(
+    program_id: &Pubkey,
+    relayer: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    from: &Pubkey,
+    to: &Pubkey,
+    amount: u64,
+    nonce: [u8; 32],
+    expiry: i64,
+) -> Instruction {
+    let (delegate, _) = find_permit_delegate_address(program_id, from);
+    let (nonce_account, _) = find_permit_nonce_address(program_id, from, &nonce);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::TransferWithAuthorization {
+            from: *from,
+            to: *to,
+            amount,
+            nonce,
+            expiry,
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(delegate, false),
+            AccountMeta::new(nonce_account, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建把代币账户授权给持有人委托 PDA 的 SPL Token `Approve` 指令，由持有人签名
+pub fn approve_permit_delegate(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    token_account: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let (delegate, _) = find_permit_delegate_address(program_id, owner);
+    spl_token::instruction::approve(
+        &spl_token::id(),
+        token_account,
+        &delegate,
+        owner,
+        &[],
+        amount,
+    )
+}
+
+/// 用持有人的公钥、签名和消息构建 Ed25519 程序的验签指令
+///
+/// 中继者只持有签名而没有持有人的私钥，因此不能使用 SDK 中需要私钥的构建函数。
+pub fn ed25519_verify_instruction(
+    signer: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Instruction {
+    let public_key_offset = ED25519_DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for value in [
+        signature_offset as u16,
+        ED25519_CURRENT_INSTRUCTION,
+        public_key_offset as u16,
+        ED25519_CURRENT_INSTRUCTION,
+        message_offset as u16,
+        message.len() as u16,
+        ED25519_CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// 确认当前指令之前的一条指令是 Ed25519 程序对 `expected_signer` 与 `expected_message` 的验签
+///
+/// 签名本身由运行时在执行交易前校验，这里只需确认验签的公钥与消息。
+fn verify_ed25519_instruction(
+    instructions_sysvar: &AccountInfo,
+    expected_signer: &Pubkey,
+    expected_message: &[u8],
+) -> ProgramResult {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    let previous_index = current_index
+        .checked_sub(1)
+        .ok_or(TokenError::InvalidAuthorizationSignature)?;
+    let instruction = load_instruction_at_checked(previous_index as usize, instructions_sysvar)?;
+    if instruction.program_id != ed25519_program::id() {
+        return Err(TokenError::InvalidAuthorizationSignature.into());
+    }
+
+    let data = &instruction.data;
+    if data.len() < ED25519_DATA_START || data[0] != 1 {
+        return Err(TokenError::InvalidAuthorizationSignature.into());
+    }
+    let read_u16 = |index: usize| {
+        let offset = 2 + index * 2;
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    };
+    let (signature_index, public_key_offset, public_key_index) =
+        (read_u16(1), read_u16(2) as usize, read_u16(3));
+    let (message_offset, message_size, message_index) =
+        (read_u16(4) as usize, read_u16(5) as usize, read_u16(6));
+
+    // 公钥、签名与消息都必须位于验签指令自身的数据中
+    if [signature_index, public_key_index, message_index]
+        .iter()
+        .any(|index| *index != ED25519_CURRENT_INSTRUCTION)
+    {
+        return Err(TokenError::InvalidAuthorizationSignature.into());
+    }
+    let public_key = data.get(public_key_offset..public_key_offset + 32);
+    let message = data.get(message_offset..message_offset + message_size);
+    if public_key != Some(expected_signer.as_ref()) || message != Some(expected_message) {
+        return Err(TokenError::InvalidAuthorizationSignature.into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,786 @@
+//! 联合曲线发售
+//!
+//! `CreateSale` 把铸造权限交给发售 PDA，之后 `Buy` 按曲线价格收取 lamports 并由 PDA 铸造代币，
+//! `Sell` 销毁代币并沿曲线退款。发售 PDA 中的储备始终等于曲线从 0 到已售数量的积分
+//! [`Curve::reserve_at`]，买卖都按储备差额计价，因此任何买卖顺序下储备都足以支付退款。
+//!
+//! 到达 `end`（或售罄）后任何人都可以调用 `GraduateSale`：永久停止买入并放弃铸造权限，
+//! 总量此后只会因卖出而减少。本 crate 没有 AMM 程序，储备不会转给发起人，而是继续锁定在发售 PDA 中，
+//! 持有人在开始后（包括结束和毕业之后）随时可以用 `Sell` 沿曲线赎回。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+use spl_token::{
+    instruction::{burn, mint_to, set_authority, AuthorityType as SplAuthorityType},
+    state::Mint,
+};
+
+use crate::{
+    accounts::{
+        check_owner, check_program_account, check_signer, check_system_program,
+        check_token_owned_writable, check_token_program, check_writable,
+    },
+    create_pda_account,
+    events::TokenEvent,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    validate_token_account, TokenError, TokenInstruction,
+};
+
+/// 发售 PDA 种子前缀
+pub const SALE_SEED: &[u8] = b"sale";
+
+/// 指数曲线定点计算的精度
+const CURVE_PRECISION: u128 = 1_000_000_000_000;
+/// 基点分母
+const BPS_DENOMINATOR: u128 = 10_000;
+/// 支持的最大代币精度，保证 `10^decimals` 不溢出
+const MAX_SALE_DECIMALS: u8 = 18;
+
+/// 价格曲线类型
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Curve {
+    /// 线性曲线：每售出 1 个代币，价格增加 `growth` lamports
+    Linear,
+    /// 指数曲线：每售出 1 个完整代币，价格上涨 `growth` 个基点
+    Exponential,
+}
+
+/// 曲线参数
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurveParams {
+    /// 初始价格（lamports / 代币）
+    pub initial_price: u64,
+    /// 价格增长：线性曲线为 lamports / 代币，指数曲线为基点 / 代币
+    pub growth: u64,
+}
+
+impl Curve {
+    /// 已售 `supply`（最小单位）时曲线积分，即发售 PDA 应持有的储备（lamports，向下取整）
+    pub fn reserve_at(
+        &self,
+        params: &CurveParams,
+        supply: u64,
+        decimals: u8,
+    ) -> Result<u64, ProgramError> {
+        let unit = 10u128.pow(decimals as u32);
+        let supply = supply as u128;
+        let initial_price = params.initial_price as u128;
+        let growth = params.growth as u128;
+
+        let reserve = match self {
+            Curve::Linear => {
+                // ∫ (p0 + g·x) dx = p0·s + g·s²/2，s 以完整代币计
+                let base = initial_price
+                    .checked_mul(supply)
+                    .ok_or(TokenError::Overflow)?
+                    / unit;
+                let squared = supply.checked_mul(supply).ok_or(TokenError::Overflow)? / unit;
+                let slope = growth.checked_mul(squared).ok_or(TokenError::Overflow)? / (2 * unit);
+                base.checked_add(slope).ok_or(TokenError::Overflow)?
+            }
+            Curve::Exponential if growth == 0 => {
+                initial_price
+                    .checked_mul(supply)
+                    .ok_or(TokenError::Overflow)?
+                    / unit
+            }
+            Curve::Exponential => {
+                // 第 n 个完整代币的价格为 p0·q^n，前 n 个代币为等比数列之和，余下部分按 p0·q^n 计价
+                let ratio = CURVE_PRECISION + growth * CURVE_PRECISION / BPS_DENOMINATOR;
+                let whole = u32::try_from(supply / unit).map_err(|_| TokenError::Overflow)?;
+                let partial = supply % unit;
+                let power = pow_fixed(ratio, whole)?;
+                let series = (power - CURVE_PRECISION)
+                    .checked_mul(CURVE_PRECISION)
+                    .ok_or(TokenError::Overflow)?
+                    / (ratio - CURVE_PRECISION);
+                let full = initial_price
+                    .checked_mul(series)
+                    .ok_or(TokenError::Overflow)?
+                    / CURVE_PRECISION;
+                let rest = initial_price
+                    .checked_mul(power)
+                    .and_then(|value| value.checked_mul(partial))
+                    .ok_or(TokenError::Overflow)?
+                    / CURVE_PRECISION
+                    / unit;
+                full.checked_add(rest).ok_or(TokenError::Overflow)?
+            }
+        };
+        u64::try_from(reserve).map_err(|_| TokenError::Overflow.into())
+    }
+
+    /// 已售 `sold` 时再买入 `amount` 需要支付的 lamports
+    pub fn buy_cost(
+        &self,
+        params: &CurveParams,
+        sold: u64,
+        amount: u64,
+        decimals: u8,
+    ) -> Result<u64, ProgramError> {
+        let after = sold.checked_add(amount).ok_or(TokenError::Overflow)?;
+        self.reserve_at(params, after, decimals)?
+            .checked_sub(self.reserve_at(params, sold, decimals)?)
+            .ok_or_else(|| TokenError::Overflow.into())
+    }
+
+    /// `max_lamports` 最多可以买入的数量，不超过 `remaining`
+    pub fn tokens_for_lamports(
+        &self,
+        params: &CurveParams,
+        sold: u64,
+        max_lamports: u64,
+        remaining: u64,
+        decimals: u8,
+    ) -> u64 {
+        // 成本随数量单调递增，二分查找不超过预算的最大数量；溢出视为超出预算
+        let (mut low, mut high) = (0u64, remaining);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            match self.buy_cost(params, sold, mid, decimals) {
+                Ok(cost) if cost <= max_lamports => low = mid,
+                _ => high = mid - 1,
+            }
+        }
+        low
+    }
+}
+
+/// 定点数整数次幂
+fn pow_fixed(mut base: u128, mut exponent: u32) -> Result<u128, ProgramError> {
+    let mut result = CURVE_PRECISION;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.checked_mul(base).ok_or(TokenError::Overflow)? / CURVE_PRECISION;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = base.checked_mul(base).ok_or(TokenError::Overflow)? / CURVE_PRECISION;
+        }
+    }
+    Ok(result)
+}
+
+/// 发售状态
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Sale {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 发起人，创建发售时的铸造权限，不参与储备分配
+    pub creator: Pubkey,
+    /// 发售的代币铸造账户
+    pub mint: Pubkey,
+    /// 代币精度
+    pub decimals: u8,
+    /// 价格曲线
+    pub curve: Curve,
+    /// 曲线参数
+    pub params: CurveParams,
+    /// 最多售出的数量（最小单位）
+    pub cap: u64,
+    /// 开始时间（unix 时间戳）
+    pub start: i64,
+    /// 结束时间（unix 时间戳）
+    pub end: i64,
+    /// 已售出的数量
+    pub sold: u64,
+    /// 储备的 lamports
+    pub reserve: u64,
+    /// 是否已毕业
+    pub graduated: bool,
+    /// 发售 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for Sale {
+    const ACCOUNT_TYPE: AccountType = AccountType::Sale;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 * 2 + 1 + 1 + 16 + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+}
+
+impl Sale {
+    /// 当前是否可以买入
+    fn is_active(&self, now: i64) -> bool {
+        !self.graduated && now >= self.start && now < self.end
+    }
+
+    /// 当前是否可以卖出，储备锁定在发售 PDA 中，开始后始终可以沿曲线赎回
+    fn is_redeemable(&self, now: i64) -> bool {
+        now >= self.start
+    }
+}
+
+/// 计算发售 PDA 地址，发售 PDA 同时是代币的铸造权限
+pub fn find_sale_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SALE_SEED, mint.as_ref()], program_id)
+}
+
+/// `CreateSale` 指令的账户
+pub struct CreateSaleAccounts<'a, 'info> {
+    /// `[signer, writable]` 发起人，同时是铸造账户当前的铸造权限（付费账户）
+    pub creator: &'a AccountInfo<'info>,
+    /// `[writable]` 发售 PDA
+    pub sale: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateSaleAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            creator: next_account_info(accounts_iter)?,
+            sale: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.creator)?;
+        check_writable(ctx.creator)?;
+        check_writable(ctx.sale)?;
+        check_token_owned_writable(ctx.mint)?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `Buy` 指令的账户
+pub struct BuyAccounts<'a, 'info> {
+    /// `[signer, writable]` 买方，支付 lamports
+    pub buyer: &'a AccountInfo<'info>,
+    /// `[writable]` 发售 PDA
+    pub sale: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 买方的代币账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> BuyAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            buyer: next_account_info(accounts_iter)?,
+            sale: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.buyer)?;
+        check_writable(ctx.buyer)?;
+        check_writable(ctx.sale)?;
+        check_owner(ctx.sale, program_id)?;
+        check_token_owned_writable(ctx.mint)?;
+        check_token_owned_writable(ctx.token_account)?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `Sell` 指令的账户
+pub struct SellAccounts<'a, 'info> {
+    /// `[signer, writable]` 卖方，接收退款
+    pub seller: &'a AccountInfo<'info>,
+    /// `[writable]` 发售 PDA
+    pub sale: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[writable]` 卖方的代币账户
+    pub token_account: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SellAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            seller: next_account_info(accounts_iter)?,
+            sale: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            token_account: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.seller)?;
+        check_writable(ctx.seller)?;
+        check_writable(ctx.sale)?;
+        check_owner(ctx.sale, program_id)?;
+        check_token_owned_writable(ctx.mint)?;
+        check_token_owned_writable(ctx.token_account)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `GraduateSale` 指令的账户
+pub struct GraduateSaleAccounts<'a, 'info> {
+    /// `[writable]` 发售 PDA
+    pub sale: &'a AccountInfo<'info>,
+    /// `[writable]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> GraduateSaleAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            sale: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_writable(ctx.sale)?;
+        check_owner(ctx.sale, program_id)?;
+        check_token_owned_writable(ctx.mint)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理发售创建
+#[allow(clippy::too_many_arguments)]
+pub fn process_create_sale(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    curve: Curve,
+    params: CurveParams,
+    cap: u64,
+    start: i64,
+    end: i64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let CreateSaleAccounts {
+        creator,
+        sale: sale_account,
+        mint,
+        system_program: system_program_account,
+        token_program,
+    } = CreateSaleAccounts::parse(accounts)?;
+
+    let mint_state = Mint::unpack(&mint.data.borrow())?;
+    if cap == 0
+        || params.initial_price == 0
+        || start >= end
+        || mint_state.supply != 0
+        || mint_state.decimals > MAX_SALE_DECIMALS
+    {
+        return Err(TokenError::InvalidSaleConfig.into());
+    }
+    if mint_state.mint_authority != COption::Some(*creator.key) {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    // 售罄时的储备必须可以计算，避免发售中途溢出
+    curve.reserve_at(&params, cap, mint_state.decimals)?;
+
+    let (sale_address, bump) = find_sale_address(program_id, mint.key);
+    if sale_address != *sale_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if !sale_account.data_is_empty() {
+        return Err(TokenError::AccountAlreadyInitialized.into());
+    }
+
+    create_pda_account(
+        creator,
+        sale_account,
+        system_program_account,
+        program_id,
+        Sale::LEN,
+        &[SALE_SEED, mint.key.as_ref(), &[bump]],
+    )?;
+
+    // 铸造权限交给发售 PDA
+    invoke(
+        &set_authority(
+            token_program.key,
+            mint.key,
+            Some(&sale_address),
+            SplAuthorityType::MintTokens,
+            creator.key,
+            &[],
+        )?,
+        &[mint.clone(), creator.clone()],
+    )?;
+
+    let sale = Sale {
+        is_initialized: true,
+        creator: *creator.key,
+        mint: *mint.key,
+        decimals: mint_state.decimals,
+        curve,
+        params,
+        cap,
+        start,
+        end,
+        sold: 0,
+        reserve: 0,
+        graduated: false,
+        bump,
+    };
+    sale.pack_account(&mut sale_account.data.borrow_mut())?;
+
+    TokenEvent::SaleCreated {
+        sale: sale_address,
+        mint: *mint.key,
+        creator: *creator.key,
+        curve,
+        cap,
+        start,
+        end,
+    }
+    .emit();
+
+    log_msg!("发售已创建: {}", sale_address);
+    log_msg!("上限: {}，时间: {} - {}", cap, start, end);
+
+    Ok(())
+}
+
+/// 处理买入
+pub fn process_buy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_lamports: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let BuyAccounts {
+        buyer,
+        sale: sale_account,
+        mint,
+        token_account,
+        system_program: system_program_account,
+        token_program,
+    } = BuyAccounts::parse(program_id, accounts)?;
+
+    let mut sale = load_sale(sale_account, mint)?;
+    if !sale.is_active(Clock::get()?.unix_timestamp) {
+        return Err(TokenError::SaleNotActive.into());
+    }
+    validate_token_account(token_account, &sale.mint, buyer.key)?;
+
+    if max_lamports == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+    let remaining = sale.cap - sale.sold;
+    if remaining == 0 {
+        return Err(TokenError::SaleSoldOut.into());
+    }
+    let amount = sale.curve.tokens_for_lamports(
+        &sale.params,
+        sale.sold,
+        max_lamports,
+        remaining,
+        sale.decimals,
+    );
+    if amount == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+    let cost = sale
+        .curve
+        .buy_cost(&sale.params, sale.sold, amount, sale.decimals)?;
+
+    invoke(
+        &system_instruction::transfer(buyer.key, sale_account.key, cost),
+        &[
+            buyer.clone(),
+            sale_account.clone(),
+            system_program_account.clone(),
+        ],
+    )?;
+    invoke_signed(
+        &mint_to(
+            token_program.key,
+            mint.key,
+            token_account.key,
+            sale_account.key,
+            &[],
+            amount,
+        )?,
+        &[mint.clone(), token_account.clone(), sale_account.clone()],
+        &[&[SALE_SEED, mint.key.as_ref(), &[sale.bump]]],
+    )?;
+
+    sale.sold += amount;
+    sale.reserve = sale.reserve.checked_add(cost).ok_or(TokenError::Overflow)?;
+    sale.pack_account(&mut sale_account.data.borrow_mut())?;
+
+    TokenEvent::TokensBought {
+        sale: *sale_account.key,
+        buyer: *buyer.key,
+        amount,
+        lamports: cost,
+    }
+    .emit();
+
+    log_msg!("买入 {} 个代币，支付 {} lamports", amount, cost);
+
+    Ok(())
+}
+
+/// 处理卖出
+pub fn process_sell(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let SellAccounts {
+        seller,
+        sale: sale_account,
+        mint,
+        token_account,
+        token_program,
+    } = SellAccounts::parse(program_id, accounts)?;
+
+    let mut sale = load_sale(sale_account, mint)?;
+    if !sale.is_redeemable(Clock::get()?.unix_timestamp) {
+        return Err(TokenError::SaleNotActive.into());
+    }
+    if amount == 0 || amount > sale.sold {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    let remaining = sale.sold - amount;
+    let refund = sale
+        .curve
+        .buy_cost(&sale.params, remaining, amount, sale.decimals)?;
+
+    invoke(
+        &burn(
+            token_program.key,
+            token_account.key,
+            mint.key,
+            seller.key,
+            &[],
+            amount,
+        )?,
+        &[token_account.clone(), mint.clone(), seller.clone()],
+    )?;
+
+    // 发售 PDA 由本程序持有，直接扣减 lamports 退款
+    **sale_account.try_borrow_mut_lamports()? -= refund;
+    **seller.try_borrow_mut_lamports()? = seller
+        .lamports()
+        .checked_add(refund)
+        .ok_or(TokenError::Overflow)?;
+
+    sale.sold = remaining;
+    sale.reserve = sale
+        .reserve
+        .checked_sub(refund)
+        .ok_or(TokenError::Overflow)?;
+    sale.pack_account(&mut sale_account.data.borrow_mut())?;
+
+    TokenEvent::TokensSold {
+        sale: *sale_account.key,
+        seller: *seller.key,
+        amount,
+        lamports: refund,
+    }
+    .emit();
+
+    log_msg!("卖出 {} 个代币，退款 {} lamports", amount, refund);
+
+    Ok(())
+}
+
+/// 处理发售毕业
+pub fn process_graduate_sale(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let GraduateSaleAccounts {
+        sale: sale_account,
+        mint,
+        token_program,
+    } = GraduateSaleAccounts::parse(program_id, accounts)?;
+
+    let mut sale = load_sale(sale_account, mint)?;
+    if sale.graduated {
+        return Err(TokenError::SaleNotActive.into());
+    }
+    if Clock::get()?.unix_timestamp < sale.end && sale.sold < sale.cap {
+        return Err(TokenError::SaleNotEnded.into());
+    }
+
+    // 永久放弃铸造权限，总量固定为已售数量
+    invoke_signed(
+        &set_authority(
+            token_program.key,
+            mint.key,
+            None,
+            SplAuthorityType::MintTokens,
+            sale_account.key,
+            &[],
+        )?,
+        &[mint.clone(), sale_account.clone()],
+        &[&[SALE_SEED, mint.key.as_ref(), &[sale.bump]]],
+    )?;
+
+    // 储备留在发售 PDA 中，供持有人继续沿曲线卖出
+    sale.graduated = true;
+    sale.pack_account(&mut sale_account.data.borrow_mut())?;
+
+    TokenEvent::SaleGraduated {
+        sale: *sale_account.key,
+        mint: sale.mint,
+        sold: sale.sold,
+        reserve: sale.reserve,
+    }
+    .emit();
+
+    log_msg!(
+        "发售已毕业: 售出 {}，锁定储备 {} lamports",
+        sale.sold,
+        sale.reserve
+    );
+
+    Ok(())
+}
+
+/// 构建 `CreateSale` 指令，`creator` 需为铸造账户当前的铸造权限
+#[allow(clippy::too_many_arguments)]
+pub fn create_sale(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    mint: &Pubkey,
+    curve: Curve,
+    params: CurveParams,
+    cap: u64,
+    start: i64,
+    end: i64,
+) -> Instruction {
+    let (sale, _) = find_sale_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::CreateSale {
+            curve,
+            params,
+            cap,
+            start,
+            end,
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*creator, true),
+            AccountMeta::new(sale, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `Buy` 指令
+pub fn buy(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    max_lamports: u64,
+) -> Instruction {
+    let (sale, _) = find_sale_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::Buy { max_lamports }.pack(),
+        accounts: vec![
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new(sale, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `Sell` 指令
+pub fn sell(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    mint: &Pubkey,
+    token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (sale, _) = find_sale_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::Sell { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*seller, true),
+            AccountMeta::new(sale, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `GraduateSale` 指令
+pub fn graduate_sale(program_id: &Pubkey, mint: &Pubkey) -> Instruction {
+    let (sale, _) = find_sale_address(program_id, mint);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::GraduateSale.pack(),
+        accounts: vec![
+            AccountMeta::new(sale, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 读取发售状态并确认铸造账户一致
+fn load_sale(sale_account: &AccountInfo, mint: &AccountInfo) -> Result<Sale, ProgramError> {
+    let sale = Sale::unpack_account(&sale_account.data.borrow())?;
+    if !sale.is_initialized {
+        return Err(TokenError::AccountNotInitialized.into());
+    }
+    if sale.mint != *mint.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+    Ok(sale)
+}
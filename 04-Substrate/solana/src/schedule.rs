@@ -0,0 +1,566 @@
+//! 定时转账
+//!
+//! 发送方通过 `ScheduleTransfer` 把代币转入由定时转账 PDA 持有的金库，
+//! 到达 `release_at`（unix 时间戳，取自 Clock sysvar）后任何人都可以调用
+//! `ExecuteScheduled` 把代币发给接收方；释放前发送方可以用 `CancelScheduled` 取回。
+//! 执行或取消后金库与定时转账账户都会关闭，租金退还给发送方。
+//! 金库中的全部余额（包括他人额外转入的代币）都会转给执行时的接收方或取消时的发送方，
+//! 避免余额不为零导致金库无法关闭。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::Sysvar,
+};
+use spl_token::{
+    instruction::{close_account, transfer},
+    state::Account as SplAccount,
+};
+
+use crate::{
+    accounts::{
+        check_owner, check_program_account, check_signer, check_system_program,
+        check_token_owned_writable, check_token_program, check_writable,
+    },
+    close_program_account, create_pda_account, create_pda_token_account,
+    events::TokenEvent,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    validate_token_account, TokenError, TokenInstruction,
+};
+
+/// 定时转账 PDA 种子前缀
+pub const SCHEDULE_SEED: &[u8] = b"schedule";
+/// 定时转账金库 PDA 种子前缀
+pub const SCHEDULE_VAULT_SEED: &[u8] = b"schedule_vault";
+
+/// 定时转账状态
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledTransfer {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 发送方
+    pub sender: Pubkey,
+    /// 接收方钱包地址
+    pub recipient: Pubkey,
+    /// 代币铸造账户
+    pub mint: Pubkey,
+    /// 转账数量
+    pub amount: u64,
+    /// 释放时间（unix 时间戳）
+    pub release_at: i64,
+    /// 定时转账 PDA bump
+    pub bump: u8,
+    /// 金库 PDA bump
+    pub vault_bump: u8,
+}
+
+impl ProgramAccount for ScheduledTransfer {
+    const ACCOUNT_TYPE: AccountType = AccountType::ScheduledTransfer;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 * 3 + 8 + 8 + 1 + 1;
+}
+
+/// 计算定时转账 PDA 地址
+///
+/// 同一发送方、接收方、铸造账户和释放时间只能存在一笔定时转账。
+pub fn find_schedule_address(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+    release_at: i64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SCHEDULE_SEED,
+            sender.as_ref(),
+            recipient.as_ref(),
+            mint.as_ref(),
+            &release_at.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// 计算定时转账金库 PDA 地址
+pub fn find_schedule_vault_address(program_id: &Pubkey, schedule: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SCHEDULE_VAULT_SEED, schedule.as_ref()], program_id)
+}
+
+/// `ScheduleTransfer` 指令的账户
+pub struct ScheduleTransferAccounts<'a, 'info> {
+    /// `[signer, writable]` 发送方（付费账户）
+    pub sender: &'a AccountInfo<'info>,
+    /// `[writable]` 定时转账 PDA
+    pub schedule: &'a AccountInfo<'info>,
+    /// `[writable]` 金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[writable]` 发送方的代币账户
+    pub source: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ScheduleTransferAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            sender: next_account_info(accounts_iter)?,
+            schedule: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            source: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.sender)?;
+        check_writable(ctx.sender)?;
+        check_writable(ctx.schedule)?;
+        check_writable(ctx.vault)?;
+        check_token_owned_writable(ctx.source)?;
+        check_owner(ctx.mint, &spl_token::id())?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `ExecuteScheduled` 指令的账户
+pub struct ExecuteScheduledAccounts<'a, 'info> {
+    /// `[writable]` 定时转账 PDA
+    pub schedule: &'a AccountInfo<'info>,
+    /// `[writable]` 金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[writable]` 接收方的代币账户
+    pub destination: &'a AccountInfo<'info>,
+    /// `[writable]` 发送方，接收退还的租金
+    pub sender: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ExecuteScheduledAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            schedule: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            sender: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_writable(ctx.schedule)?;
+        check_owner(ctx.schedule, program_id)?;
+        check_token_owned_writable(ctx.vault)?;
+        check_token_owned_writable(ctx.destination)?;
+        check_writable(ctx.sender)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `CancelScheduled` 指令的账户
+pub struct CancelScheduledAccounts<'a, 'info> {
+    /// `[signer, writable]` 发送方
+    pub sender: &'a AccountInfo<'info>,
+    /// `[writable]` 定时转账 PDA
+    pub schedule: &'a AccountInfo<'info>,
+    /// `[writable]` 金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[writable]` 接收退回代币的账户
+    pub destination: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CancelScheduledAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            sender: next_account_info(accounts_iter)?,
+            schedule: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.sender)?;
+        check_writable(ctx.sender)?;
+        check_writable(ctx.schedule)?;
+        check_owner(ctx.schedule, program_id)?;
+        check_token_owned_writable(ctx.vault)?;
+        check_token_owned_writable(ctx.destination)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理定时转账创建
+pub fn process_schedule_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    release_at: i64,
+    recipient: Pubkey,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ScheduleTransferAccounts {
+        sender,
+        schedule: schedule_account,
+        vault,
+        source,
+        mint,
+        system_program: system_program_account,
+        token_program,
+    } = ScheduleTransferAccounts::parse(accounts)?;
+
+    if amount == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+    if release_at <= Clock::get()?.unix_timestamp {
+        return Err(TokenError::InvalidReleaseTime.into());
+    }
+
+    let (schedule_address, bump) =
+        find_schedule_address(program_id, sender.key, &recipient, mint.key, release_at);
+    if schedule_address != *schedule_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if !schedule_account.data_is_empty() {
+        return Err(TokenError::AccountAlreadyInitialized.into());
+    }
+    let (vault_address, vault_bump) = find_schedule_vault_address(program_id, &schedule_address);
+    if vault_address != *vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+
+    create_pda_account(
+        sender,
+        schedule_account,
+        system_program_account,
+        program_id,
+        ScheduledTransfer::LEN,
+        &[
+            SCHEDULE_SEED,
+            sender.key.as_ref(),
+            recipient.as_ref(),
+            mint.key.as_ref(),
+            &release_at.to_le_bytes(),
+            &[bump],
+        ],
+    )?;
+    create_pda_token_account(
+        sender,
+        vault,
+        mint,
+        schedule_account,
+        system_program_account,
+        token_program,
+        &[
+            SCHEDULE_VAULT_SEED,
+            schedule_address.as_ref(),
+            &[vault_bump],
+        ],
+    )?;
+
+    // 发送方把代币转入金库
+    invoke(
+        &transfer(
+            token_program.key,
+            source.key,
+            vault.key,
+            sender.key,
+            &[],
+            amount,
+        )?,
+        &[source.clone(), vault.clone(), sender.clone()],
+    )?;
+
+    let schedule = ScheduledTransfer {
+        is_initialized: true,
+        sender: *sender.key,
+        recipient,
+        mint: *mint.key,
+        amount,
+        release_at,
+        bump,
+        vault_bump,
+    };
+    schedule.pack_account(&mut schedule_account.data.borrow_mut())?;
+
+    TokenEvent::TransferScheduled {
+        schedule: schedule_address,
+        sender: *sender.key,
+        recipient,
+        mint: *mint.key,
+        amount,
+        release_at,
+    }
+    .emit();
+
+    log_msg!("定时转账已创建: {}", schedule_address);
+    log_msg!("数量: {}，释放时间: {}", amount, release_at);
+
+    Ok(())
+}
+
+/// 处理定时转账执行
+pub fn process_execute_scheduled(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ExecuteScheduledAccounts {
+        schedule: schedule_account,
+        vault,
+        destination,
+        sender,
+        token_program,
+    } = ExecuteScheduledAccounts::parse(program_id, accounts)?;
+
+    let schedule = load_schedule(program_id, schedule_account, vault)?;
+    if schedule.sender != *sender.key {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    validate_token_account(destination, &schedule.mint, &schedule.recipient)?;
+    if Clock::get()?.unix_timestamp < schedule.release_at {
+        return Err(TokenError::ScheduleNotReleased.into());
+    }
+
+    release_vault(
+        &schedule,
+        schedule_account,
+        vault,
+        destination,
+        sender,
+        token_program,
+    )?;
+
+    TokenEvent::ScheduledTransferExecuted {
+        schedule: *schedule_account.key,
+        recipient: schedule.recipient,
+        amount: schedule.amount,
+    }
+    .emit();
+
+    log_msg!(
+        "定时转账已执行: {} 个代币转给 {}",
+        schedule.amount,
+        schedule.recipient
+    );
+
+    Ok(())
+}
+
+/// 处理定时转账取消
+pub fn process_cancel_scheduled(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let CancelScheduledAccounts {
+        sender,
+        schedule: schedule_account,
+        vault,
+        destination,
+        token_program,
+    } = CancelScheduledAccounts::parse(program_id, accounts)?;
+
+    let schedule = load_schedule(program_id, schedule_account, vault)?;
+    if schedule.sender != *sender.key {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    validate_token_account(destination, &schedule.mint, sender.key)?;
+    if Clock::get()?.unix_timestamp >= schedule.release_at {
+        return Err(TokenError::ScheduleAlreadyReleased.into());
+    }
+
+    release_vault(
+        &schedule,
+        schedule_account,
+        vault,
+        destination,
+        sender,
+        token_program,
+    )?;
+
+    TokenEvent::ScheduledTransferCancelled {
+        schedule: *schedule_account.key,
+        sender: schedule.sender,
+        amount: schedule.amount,
+    }
+    .emit();
+
+    log_msg!("定时转账已取消: {}", schedule_account.key);
+
+    Ok(())
+}
+
+/// 构建 `ScheduleTransfer` 指令
+pub fn schedule_transfer(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    release_at: i64,
+) -> Instruction {
+    let (schedule, _) = find_schedule_address(program_id, sender, recipient, mint, release_at);
+    let (vault, _) = find_schedule_vault_address(program_id, &schedule);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::ScheduleTransfer {
+            amount,
+            release_at,
+            recipient: *recipient,
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*sender, true),
+            AccountMeta::new(schedule, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `ExecuteScheduled` 指令，`destination` 为接收方在该铸造账户下的代币账户
+pub fn execute_scheduled(
+    program_id: &Pubkey,
+    schedule: &Pubkey,
+    sender: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let (vault, _) = find_schedule_vault_address(program_id, schedule);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::ExecuteScheduled.pack(),
+        accounts: vec![
+            AccountMeta::new(*schedule, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new(*sender, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `CancelScheduled` 指令，`destination` 为发送方接收退回代币的账户
+pub fn cancel_scheduled(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    schedule: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let (vault, _) = find_schedule_vault_address(program_id, schedule);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::CancelScheduled.pack(),
+        accounts: vec![
+            AccountMeta::new(*sender, true),
+            AccountMeta::new(*schedule, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 读取定时转账并校验金库地址
+fn load_schedule(
+    program_id: &Pubkey,
+    schedule_account: &AccountInfo,
+    vault: &AccountInfo,
+) -> Result<ScheduledTransfer, ProgramError> {
+    let schedule = ScheduledTransfer::unpack_account(&schedule_account.data.borrow())?;
+    if !schedule.is_initialized {
+        return Err(TokenError::AccountNotInitialized.into());
+    }
+    let expected = Pubkey::create_program_address(
+        &[
+            SCHEDULE_VAULT_SEED,
+            schedule_account.key.as_ref(),
+            &[schedule.vault_bump],
+        ],
+        program_id,
+    )?;
+    if expected != *vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    Ok(schedule)
+}
+
+/// 把金库中的全部代币转给 `destination`，然后关闭金库与定时转账账户，租金退还给发送方
+fn release_vault<'a>(
+    schedule: &ScheduledTransfer,
+    schedule_account: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    sender: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let release_at = schedule.release_at.to_le_bytes();
+    let bump = [schedule.bump];
+    let schedule_seeds: &[&[u8]] = &[
+        SCHEDULE_SEED,
+        schedule.sender.as_ref(),
+        schedule.recipient.as_ref(),
+        schedule.mint.as_ref(),
+        &release_at,
+        &bump,
+    ];
+
+    // 他人可以向金库额外转入代币，按实际余额转出才能关闭金库
+    let balance = SplAccount::unpack(&vault.data.borrow())?.amount;
+    invoke_signed(
+        &transfer(
+            token_program.key,
+            vault.key,
+            destination.key,
+            schedule_account.key,
+            &[],
+            balance,
+        )?,
+        &[vault.clone(), destination.clone(), schedule_account.clone()],
+        &[schedule_seeds],
+    )?;
+    invoke_signed(
+        &close_account(
+            token_program.key,
+            vault.key,
+            sender.key,
+            schedule_account.key,
+            &[],
+        )?,
+        &[vault.clone(), sender.clone(), schedule_account.clone()],
+        &[schedule_seeds],
+    )?;
+    close_program_account(schedule_account, sender)
+}
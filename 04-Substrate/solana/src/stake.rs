@@ -0,0 +1,998 @@
+//! 单币质押池
+//!
+//! 对应 EVM 版本 `02-stake/contracts/StakePool.sol` 的设计：用户质押代币，
+//! 按 slot 累积奖励。质押池 PDA 记录每份质押累积的奖励（`acc_reward_per_share`），
+//! 用户质押 PDA 记录已结算的奖励债务，奖励通过 CPI 铸造或从奖励金库支付。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::Sysvar,
+};
+use spl_token::state::Mint;
+
+use crate::{
+    accounts::{
+        check_owner, check_program_account, check_signer, check_system_program,
+        check_token_owned_writable, check_token_program, check_writable,
+    },
+    create_pda_account, create_pda_token_account,
+    events::TokenEvent,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    TokenError, TokenInstruction,
+};
+
+/// 质押池 PDA 种子前缀
+pub const STAKE_POOL_SEED: &[u8] = b"stake_pool";
+/// 用户质押 PDA 种子前缀
+pub const USER_STAKE_SEED: &[u8] = b"user_stake";
+/// 质押金库 PDA 种子前缀
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+/// 奖励金库 PDA 种子前缀
+pub const REWARD_VAULT_SEED: &[u8] = b"reward_vault";
+
+/// 累积奖励的放大倍数，与 EVM 版本的 1e12 保持一致
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// 每个 slot 奖励数量的上限
+///
+/// 即使池中只有 1 个最小单位的质押，`acc_reward_per_share` 每个 slot 也最多增加
+/// `MAX_REWARD_PER_SLOT * REWARD_PRECISION`（1e24），数千年内不会超出 `u128`。
+pub const MAX_REWARD_PER_SLOT: u64 = 1_000_000_000_000;
+
+/// 奖励发放方式
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardSource {
+    /// 质押池 PDA 作为奖励代币的铸造权限，领取时直接铸造
+    Mint,
+    /// 从质押池持有的奖励金库中转出，需要事先向金库注资
+    Vault,
+}
+
+/// 质押池状态
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct StakePool {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 质押池管理员
+    pub authority: Pubkey,
+    /// 质押代币铸造账户
+    pub stake_mint: Pubkey,
+    /// 奖励代币铸造账户
+    pub reward_mint: Pubkey,
+    /// 奖励发放方式
+    pub reward_source: RewardSource,
+    /// 每个 slot 发放的奖励数量
+    pub reward_per_slot: u64,
+    /// 质押锁定时长（秒）
+    pub lock_period: i64,
+    /// 每份质押累积的奖励（放大 `REWARD_PRECISION` 倍）
+    pub acc_reward_per_share: u128,
+    /// 上次更新奖励的 slot
+    pub last_update_slot: u64,
+    /// 池中总质押数量
+    pub total_staked: u64,
+    /// 质押池 PDA bump
+    pub bump: u8,
+    /// 质押金库 PDA bump
+    pub vault_bump: u8,
+    /// 奖励金库 PDA bump
+    pub reward_vault_bump: u8,
+}
+
+impl ProgramAccount for StakePool {
+    const ACCOUNT_TYPE: AccountType = AccountType::StakePool;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 * 3 + 1 + 8 + 8 + 16 + 8 + 8 + 1 + 1 + 1;
+}
+
+impl StakePool {
+    /// 将奖励累积到当前 slot
+    pub fn update(&mut self, current_slot: u64) -> Result<(), ProgramError> {
+        if current_slot <= self.last_update_slot {
+            return Ok(());
+        }
+        if self.total_staked > 0 {
+            let elapsed = (current_slot - self.last_update_slot) as u128;
+            let reward = elapsed
+                .checked_mul(self.reward_per_slot as u128)
+                .and_then(|r| r.checked_mul(REWARD_PRECISION))
+                .ok_or(TokenError::Overflow)?;
+            self.acc_reward_per_share = self
+                .acc_reward_per_share
+                .checked_add(reward / self.total_staked as u128)
+                .ok_or(TokenError::Overflow)?;
+        }
+        self.last_update_slot = current_slot;
+        Ok(())
+    }
+}
+
+/// 用户质押状态
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct UserStake {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 所属质押池
+    pub pool: Pubkey,
+    /// 质押用户
+    pub owner: Pubkey,
+    /// 质押数量
+    pub amount: u64,
+    /// 已结算的奖励债务
+    pub reward_debt: u128,
+    /// 已结算但尚未领取的奖励
+    pub pending_rewards: u64,
+    /// 解锁时间（unix 时间戳）
+    pub lock_until: i64,
+    /// 用户质押 PDA bump
+    pub bump: u8,
+}
+
+impl ProgramAccount for UserStake {
+    const ACCOUNT_TYPE: AccountType = AccountType::UserStake;
+    const VERSION: u8 = 2;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 * 2 + 8 + 16 + 8 + 8 + 1;
+
+    /// 版本 0 和 1 的奖励债务放大了 `REWARD_PRECISION` 倍，读取时换算为奖励数量
+    fn unpack_version(version: u8, body: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            0 | 1 => {
+                let mut user_stake = Self::deserialize(&mut &body[..])?;
+                user_stake.reward_debt /= REWARD_PRECISION;
+                Ok(user_stake)
+            }
+            _ => Err(TokenError::AccountVersionMismatch.into()),
+        }
+    }
+}
+
+impl UserStake {
+    /// 按当前质押数量计算截至 `acc_reward_per_share` 的累计奖励
+    ///
+    /// 即 `amount * acc_reward_per_share / REWARD_PRECISION`，把累积值拆成整数部分和余数分别相乘，
+    /// 中间结果不会溢出 `u128`。
+    pub fn accrued(&self, acc_reward_per_share: u128) -> Result<u128, ProgramError> {
+        let amount = self.amount as u128;
+        let whole = amount
+            .checked_mul(acc_reward_per_share / REWARD_PRECISION)
+            .ok_or(TokenError::Overflow)?;
+        let fraction = amount * (acc_reward_per_share % REWARD_PRECISION) / REWARD_PRECISION;
+        whole
+            .checked_add(fraction)
+            .ok_or_else(|| TokenError::Overflow.into())
+    }
+
+    /// 把截至 `acc_reward_per_share` 的奖励结算到 `pending_rewards`
+    pub fn settle(&mut self, acc_reward_per_share: u128) -> Result<(), ProgramError> {
+        let pending = self
+            .accrued(acc_reward_per_share)?
+            .checked_sub(self.reward_debt)
+            .ok_or(TokenError::Overflow)?;
+        self.pending_rewards = self
+            .pending_rewards
+            .checked_add(pending.try_into().map_err(|_| TokenError::Overflow)?)
+            .ok_or(TokenError::Overflow)?;
+        Ok(())
+    }
+
+    /// 按当前质押数量重置奖励债务
+    pub fn reset_debt(&mut self, acc_reward_per_share: u128) -> Result<(), ProgramError> {
+        self.reward_debt = self.accrued(acc_reward_per_share)?;
+        Ok(())
+    }
+}
+
+/// 计算质押池 PDA 地址
+///
+/// 种子包含质押池管理员，每个管理员各自拥有同一对铸造账户的质押池，
+/// 其他人无法抢先创建并占用某个管理员的质押池地址。
+pub fn find_stake_pool_address(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    stake_mint: &Pubkey,
+    reward_mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            STAKE_POOL_SEED,
+            authority.as_ref(),
+            stake_mint.as_ref(),
+            reward_mint.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// 计算用户质押 PDA 地址
+pub fn find_user_stake_address(program_id: &Pubkey, pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[USER_STAKE_SEED, pool.as_ref(), owner.as_ref()],
+        program_id,
+    )
+}
+
+/// 计算质押金库 PDA 地址
+pub fn find_stake_vault_address(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_VAULT_SEED, pool.as_ref()], program_id)
+}
+
+/// 计算奖励金库 PDA 地址
+pub fn find_reward_vault_address(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_VAULT_SEED, pool.as_ref()], program_id)
+}
+
+/// `CreateStakePool` 指令的账户
+pub struct CreateStakePoolAccounts<'a, 'info> {
+    /// `[signer, writable]` 质押池管理员（付费账户）
+    pub authority: &'a AccountInfo<'info>,
+    /// `[writable]` 质押池 PDA
+    pub pool: &'a AccountInfo<'info>,
+    /// `[]` 质押代币铸造账户
+    pub stake_mint: &'a AccountInfo<'info>,
+    /// `[]` 奖励代币铸造账户
+    pub reward_mint: &'a AccountInfo<'info>,
+    /// `[writable]` 质押金库 PDA
+    pub stake_vault: &'a AccountInfo<'info>,
+    /// `[writable]` 奖励金库 PDA
+    pub reward_vault: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateStakePoolAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            authority: next_account_info(accounts_iter)?,
+            pool: next_account_info(accounts_iter)?,
+            stake_mint: next_account_info(accounts_iter)?,
+            reward_mint: next_account_info(accounts_iter)?,
+            stake_vault: next_account_info(accounts_iter)?,
+            reward_vault: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.authority)?;
+        check_writable(ctx.authority)?;
+        check_writable(ctx.pool)?;
+        check_owner(ctx.stake_mint, &spl_token::id())?;
+        check_owner(ctx.reward_mint, &spl_token::id())?;
+        check_writable(ctx.stake_vault)?;
+        check_writable(ctx.reward_vault)?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `Stake` 指令的账户
+pub struct StakeAccounts<'a, 'info> {
+    /// `[signer, writable]` 质押用户（付费账户）
+    pub owner: &'a AccountInfo<'info>,
+    /// `[writable]` 质押池 PDA
+    pub pool: &'a AccountInfo<'info>,
+    /// `[writable]` 用户质押 PDA
+    pub user_stake: &'a AccountInfo<'info>,
+    /// `[writable]` 用户的质押代币账户
+    pub source: &'a AccountInfo<'info>,
+    /// `[writable]` 质押金库 PDA
+    pub stake_vault: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> StakeAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            owner: next_account_info(accounts_iter)?,
+            pool: next_account_info(accounts_iter)?,
+            user_stake: next_account_info(accounts_iter)?,
+            source: next_account_info(accounts_iter)?,
+            stake_vault: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.owner)?;
+        check_writable(ctx.owner)?;
+        check_writable(ctx.pool)?;
+        check_owner(ctx.pool, program_id)?;
+        check_writable(ctx.user_stake)?;
+        // 首次质押时用户质押 PDA 尚未创建
+        if !ctx.user_stake.data_is_empty() {
+            check_owner(ctx.user_stake, program_id)?;
+        }
+        check_token_owned_writable(ctx.source)?;
+        check_token_owned_writable(ctx.stake_vault)?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `Unstake` 指令的账户
+pub struct UnstakeAccounts<'a, 'info> {
+    /// `[signer]` 质押用户
+    pub owner: &'a AccountInfo<'info>,
+    /// `[writable]` 质押池 PDA
+    pub pool: &'a AccountInfo<'info>,
+    /// `[writable]` 用户质押 PDA
+    pub user_stake: &'a AccountInfo<'info>,
+    /// `[writable]` 质押金库 PDA
+    pub stake_vault: &'a AccountInfo<'info>,
+    /// `[writable]` 接收质押代币的账户
+    pub destination: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> UnstakeAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            owner: next_account_info(accounts_iter)?,
+            pool: next_account_info(accounts_iter)?,
+            user_stake: next_account_info(accounts_iter)?,
+            stake_vault: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.owner)?;
+        check_writable(ctx.pool)?;
+        check_owner(ctx.pool, program_id)?;
+        check_writable(ctx.user_stake)?;
+        check_owner(ctx.user_stake, program_id)?;
+        check_token_owned_writable(ctx.stake_vault)?;
+        check_token_owned_writable(ctx.destination)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `ClaimRewards` 指令的账户
+pub struct ClaimRewardsAccounts<'a, 'info> {
+    /// `[signer]` 质押用户
+    pub owner: &'a AccountInfo<'info>,
+    /// `[writable]` 质押池 PDA
+    pub pool: &'a AccountInfo<'info>,
+    /// `[writable]` 用户质押 PDA
+    pub user_stake: &'a AccountInfo<'info>,
+    /// `[writable]` 奖励代币铸造账户
+    pub reward_mint: &'a AccountInfo<'info>,
+    /// `[writable]` 奖励金库 PDA（`RewardSource::Mint` 模式下未创建）
+    pub reward_vault: &'a AccountInfo<'info>,
+    /// `[writable]` 接收奖励的代币账户
+    pub destination: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ClaimRewardsAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            owner: next_account_info(accounts_iter)?,
+            pool: next_account_info(accounts_iter)?,
+            user_stake: next_account_info(accounts_iter)?,
+            reward_mint: next_account_info(accounts_iter)?,
+            reward_vault: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.owner)?;
+        check_writable(ctx.pool)?;
+        check_owner(ctx.pool, program_id)?;
+        check_writable(ctx.user_stake)?;
+        check_owner(ctx.user_stake, program_id)?;
+        check_token_owned_writable(ctx.reward_mint)?;
+        check_writable(ctx.reward_vault)?;
+        check_token_owned_writable(ctx.destination)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理质押池创建
+pub fn process_create_stake_pool(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reward_per_slot: u64,
+    lock_period: i64,
+    reward_source: RewardSource,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let CreateStakePoolAccounts {
+        authority,
+        pool: pool_account,
+        stake_mint,
+        reward_mint,
+        stake_vault,
+        reward_vault,
+        system_program: system_program_account,
+        token_program,
+    } = CreateStakePoolAccounts::parse(accounts)?;
+
+    if lock_period < 0 {
+        return Err(TokenError::InvalidLockPeriod.into());
+    }
+    if reward_per_slot > MAX_REWARD_PER_SLOT {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    let (pool_address, bump) =
+        find_stake_pool_address(program_id, authority.key, stake_mint.key, reward_mint.key);
+    if pool_address != *pool_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if !pool_account.data_is_empty() {
+        return Err(TokenError::AccountAlreadyInitialized.into());
+    }
+    let (vault_address, vault_bump) = find_stake_vault_address(program_id, pool_account.key);
+    if vault_address != *stake_vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    let (reward_vault_address, reward_vault_bump) =
+        find_reward_vault_address(program_id, pool_account.key);
+    if reward_vault_address != *reward_vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+
+    // 两个铸造账户都必须是有效的 SPL Token 铸造账户
+    for mint in [stake_mint, reward_mint] {
+        Mint::unpack(&mint.data.borrow())?;
+    }
+    if reward_source == RewardSource::Mint {
+        let mint = Mint::unpack(&reward_mint.data.borrow())?;
+        if mint.mint_authority != COption::Some(pool_address) {
+            return Err(TokenError::RewardMintAuthorityMismatch.into());
+        }
+    }
+
+    let pool_seeds: &[&[u8]] = &[
+        STAKE_POOL_SEED,
+        authority.key.as_ref(),
+        stake_mint.key.as_ref(),
+        reward_mint.key.as_ref(),
+        &[bump],
+    ];
+    create_pda_account(
+        authority,
+        pool_account,
+        system_program_account,
+        program_id,
+        StakePool::LEN,
+        pool_seeds,
+    )?;
+
+    create_pda_token_account(
+        authority,
+        stake_vault,
+        stake_mint,
+        pool_account,
+        system_program_account,
+        token_program,
+        &[STAKE_VAULT_SEED, pool_account.key.as_ref(), &[vault_bump]],
+    )?;
+    if reward_source == RewardSource::Vault {
+        create_pda_token_account(
+            authority,
+            reward_vault,
+            reward_mint,
+            pool_account,
+            system_program_account,
+            token_program,
+            &[
+                REWARD_VAULT_SEED,
+                pool_account.key.as_ref(),
+                &[reward_vault_bump],
+            ],
+        )?;
+    }
+
+    let pool = StakePool {
+        is_initialized: true,
+        authority: *authority.key,
+        stake_mint: *stake_mint.key,
+        reward_mint: *reward_mint.key,
+        reward_source,
+        reward_per_slot,
+        lock_period,
+        acc_reward_per_share: 0,
+        last_update_slot: Clock::get()?.slot,
+        total_staked: 0,
+        bump,
+        vault_bump,
+        reward_vault_bump,
+    };
+    pool.pack_account(&mut pool_account.data.borrow_mut())?;
+
+    TokenEvent::StakePoolCreated {
+        pool: *pool_account.key,
+        stake_mint: *stake_mint.key,
+        reward_mint: *reward_mint.key,
+        reward_per_slot,
+        lock_period,
+    }
+    .emit();
+
+    log_msg!("质押池创建完成: {}", pool_account.key);
+    log_msg!(
+        "每 slot 奖励: {}，锁定时长: {} 秒",
+        reward_per_slot,
+        lock_period
+    );
+
+    Ok(())
+}
+
+/// 处理质押
+pub fn process_stake(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let StakeAccounts {
+        owner,
+        pool: pool_account,
+        user_stake: user_stake_account,
+        source,
+        stake_vault,
+        system_program: system_program_account,
+        token_program,
+    } = StakeAccounts::parse(program_id, accounts)?;
+
+    if amount == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    let mut pool = load_pool(pool_account)?;
+    check_vault(
+        program_id,
+        pool_account,
+        stake_vault,
+        STAKE_VAULT_SEED,
+        pool.vault_bump,
+    )?;
+
+    let clock = Clock::get()?;
+    pool.update(clock.slot)?;
+
+    // 首次质押时创建用户质押 PDA
+    let (user_stake_address, user_bump) =
+        find_user_stake_address(program_id, pool_account.key, owner.key);
+    if user_stake_address != *user_stake_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    let mut user_stake = if user_stake_account.data_is_empty() {
+        create_pda_account(
+            owner,
+            user_stake_account,
+            system_program_account,
+            program_id,
+            UserStake::LEN,
+            &[
+                USER_STAKE_SEED,
+                pool_account.key.as_ref(),
+                owner.key.as_ref(),
+                &[user_bump],
+            ],
+        )?;
+        UserStake {
+            is_initialized: true,
+            pool: *pool_account.key,
+            owner: *owner.key,
+            amount: 0,
+            reward_debt: 0,
+            pending_rewards: 0,
+            lock_until: 0,
+            bump: user_bump,
+        }
+    } else {
+        load_user_stake(pool_account, owner, user_stake_account)?
+    };
+
+    user_stake.settle(pool.acc_reward_per_share)?;
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            source.key,
+            stake_vault.key,
+            owner.key,
+            &[],
+            amount,
+        )?,
+        &[source.clone(), stake_vault.clone(), owner.clone()],
+    )?;
+
+    user_stake.amount = user_stake
+        .amount
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    user_stake.reset_debt(pool.acc_reward_per_share)?;
+    user_stake.lock_until = clock
+        .unix_timestamp
+        .checked_add(pool.lock_period)
+        .ok_or(TokenError::Overflow)?;
+    pool.total_staked = pool
+        .total_staked
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+
+    pool.pack_account(&mut pool_account.data.borrow_mut())?;
+    user_stake.pack_account(&mut user_stake_account.data.borrow_mut())?;
+
+    TokenEvent::Staked {
+        pool: *pool_account.key,
+        owner: *owner.key,
+        amount,
+    }
+    .emit();
+
+    log_msg!("成功质押 {} 个代币", amount);
+
+    Ok(())
+}
+
+/// 处理解除质押
+pub fn process_unstake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let UnstakeAccounts {
+        owner,
+        pool: pool_account,
+        user_stake: user_stake_account,
+        stake_vault,
+        destination,
+        token_program,
+    } = UnstakeAccounts::parse(program_id, accounts)?;
+
+    if amount == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    let mut pool = load_pool(pool_account)?;
+    check_vault(
+        program_id,
+        pool_account,
+        stake_vault,
+        STAKE_VAULT_SEED,
+        pool.vault_bump,
+    )?;
+    let mut user_stake = load_user_stake(pool_account, owner, user_stake_account)?;
+
+    let clock = Clock::get()?;
+    if clock.unix_timestamp < user_stake.lock_until {
+        log_msg!("质押锁定至 {}", user_stake.lock_until);
+        return Err(TokenError::StakeLocked.into());
+    }
+    if user_stake.amount < amount {
+        return Err(TokenError::InsufficientBalance.into());
+    }
+
+    pool.update(clock.slot)?;
+    user_stake.settle(pool.acc_reward_per_share)?;
+
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            stake_vault.key,
+            destination.key,
+            pool_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            stake_vault.clone(),
+            destination.clone(),
+            pool_account.clone(),
+        ],
+        &[&pool_signer_seeds(&pool, &[pool.bump])],
+    )?;
+
+    user_stake.amount -= amount;
+    user_stake.reset_debt(pool.acc_reward_per_share)?;
+    pool.total_staked -= amount;
+
+    pool.pack_account(&mut pool_account.data.borrow_mut())?;
+    user_stake.pack_account(&mut user_stake_account.data.borrow_mut())?;
+
+    TokenEvent::Unstaked {
+        pool: *pool_account.key,
+        owner: *owner.key,
+        amount,
+    }
+    .emit();
+
+    log_msg!("成功解除质押 {} 个代币", amount);
+
+    Ok(())
+}
+
+/// 处理奖励领取
+pub fn process_claim_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ClaimRewardsAccounts {
+        owner,
+        pool: pool_account,
+        user_stake: user_stake_account,
+        reward_mint,
+        reward_vault,
+        destination,
+        token_program,
+    } = ClaimRewardsAccounts::parse(program_id, accounts)?;
+
+    let mut pool = load_pool(pool_account)?;
+    if pool.reward_mint != *reward_mint.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+    let mut user_stake = load_user_stake(pool_account, owner, user_stake_account)?;
+
+    pool.update(Clock::get()?.slot)?;
+    user_stake.settle(pool.acc_reward_per_share)?;
+    user_stake.reset_debt(pool.acc_reward_per_share)?;
+
+    let reward = user_stake.pending_rewards;
+    if reward > 0 {
+        let bump = [pool.bump];
+        let pool_seeds = pool_signer_seeds(&pool, &bump);
+        match pool.reward_source {
+            RewardSource::Mint => invoke_signed(
+                &spl_token::instruction::mint_to(
+                    token_program.key,
+                    reward_mint.key,
+                    destination.key,
+                    pool_account.key,
+                    &[],
+                    reward,
+                )?,
+                &[
+                    reward_mint.clone(),
+                    destination.clone(),
+                    pool_account.clone(),
+                ],
+                &[&pool_seeds],
+            )?,
+            RewardSource::Vault => {
+                check_vault(
+                    program_id,
+                    pool_account,
+                    reward_vault,
+                    REWARD_VAULT_SEED,
+                    pool.reward_vault_bump,
+                )?;
+                invoke_signed(
+                    &spl_token::instruction::transfer(
+                        token_program.key,
+                        reward_vault.key,
+                        destination.key,
+                        pool_account.key,
+                        &[],
+                        reward,
+                    )?,
+                    &[
+                        reward_vault.clone(),
+                        destination.clone(),
+                        pool_account.clone(),
+                    ],
+                    &[&pool_seeds],
+                )?
+            }
+        }
+        user_stake.pending_rewards = 0;
+    }
+
+    pool.pack_account(&mut pool_account.data.borrow_mut())?;
+    user_stake.pack_account(&mut user_stake_account.data.borrow_mut())?;
+
+    TokenEvent::RewardsClaimed {
+        pool: *pool_account.key,
+        owner: *owner.key,
+        amount: reward,
+    }
+    .emit();
+
+    log_msg!("成功领取 {} 个奖励代币", reward);
+
+    Ok(())
+}
+
+/// 构建 `CreateStakePool` 指令
+#[allow(clippy::too_many_arguments)]
+pub fn create_stake_pool(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    stake_mint: &Pubkey,
+    reward_mint: &Pubkey,
+    reward_per_slot: u64,
+    lock_period: i64,
+    reward_source: RewardSource,
+) -> Instruction {
+    let (pool, _) = find_stake_pool_address(program_id, authority, stake_mint, reward_mint);
+    let (stake_vault, _) = find_stake_vault_address(program_id, &pool);
+    let (reward_vault, _) = find_reward_vault_address(program_id, &pool);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::CreateStakePool {
+            reward_per_slot,
+            lock_period,
+            reward_source,
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(*stake_mint, false),
+            AccountMeta::new_readonly(*reward_mint, false),
+            AccountMeta::new(stake_vault, false),
+            AccountMeta::new(reward_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `Stake` 指令
+pub fn stake(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    pool: &Pubkey,
+    source: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (user_stake, _) = find_user_stake_address(program_id, pool, owner);
+    let (stake_vault, _) = find_stake_vault_address(program_id, pool);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::Stake { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(user_stake, false),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(stake_vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `Unstake` 指令
+pub fn unstake(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    pool: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (user_stake, _) = find_user_stake_address(program_id, pool, owner);
+    let (stake_vault, _) = find_stake_vault_address(program_id, pool);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::Unstake { amount }.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(user_stake, false),
+            AccountMeta::new(stake_vault, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `ClaimRewards` 指令
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    pool: &Pubkey,
+    reward_mint: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let (user_stake, _) = find_user_stake_address(program_id, pool, owner);
+    let (reward_vault, _) = find_reward_vault_address(program_id, pool);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::ClaimRewards.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(*pool, false),
+            AccountMeta::new(user_stake, false),
+            AccountMeta::new(*reward_mint, false),
+            AccountMeta::new(reward_vault, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 质押池 PDA 的签名种子
+fn pool_signer_seeds<'a>(pool: &'a StakePool, bump: &'a [u8; 1]) -> [&'a [u8]; 5] {
+    [
+        STAKE_POOL_SEED,
+        pool.authority.as_ref(),
+        pool.stake_mint.as_ref(),
+        pool.reward_mint.as_ref(),
+        bump,
+    ]
+}
+
+/// 读取并校验质押池账户
+fn load_pool(pool_account: &AccountInfo) -> Result<StakePool, ProgramError> {
+    let pool = StakePool::unpack_account(&pool_account.data.borrow())?;
+    if !pool.is_initialized {
+        return Err(TokenError::AccountNotInitialized.into());
+    }
+    Ok(pool)
+}
+
+/// 读取并校验用户质押账户
+fn load_user_stake(
+    pool_account: &AccountInfo,
+    owner: &AccountInfo,
+    user_stake_account: &AccountInfo,
+) -> Result<UserStake, ProgramError> {
+    let user_stake = UserStake::unpack_account(&user_stake_account.data.borrow())?;
+    if !user_stake.is_initialized {
+        return Err(TokenError::AccountNotInitialized.into());
+    }
+    if user_stake.pool != *pool_account.key || user_stake.owner != *owner.key {
+        return Err(TokenError::StakeAccountMismatch.into());
+    }
+    Ok(user_stake)
+}
+
+/// 校验金库 PDA 地址
+fn check_vault(
+    program_id: &Pubkey,
+    pool_account: &AccountInfo,
+    vault: &AccountInfo,
+    seed: &[u8],
+    bump: u8,
+) -> ProgramResult {
+    let expected =
+        Pubkey::create_program_address(&[seed, pool_account.key.as_ref(), &[bump]], program_id)?;
+    if expected != *vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    Ok(())
+}
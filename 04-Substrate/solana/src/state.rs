@@ -0,0 +1,287 @@
+//! 程序账户的版本化布局
+//!
+//! 本程序持有的每个账户都以 2 字节头部开头：账户类型（[`AccountType`]）和布局版本，
+//! 其后是 Borsh 编码的数据。读取时校验账户类型，防止把一种账户当作另一种使用；
+//! 版本与程序不一致时拒绝读取，需先通过 `MigrateAccount` 升级。
+//!
+//! 引入头部之前创建的账户没有头部（版本 0），数据直接以 `is_initialized = 1` 开头。
+//! [`AccountType`] 的取值从 2 开始，`MigrateAccount` 据此区分旧布局；各类型旧布局的长度互不相同，
+//! 调用方声明的账户类型与长度不符时拒绝迁移。迁移在原地完成：按新布局的长度 `realloc`，
+//! 由付费账户补足租金。
+//!
+//! 新增账户类型只能追加到 [`AccountType`] 末尾；修改布局时提高 `VERSION`，
+//! 并在 [`ProgramAccount::unpack_version`] 中读取旧版本。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+
+use crate::{
+    accounts::{
+        check_owner, check_program_account, check_signer, check_system_program, check_writable,
+    },
+    config::{LaunchWallet, MintConfig},
+    dividend::{DividendCheckpoint, DividendPool},
+    events::TokenEvent,
+    ledger::LedgerMint,
+    memo::MemoRequirement,
+    nft::{Collection, Metadata},
+    permit::PermitNonce,
+    sale::Sale,
+    schedule::ScheduledTransfer,
+    stake::{StakePool, UserStake},
+    stream::Stream,
+    TokenError, TokenInstruction,
+};
+
+/// 账户头部长度：账户类型和布局版本各 1 字节
+pub const ACCOUNT_HEADER_LEN: usize = 2;
+
+/// 程序账户类型，作为账户数据的第一个字节
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    /// 未初始化
+    Uninitialized,
+    /// 保留：没有头部的旧布局以 `is_initialized = 1` 开头
+    Legacy,
+    /// 质押池
+    StakePool,
+    /// 用户质押
+    UserStake,
+    /// 定时转账
+    ScheduledTransfer,
+    /// 支付流
+    Stream,
+    /// 已使用的 permit nonce
+    PermitNonce,
+    /// 分红池
+    DividendPool,
+    /// 分红检查点
+    DividendCheckpoint,
+    /// 联合曲线发售
+    Sale,
+    /// 铸造账户配置
+    MintConfig,
+    /// 开盘记录
+    LaunchWallet,
+    /// 转入备注要求
+    MemoRequirement,
+    /// NFT 元数据
+    NftMetadata,
+    /// NFT 集合
+    NftCollection,
+    /// 账本铸造账户
+    LedgerMint,
+    /// 账本代币账户（`Pack` 定长布局）
+    LedgerAccount,
+}
+
+/// 带头部的程序账户
+pub trait ProgramAccount: BorshSerialize + BorshDeserialize {
+    /// 账户类型
+    const ACCOUNT_TYPE: AccountType;
+    /// 当前布局版本
+    const VERSION: u8;
+    /// 账户长度（含头部），可变长度字段按最大长度计算
+    const LEN: usize;
+
+    /// 读取当前版本的账户数据，末尾允许有未使用的字节
+    fn unpack_account(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.first() == Some(&(AccountType::Legacy as u8)) {
+            return Err(TokenError::AccountVersionMismatch.into());
+        }
+        if data.len() < ACCOUNT_HEADER_LEN || data[0] != Self::ACCOUNT_TYPE as u8 {
+            return Err(TokenError::AccountTypeMismatch.into());
+        }
+        if data[1] != Self::VERSION {
+            return Err(TokenError::AccountVersionMismatch.into());
+        }
+        Ok(Self::deserialize(&mut &data[ACCOUNT_HEADER_LEN..])?)
+    }
+
+    /// 写入头部和数据
+    fn pack_account(&self, data: &mut [u8]) -> ProgramResult {
+        if data.len() < ACCOUNT_HEADER_LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[0] = Self::ACCOUNT_TYPE as u8;
+        data[1] = Self::VERSION;
+        self.serialize(&mut &mut data[ACCOUNT_HEADER_LEN..])?;
+        Ok(())
+    }
+
+    /// 读取旧版本的数据，版本 0 为没有头部的原始布局
+    ///
+    /// 布局变化时覆盖该方法，把旧版本转换为当前结构。
+    fn unpack_version(version: u8, body: &[u8]) -> Result<Self, ProgramError> {
+        match version {
+            0 => Ok(Self::deserialize(&mut &body[..])?),
+            _ => Err(TokenError::AccountVersionMismatch.into()),
+        }
+    }
+}
+
+/// 读取任意版本的账户，返回原版本号和按当前结构解析的数据
+fn unpack_any_version<T: ProgramAccount>(data: &[u8]) -> Result<(u8, T), ProgramError> {
+    match data.first() {
+        // 旧布局没有头部，按长度确认账户类型
+        Some(&first) if first == AccountType::Legacy as u8 => {
+            if data.len() != T::LEN - ACCOUNT_HEADER_LEN {
+                return Err(TokenError::AccountTypeMismatch.into());
+            }
+            Ok((0, T::unpack_version(0, data)?))
+        }
+        Some(&first) if first == T::ACCOUNT_TYPE as u8 && data.len() >= ACCOUNT_HEADER_LEN => {
+            let version = data[1];
+            if version == T::VERSION {
+                return Ok((version, T::unpack_account(data)?));
+            }
+            Ok((
+                version,
+                T::unpack_version(version, &data[ACCOUNT_HEADER_LEN..])?,
+            ))
+        }
+        _ => Err(TokenError::AccountTypeMismatch.into()),
+    }
+}
+
+/// `MigrateAccount` 指令的账户
+pub struct MigrateAccountAccounts<'a, 'info> {
+    /// `[signer, writable]` 付费账户，补足扩容后的租金
+    pub payer: &'a AccountInfo<'info>,
+    /// `[writable]` 待升级的程序账户
+    pub account: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> MigrateAccountAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            payer: next_account_info(accounts_iter)?,
+            account: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.payer)?;
+        check_writable(ctx.payer)?;
+        check_owner(ctx.account, program_id)?;
+        check_writable(ctx.account)?;
+        check_system_program(ctx.system_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理 `MigrateAccount`：把程序账户原地升级到当前布局，已是当前版本时不做修改
+pub fn process_migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_type: AccountType,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let ctx = MigrateAccountAccounts::parse(program_id, accounts)?;
+    match account_type {
+        AccountType::StakePool => migrate::<StakePool>(&ctx),
+        AccountType::UserStake => migrate::<UserStake>(&ctx),
+        AccountType::ScheduledTransfer => migrate::<ScheduledTransfer>(&ctx),
+        AccountType::Stream => migrate::<Stream>(&ctx),
+        AccountType::PermitNonce => migrate::<PermitNonce>(&ctx),
+        AccountType::DividendPool => migrate::<DividendPool>(&ctx),
+        AccountType::DividendCheckpoint => migrate::<DividendCheckpoint>(&ctx),
+        AccountType::Sale => migrate::<Sale>(&ctx),
+        AccountType::MintConfig => migrate::<MintConfig>(&ctx),
+        AccountType::LaunchWallet => migrate::<LaunchWallet>(&ctx),
+        AccountType::MemoRequirement => migrate::<MemoRequirement>(&ctx),
+        AccountType::NftMetadata => migrate::<Metadata>(&ctx),
+        AccountType::NftCollection => migrate::<Collection>(&ctx),
+        AccountType::LedgerMint => migrate::<LedgerMint>(&ctx),
+        // 账本代币账户从一开始就带有头部，没有需要迁移的旧布局
+        AccountType::Uninitialized | AccountType::Legacy | AccountType::LedgerAccount => {
+            Err(TokenError::AccountTypeMismatch.into())
+        }
+    }
+}
+
+/// 按 `T` 的当前布局重写账户，长度不足时扩容并由付费账户补足租金
+fn migrate<T: ProgramAccount>(ctx: &MigrateAccountAccounts) -> ProgramResult {
+    let MigrateAccountAccounts {
+        payer,
+        account,
+        system_program,
+    } = *ctx;
+
+    let (from_version, state) = unpack_any_version::<T>(&account.data.borrow())?;
+    if from_version == T::VERSION {
+        log_msg!("账户 {} 已是最新版本 {}", account.key, from_version);
+        return Ok(());
+    }
+
+    if account.data_len() < T::LEN {
+        let top_up = Rent::get()?
+            .minimum_balance(T::LEN)
+            .saturating_sub(account.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(payer.key, account.key, top_up),
+                &[payer.clone(), account.clone(), system_program.clone()],
+            )?;
+        }
+        account.realloc(T::LEN, true)?;
+    }
+
+    // 清除旧数据后按新布局写入，避免残留字节
+    let mut data = account.data.borrow_mut();
+    data.fill(0);
+    state.pack_account(&mut data[..])?;
+
+    TokenEvent::AccountMigrated {
+        account: *account.key,
+        account_type: T::ACCOUNT_TYPE as u8,
+        from_version,
+        to_version: T::VERSION,
+    }
+    .emit();
+
+    log_msg!(
+        "账户 {} 已从版本 {} 升级到 {}",
+        account.key,
+        from_version,
+        T::VERSION
+    );
+
+    Ok(())
+}
+
+/// 构建 `MigrateAccount` 指令
+pub fn migrate_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    account: &Pubkey,
+    account_type: AccountType,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::MigrateAccount { account_type }.pack(),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    }
+}
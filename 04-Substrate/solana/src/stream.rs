@@ -0,0 +1,646 @@
+//! 支付流
+//!
+//! 发送方通过 `CreateStream` 把 `rate_per_second * (stop - start)` 个代币存入由支付流 PDA
+//! 持有的金库，代币在 `[start, stop)` 区间内按秒线性释放给接收方。接收方可以随时用
+//! `WithdrawFromStream` 提取已释放的部分；发送方或接收方可以用 `CancelStream` 终止支付流，
+//! 已释放未提取的部分归接收方，其余退回发送方（包括他人额外转入金库的代币），随后关闭金库与支付流账户。
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::Sysvar,
+};
+use spl_token::{
+    instruction::{close_account, transfer},
+    state::Account as SplAccount,
+};
+
+use crate::{
+    accounts::{
+        check_owner, check_program_account, check_signer, check_system_program,
+        check_token_owned_writable, check_token_program, check_writable,
+    },
+    close_program_account, create_pda_account, create_pda_token_account,
+    events::TokenEvent,
+    state::{AccountType, ProgramAccount, ACCOUNT_HEADER_LEN},
+    validate_token_account, TokenError, TokenInstruction,
+};
+
+/// 支付流 PDA 种子前缀
+pub const STREAM_SEED: &[u8] = b"stream";
+/// 支付流金库 PDA 种子前缀
+pub const STREAM_VAULT_SEED: &[u8] = b"stream_vault";
+
+/// 支付流状态
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct Stream {
+    /// 是否已初始化
+    pub is_initialized: bool,
+    /// 发送方
+    pub sender: Pubkey,
+    /// 接收方钱包地址
+    pub recipient: Pubkey,
+    /// 代币铸造账户
+    pub mint: Pubkey,
+    /// 每秒释放的代币数量
+    pub rate_per_second: u64,
+    /// 开始时间（unix 时间戳）
+    pub start: i64,
+    /// 结束时间（unix 时间戳）
+    pub stop: i64,
+    /// 存入金库的总量
+    pub deposit: u64,
+    /// 接收方已提取的数量
+    pub withdrawn: u64,
+    /// 支付流 PDA bump
+    pub bump: u8,
+    /// 金库 PDA bump
+    pub vault_bump: u8,
+}
+
+impl ProgramAccount for Stream {
+    const ACCOUNT_TYPE: AccountType = AccountType::Stream;
+    const VERSION: u8 = 1;
+    const LEN: usize = ACCOUNT_HEADER_LEN + 1 + 32 * 3 + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+}
+
+impl Stream {
+    /// 计算 `[start, stop)` 区间内的存入总量
+    pub fn deposit_for(rate_per_second: u64, start: i64, stop: i64) -> Result<u64, ProgramError> {
+        let duration = stop.checked_sub(start).ok_or(TokenError::Overflow)?;
+        let deposit = (rate_per_second as u128)
+            .checked_mul(duration as u128)
+            .ok_or(TokenError::Overflow)?;
+        u64::try_from(deposit).map_err(|_| TokenError::Overflow.into())
+    }
+
+    /// 截至 `now` 已释放给接收方的总量（含已提取部分）
+    pub fn streamed_amount(&self, now: i64) -> Result<u64, ProgramError> {
+        let elapsed_until = now.clamp(self.start, self.stop);
+        Self::deposit_for(self.rate_per_second, self.start, elapsed_until)
+    }
+
+    /// 截至 `now` 接收方可提取的数量
+    pub fn withdrawable_amount(&self, now: i64) -> Result<u64, ProgramError> {
+        self.streamed_amount(now)?
+            .checked_sub(self.withdrawn)
+            .ok_or_else(|| TokenError::Overflow.into())
+    }
+}
+
+/// 计算支付流 PDA 地址
+pub fn find_stream_address(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+    start: i64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            STREAM_SEED,
+            sender.as_ref(),
+            recipient.as_ref(),
+            mint.as_ref(),
+            &start.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
+
+/// 计算支付流金库 PDA 地址
+pub fn find_stream_vault_address(program_id: &Pubkey, stream: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STREAM_VAULT_SEED, stream.as_ref()], program_id)
+}
+
+/// `CreateStream` 指令的账户
+pub struct CreateStreamAccounts<'a, 'info> {
+    /// `[signer, writable]` 发送方（付费账户）
+    pub sender: &'a AccountInfo<'info>,
+    /// `[writable]` 支付流 PDA
+    pub stream: &'a AccountInfo<'info>,
+    /// `[writable]` 金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[writable]` 发送方的代币账户
+    pub source: &'a AccountInfo<'info>,
+    /// `[]` 铸造账户
+    pub mint: &'a AccountInfo<'info>,
+    /// `[]` 系统程序
+    pub system_program: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateStreamAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            sender: next_account_info(accounts_iter)?,
+            stream: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            source: next_account_info(accounts_iter)?,
+            mint: next_account_info(accounts_iter)?,
+            system_program: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.sender)?;
+        check_writable(ctx.sender)?;
+        check_writable(ctx.stream)?;
+        check_writable(ctx.vault)?;
+        check_token_owned_writable(ctx.source)?;
+        check_owner(ctx.mint, &spl_token::id())?;
+        check_system_program(ctx.system_program)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `WithdrawFromStream` 指令的账户
+pub struct WithdrawFromStreamAccounts<'a, 'info> {
+    /// `[signer]` 接收方
+    pub recipient: &'a AccountInfo<'info>,
+    /// `[writable]` 支付流 PDA
+    pub stream: &'a AccountInfo<'info>,
+    /// `[writable]` 金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[writable]` 接收方的代币账户
+    pub destination: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> WithdrawFromStreamAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            recipient: next_account_info(accounts_iter)?,
+            stream: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            destination: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.recipient)?;
+        check_writable(ctx.stream)?;
+        check_owner(ctx.stream, program_id)?;
+        check_token_owned_writable(ctx.vault)?;
+        check_token_owned_writable(ctx.destination)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// `CancelStream` 指令的账户
+pub struct CancelStreamAccounts<'a, 'info> {
+    /// `[signer]` 发送方或接收方
+    pub authority: &'a AccountInfo<'info>,
+    /// `[writable]` 发送方，接收退还的租金
+    pub sender: &'a AccountInfo<'info>,
+    /// `[writable]` 支付流 PDA
+    pub stream: &'a AccountInfo<'info>,
+    /// `[writable]` 金库 PDA
+    pub vault: &'a AccountInfo<'info>,
+    /// `[writable]` 发送方接收退款的代币账户
+    pub sender_token_account: &'a AccountInfo<'info>,
+    /// `[writable]` 接收方的代币账户
+    pub recipient_token_account: &'a AccountInfo<'info>,
+    /// `[]` SPL Token 程序
+    pub token_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CancelStreamAccounts<'a, 'info> {
+    /// 解析并校验账户
+    pub fn parse(
+        program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let ctx = Self {
+            authority: next_account_info(accounts_iter)?,
+            sender: next_account_info(accounts_iter)?,
+            stream: next_account_info(accounts_iter)?,
+            vault: next_account_info(accounts_iter)?,
+            sender_token_account: next_account_info(accounts_iter)?,
+            recipient_token_account: next_account_info(accounts_iter)?,
+            token_program: next_account_info(accounts_iter)?,
+        };
+
+        check_signer(ctx.authority)?;
+        check_writable(ctx.sender)?;
+        check_writable(ctx.stream)?;
+        check_owner(ctx.stream, program_id)?;
+        check_token_owned_writable(ctx.vault)?;
+        check_token_owned_writable(ctx.sender_token_account)?;
+        check_token_owned_writable(ctx.recipient_token_account)?;
+        check_token_program(ctx.token_program)?;
+
+        Ok(ctx)
+    }
+}
+
+/// 处理支付流创建
+pub fn process_create_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    recipient: Pubkey,
+    rate_per_second: u64,
+    start: i64,
+    stop: i64,
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let CreateStreamAccounts {
+        sender,
+        stream: stream_account,
+        vault,
+        source,
+        mint,
+        system_program: system_program_account,
+        token_program,
+    } = CreateStreamAccounts::parse(accounts)?;
+
+    if stop <= start || stop <= Clock::get()?.unix_timestamp {
+        return Err(TokenError::InvalidStreamTime.into());
+    }
+    let deposit = Stream::deposit_for(rate_per_second, start, stop)?;
+    if deposit == 0 {
+        return Err(TokenError::InvalidAmount.into());
+    }
+
+    let (stream_address, bump) =
+        find_stream_address(program_id, sender.key, &recipient, mint.key, start);
+    if stream_address != *stream_account.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    if !stream_account.data_is_empty() {
+        return Err(TokenError::AccountAlreadyInitialized.into());
+    }
+    let (vault_address, vault_bump) = find_stream_vault_address(program_id, &stream_address);
+    if vault_address != *vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+
+    create_pda_account(
+        sender,
+        stream_account,
+        system_program_account,
+        program_id,
+        Stream::LEN,
+        &[
+            STREAM_SEED,
+            sender.key.as_ref(),
+            recipient.as_ref(),
+            mint.key.as_ref(),
+            &start.to_le_bytes(),
+            &[bump],
+        ],
+    )?;
+    create_pda_token_account(
+        sender,
+        vault,
+        mint,
+        stream_account,
+        system_program_account,
+        token_program,
+        &[STREAM_VAULT_SEED, stream_address.as_ref(), &[vault_bump]],
+    )?;
+
+    // 发送方一次性存入全部代币
+    invoke(
+        &transfer(
+            token_program.key,
+            source.key,
+            vault.key,
+            sender.key,
+            &[],
+            deposit,
+        )?,
+        &[source.clone(), vault.clone(), sender.clone()],
+    )?;
+
+    let stream = Stream {
+        is_initialized: true,
+        sender: *sender.key,
+        recipient,
+        mint: *mint.key,
+        rate_per_second,
+        start,
+        stop,
+        deposit,
+        withdrawn: 0,
+        bump,
+        vault_bump,
+    };
+    stream.pack_account(&mut stream_account.data.borrow_mut())?;
+
+    TokenEvent::StreamCreated {
+        stream: stream_address,
+        sender: *sender.key,
+        recipient,
+        mint: *mint.key,
+        rate_per_second,
+        start,
+        stop,
+        deposit,
+    }
+    .emit();
+
+    log_msg!("支付流已创建: {}", stream_address);
+    log_msg!("每秒 {}，存入 {}", rate_per_second, deposit);
+
+    Ok(())
+}
+
+/// 处理支付流提取
+pub fn process_withdraw_from_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let WithdrawFromStreamAccounts {
+        recipient,
+        stream: stream_account,
+        vault,
+        destination,
+        token_program,
+    } = WithdrawFromStreamAccounts::parse(program_id, accounts)?;
+
+    let mut stream = load_stream(program_id, stream_account, vault)?;
+    if stream.recipient != *recipient.key {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    validate_token_account(destination, &stream.mint, recipient.key)?;
+
+    let amount = stream.withdrawable_amount(Clock::get()?.unix_timestamp)?;
+    if amount == 0 {
+        return Err(TokenError::InsufficientBalance.into());
+    }
+
+    transfer_from_vault(
+        &stream,
+        stream_account,
+        vault,
+        destination,
+        token_program,
+        amount,
+    )?;
+
+    stream.withdrawn = stream
+        .withdrawn
+        .checked_add(amount)
+        .ok_or(TokenError::Overflow)?;
+    stream.pack_account(&mut stream_account.data.borrow_mut())?;
+
+    TokenEvent::StreamWithdrawn {
+        stream: *stream_account.key,
+        recipient: *recipient.key,
+        amount,
+    }
+    .emit();
+
+    log_msg!("已从支付流提取 {}，累计 {}", amount, stream.withdrawn);
+
+    Ok(())
+}
+
+/// 处理支付流取消
+pub fn process_cancel_stream(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    check_program_account(program_id)?;
+
+    let CancelStreamAccounts {
+        authority,
+        sender,
+        stream: stream_account,
+        vault,
+        sender_token_account,
+        recipient_token_account,
+        token_program,
+    } = CancelStreamAccounts::parse(program_id, accounts)?;
+
+    let stream = load_stream(program_id, stream_account, vault)?;
+    if *authority.key != stream.sender && *authority.key != stream.recipient {
+        return Err(TokenError::InsufficientPrivileges.into());
+    }
+    if *sender.key != stream.sender {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+    validate_token_account(sender_token_account, &stream.mint, &stream.sender)?;
+    validate_token_account(recipient_token_account, &stream.mint, &stream.recipient)?;
+
+    // 已释放未提取的部分归接收方，金库剩余的代币（包括他人额外转入的部分）退回发送方，
+    // 否则金库余额不为零无法关闭
+    let recipient_amount = stream.withdrawable_amount(Clock::get()?.unix_timestamp)?;
+    let sender_amount = SplAccount::unpack(&vault.data.borrow())?
+        .amount
+        .checked_sub(recipient_amount)
+        .ok_or(TokenError::Overflow)?;
+
+    for (destination, amount) in [
+        (recipient_token_account, recipient_amount),
+        (sender_token_account, sender_amount),
+    ] {
+        if amount > 0 {
+            transfer_from_vault(
+                &stream,
+                stream_account,
+                vault,
+                destination,
+                token_program,
+                amount,
+            )?;
+        }
+    }
+
+    let start = stream.start.to_le_bytes();
+    let bump = [stream.bump];
+    invoke_signed(
+        &close_account(
+            token_program.key,
+            vault.key,
+            sender.key,
+            stream_account.key,
+            &[],
+        )?,
+        &[vault.clone(), sender.clone(), stream_account.clone()],
+        &[&stream_signer_seeds(&stream, &start, &bump)],
+    )?;
+    close_program_account(stream_account, sender)?;
+
+    TokenEvent::StreamCancelled {
+        stream: *stream_account.key,
+        sender_amount,
+        recipient_amount,
+    }
+    .emit();
+
+    log_msg!(
+        "支付流已取消: 接收方 {}，退回发送方 {}",
+        recipient_amount,
+        sender_amount
+    );
+
+    Ok(())
+}
+
+/// 构建 `CreateStream` 指令
+#[allow(clippy::too_many_arguments)]
+pub fn create_stream(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    rate_per_second: u64,
+    start: i64,
+    stop: i64,
+) -> Instruction {
+    let (stream, _) = find_stream_address(program_id, sender, recipient, mint, start);
+    let (vault, _) = find_stream_vault_address(program_id, &stream);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::CreateStream {
+            recipient: *recipient,
+            rate_per_second,
+            start,
+            stop,
+        }
+        .pack(),
+        accounts: vec![
+            AccountMeta::new(*sender, true),
+            AccountMeta::new(stream, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `WithdrawFromStream` 指令
+pub fn withdraw_from_stream(
+    program_id: &Pubkey,
+    recipient: &Pubkey,
+    stream: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let (vault, _) = find_stream_vault_address(program_id, stream);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::WithdrawFromStream.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*recipient, true),
+            AccountMeta::new(*stream, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 构建 `CancelStream` 指令，`authority` 为发送方或接收方
+pub fn cancel_stream(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    sender: &Pubkey,
+    stream: &Pubkey,
+    sender_token_account: &Pubkey,
+    recipient_token_account: &Pubkey,
+) -> Instruction {
+    let (vault, _) = find_stream_vault_address(program_id, stream);
+    Instruction {
+        program_id: *program_id,
+        data: TokenInstruction::CancelStream.pack(),
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*sender, false),
+            AccountMeta::new(*stream, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(*sender_token_account, false),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    }
+}
+
+/// 支付流 PDA 的签名种子
+fn stream_signer_seeds<'a>(
+    stream: &'a Stream,
+    start: &'a [u8; 8],
+    bump: &'a [u8; 1],
+) -> [&'a [u8]; 6] {
+    [
+        STREAM_SEED,
+        stream.sender.as_ref(),
+        stream.recipient.as_ref(),
+        stream.mint.as_ref(),
+        start,
+        bump,
+    ]
+}
+
+/// 读取支付流并校验金库地址
+fn load_stream(
+    program_id: &Pubkey,
+    stream_account: &AccountInfo,
+    vault: &AccountInfo,
+) -> Result<Stream, ProgramError> {
+    let stream = Stream::unpack_account(&stream_account.data.borrow())?;
+    if !stream.is_initialized {
+        return Err(TokenError::AccountNotInitialized.into());
+    }
+    let expected = Pubkey::create_program_address(
+        &[
+            STREAM_VAULT_SEED,
+            stream_account.key.as_ref(),
+            &[stream.vault_bump],
+        ],
+        program_id,
+    )?;
+    if expected != *vault.key {
+        return Err(TokenError::InvalidProgramAddress.into());
+    }
+    Ok(stream)
+}
+
+/// 由支付流 PDA 签名，从金库转出代币
+fn transfer_from_vault<'a>(
+    stream: &Stream,
+    stream_account: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let start = stream.start.to_le_bytes();
+    let bump = [stream.bump];
+    invoke_signed(
+        &transfer(
+            token_program.key,
+            vault.key,
+            destination.key,
+            stream_account.key,
+            &[],
+            amount,
+        )?,
+        &[vault.clone(), destination.clone(), stream_account.clone()],
+        &[&stream_signer_seeds(stream, &start, &bump)],
+    )
+}
@@ -1,33 +1,44 @@
 //! Solana SPL 代币程序测试
 
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+};
 use solana_program_test::*;
 use solana_sdk::{
-    commitment_config::CommitmentLevel,
-    instruction::Instruction,
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
     pubkey::Pubkey,
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    system_instruction,
+    transaction::{Transaction, TransactionError},
 };
 use spl_token::{
     instruction::{initialize_mint, mint_to, transfer},
-    state::Mint,
+    state::{Account as SplAccount, Mint},
 };
 use spl_associated_token_account::instruction::create_associated_token_account;
 
 use crate::{
-    process_instruction, TokenInstruction, TokenAccountState, validate_token_account,
-    get_associated_token_address,
+    config, cpi, dividend, fuzz, hook, idl, instruction, ledger, memo, native, nft, parse_events, parse_events_for, permit, process_instruction, sale, schedule, stake, state, stream, AuthorityType, TokenError, TokenEvent,
+    TokenInstruction, validate_token_account, get_associated_token_address,
 };
+use crate::state::{ProgramAccount, ACCOUNT_HEADER_LEN};
 
 const LAMPORTS_PER_TOKEN: u64 = 1_000_000_000;
 
-/// 设置测试程序环境
-async fn setup_program_test() -> (ProgramTestContext, Keypair, Keypair, Keypair) {
-    let program_id = Pubkey::new_unique();
+/// 测试中部署本程序使用的程序 ID
+const PROGRAM_ID: Pubkey = crate::ID;
 
+/// 加载本程序、SPL Token 程序和关联代币账户程序的测试环境
+fn program_test() -> ProgramTest {
     let mut program_test = ProgramTest::new(
         "solana_spl_token",
-        program_id,
+        PROGRAM_ID,
         processor!(process_instruction),
     );
 
@@ -35,7 +46,7 @@ async fn setup_program_test() -> (ProgramTestContext, Keypair, Keypair, Keypair)
     program_test.add_program(
         "spl_token",
         spl_token::id(),
-        processor!(spl_token::processor::process_instruction),
+        processor!(spl_token::processor::Processor::process),
     );
 
     // 添加关联代币账户程序
@@ -45,7 +56,19 @@ async fn setup_program_test() -> (ProgramTestContext, Keypair, Keypair, Keypair)
         processor!(spl_associated_token_account::processor::process_instruction),
     );
 
-    let context = program_test.start().await;
+    program_test
+}
+
+/// 设置测试程序环境
+async fn setup_program_test() -> (ProgramTestContext, Keypair, Keypair, Keypair) {
+    start_program_test(program_test()).await
+}
+
+/// 启动测试环境并创建铸造权限、用户和接收者三个有余额的账户
+async fn start_program_test(
+    program_test: ProgramTest,
+) -> (ProgramTestContext, Keypair, Keypair, Keypair) {
+    let mut context = program_test.start_with_context().await;
 
     // 创建测试密钥对
     let mint_authority = Keypair::new();
@@ -73,6 +96,164 @@ async fn setup_program_test() -> (ProgramTestContext, Keypair, Keypair, Keypair)
     (context, mint_authority, user, recipient)
 }
 
+/// 创建并初始化铸造账户的指令
+fn create_mint_instructions(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    decimals: u8,
+) -> Vec<Instruction> {
+    vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            solana_sdk::rent::Rent::default().minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        initialize_mint(&spl_token::id(), mint, mint_authority, None, decimals).unwrap(),
+    ]
+}
+
+/// 读取代币账户余额
+async fn get_token_balance(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .expect("代币账户不存在");
+    spl_token_2022::extension::StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+/// 断言交易因指定的自定义错误失败
+fn assert_custom_error(result: Result<(), BanksClientError>, error: TokenError) {
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    );
+}
+
+/// 创建铸造账户和 `owner` 的关联代币账户，并向其铸造 `amount` 个代币
+async fn setup_funded_mint(
+    context: &mut ProgramTestContext,
+    mint: &Keypair,
+    mint_authority: &Keypair,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let payer = context.payer.pubkey();
+    let token_account = get_associated_token_address(owner, &mint.pubkey());
+    let mut instructions = create_mint_instructions(&payer, &mint.pubkey(), &mint_authority.pubkey(), 9);
+    instructions.extend([
+        create_associated_token_account(&payer, owner, &mint.pubkey(), &spl_token::id()),
+        mint_to(&spl_token::id(), &mint.pubkey(), &token_account, &mint_authority.pubkey(), &[], amount)
+            .unwrap(),
+    ]);
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer),
+        &[&context.payer, mint, mint_authority],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    token_account
+}
+
+/// 以 `context.payer` 付费发送单条指令
+async fn send_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// 以 `context.payer` 付费发送一组指令
+async fn send_instructions(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// 以 `context.payer` 付费发送一组指令，返回交易消耗的计算单元
+async fn send_with_compute_units(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> u64 {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+    result.result.unwrap();
+    result.metadata.expect("交易缺少执行元数据").compute_units_consumed
+}
+
+/// 测试指令打包与解析
+#[test]
+fn test_token_instructions() {
+    let payer = Pubkey::new_unique();
+    let init_mint_ix = TokenInstruction::InitializeMint {
+        decimals: 9,
+        mint_authority: payer,
+        freeze_authority: None,
+        non_transferable: false,
+        clawback_authority: None,
+        interest_rate: None,
+    };
+
+    let instruction = init_mint_ix.into_instruction();
+    match TokenInstruction::unpack(&instruction.data).unwrap() {
+        TokenInstruction::InitializeMint {
+            decimals,
+            mint_authority,
+            freeze_authority,
+            non_transferable,
+            clawback_authority,
+            interest_rate,
+        } => {
+            assert_eq!(decimals, 9);
+            assert_eq!(mint_authority, payer);
+            assert_eq!(freeze_authority, None);
+            assert!(!non_transferable);
+            assert_eq!(clawback_authority, None);
+            assert_eq!(interest_rate, None);
+        }
+        _ => panic!("指令解析失败"),
+    }
+
+    // 无效的指令数据
+    assert!(TokenInstruction::unpack(&[255]).is_err());
+    assert!(TokenInstruction::unpack(&[]).is_err());
+}
+
 /// 测试代币铸造账户初始化
 #[tokio::test]
 async fn test_initialize_mint() {
@@ -90,25 +271,28 @@ async fn test_initialize_mint() {
         decimals,
         mint_authority: mint_authority.pubkey(),
         freeze_authority,
+        non_transferable: false,
+        clawback_authority: None,
+        interest_rate: None,
     };
 
-    let instruction = Instruction::new_with_bincode(
-        &crate::id(),
-        &init_mint_ix,
-        vec![
-            AccountMeta::new(mint_pubkey, false),
-            AccountMeta::new_readonly(context.payer.pubkey(), true),
+    let instruction = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(mint_pubkey, true),
+            AccountMeta::new(context.payer.pubkey(), true),
             AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
             AccountMeta::new_readonly(spl_token::id(), false),
             AccountMeta::new_readonly(solana_sdk::sysvar::rent::id(), false),
         ],
-    );
+        data: init_mint_ix.pack(),
+    };
 
     // 执行交易
     let transaction = Transaction::new_signed_with_payer(
         &[instruction],
         Some(&context.payer.pubkey()),
-        &[&context.payer],
+        &[&context.payer, &mint_keypair],
         context.last_blockhash,
     );
 
@@ -127,9 +311,9 @@ async fn test_initialize_mint() {
 
     let mint_data = Mint::unpack(&mint_account.data).unwrap();
     assert_eq!(mint_data.decimals, decimals);
-    assert_eq!(mint_data.mint_authority, Some(mint_authority.pubkey()));
+    assert_eq!(mint_data.mint_authority, COption::Some(mint_authority.pubkey()));
     assert_eq!(mint_data.supply, 0);
-    assert!(!mint_data.is_initialized);
+    assert!(mint_data.is_initialized);
 
     println!("✅ 代币铸造账户初始化测试通过");
 }
@@ -144,13 +328,8 @@ async fn test_create_token_account() {
     let mint_pubkey = mint_keypair.pubkey();
 
     // 首先初始化铸造账户
-    let init_mint_ix = initialize_mint(
-        &spl_token::id(),
-        &mint_pubkey,
-        &context.payer.pubkey(),
-        None,
-        9,
-    );
+    let mut instructions =
+        create_mint_instructions(&context.payer.pubkey(), &mint_pubkey, &context.payer.pubkey(), 9);
 
     // 创建用户的关联代币账户
     let user_token_account = get_associated_token_address(&user.pubkey(), &mint_pubkey);
@@ -162,8 +341,9 @@ async fn test_create_token_account() {
     );
 
     // 构建交易
+    instructions.push(create_account_ix);
     let transaction = Transaction::new_signed_with_payer(
-        &[init_mint_ix, create_account_ix],
+        &instructions,
         Some(&context.payer.pubkey()),
         &[&context.payer, &mint_keypair],
         context.last_blockhash,
@@ -196,13 +376,8 @@ async fn test_mint_tokens() {
     let user_token_account = get_associated_token_address(&user.pubkey(), &mint_pubkey);
 
     // 初始化铸造账户
-    let init_mint_ix = initialize_mint(
-        &spl_token::id(),
-        &mint_pubkey,
-        &mint_authority.pubkey(),
-        None,
-        9,
-    );
+    let mut instructions =
+        create_mint_instructions(&context.payer.pubkey(), &mint_pubkey, &mint_authority.pubkey(), 9);
 
     // 创建用户代币账户
     let create_account_ix = create_associated_token_account(
@@ -221,10 +396,12 @@ async fn test_mint_tokens() {
         &mint_authority.pubkey(),
         &[],
         mint_amount,
-    );
+    )
+    .unwrap();
 
+    instructions.extend([create_account_ix, mint_ix]);
     let transaction = Transaction::new_signed_with_payer(
-        &[init_mint_ix, create_account_ix, mint_ix],
+        &instructions,
         Some(&context.payer.pubkey()),
         &[&context.payer, &mint_keypair, &mint_authority],
         context.last_blockhash,
@@ -234,13 +411,9 @@ async fn test_mint_tokens() {
     assert!(result.is_ok(), "代币铸造失败");
 
     // 验证代币余额
-    let token_balance = context
-        .banks_client
-        .get_token_account_balance(user_token_account)
-        .await
-        .unwrap();
+    let token_balance = get_token_balance(&mut context, user_token_account).await;
 
-    assert_eq!(token_balance.amount, mint_amount.to_string());
+    assert_eq!(token_balance, mint_amount);
 
     println!("✅ 代币铸造测试通过");
 }
@@ -257,13 +430,8 @@ async fn test_transfer_tokens() {
     let recipient_token_account = get_associated_token_address(&recipient.pubkey(), &mint_pubkey);
 
     // 初始化铸造账户
-    let init_mint_ix = initialize_mint(
-        &spl_token::id(),
-        &mint_pubkey,
-        &mint_authority.pubkey(),
-        None,
-        9,
-    );
+    let mut instructions =
+        create_mint_instructions(&context.payer.pubkey(), &mint_pubkey, &mint_authority.pubkey(), 9);
 
     // 创建用户代币账户
     let create_user_account_ix = create_associated_token_account(
@@ -290,7 +458,8 @@ async fn test_transfer_tokens() {
         &mint_authority.pubkey(),
         &[],
         mint_amount,
-    );
+    )
+    .unwrap();
 
     // 转移代币
     let transfer_amount = 100 * LAMPORTS_PER_TOKEN;
@@ -301,16 +470,17 @@ async fn test_transfer_tokens() {
         &user.pubkey(),
         &[],
         transfer_amount,
-    );
+    )
+    .unwrap();
 
+    instructions.extend([
+        create_user_account_ix,
+        create_recipient_account_ix,
+        mint_ix,
+        transfer_ix,
+    ]);
     let transaction = Transaction::new_signed_with_payer(
-        &[
-            init_mint_ix,
-            create_user_account_ix,
-            create_recipient_account_ix,
-            mint_ix,
-            transfer_ix,
-        ],
+        &instructions,
         Some(&context.payer.pubkey()),
         &[&context.payer, &mint_keypair, &mint_authority, &user],
         context.last_blockhash,
@@ -320,24 +490,173 @@ async fn test_transfer_tokens() {
     assert!(result.is_ok(), "代币转移失败");
 
     // 验证转移后的余额
-    let user_balance = context
-        .banks_client
-        .get_token_account_balance(user_token_account)
-        .await
-        .unwrap();
-
-    let recipient_balance = context
-        .banks_client
-        .get_token_account_balance(recipient_token_account)
-        .await
-        .unwrap();
+    let user_balance = get_token_balance(&mut context, user_token_account).await;
+    let recipient_balance = get_token_balance(&mut context, recipient_token_account).await;
 
-    assert_eq!(user_balance.amount, (mint_amount - transfer_amount).to_string());
-    assert_eq!(recipient_balance.amount, transfer_amount.to_string());
+    assert_eq!(user_balance, mint_amount - transfer_amount);
+    assert_eq!(recipient_balance, transfer_amount);
 
     println!("✅ 代币转移测试通过");
 }
 
+/// 测试 Token-2022 铸造账户的转账、权限变更、冻结和配置设置
+#[tokio::test]
+async fn test_token_2022_transfer() {
+    use spl_token_2022::{extension::StateWithExtensions, state::Mint as Mint2022};
+
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let token_program = spl_token_2022::id();
+    let mint = Keypair::new();
+    let token_account = |wallet: &Pubkey| {
+        spl_associated_token_account::get_associated_token_address_with_program_id(
+            wallet,
+            &mint.pubkey(),
+            &token_program,
+        )
+    };
+    let user_account = token_account(&user.pubkey());
+    let recipient_account = token_account(&recipient.pubkey());
+    let mut instructions = vec![instruction::with_token_program(
+        instruction::initialize_mint(
+            &PROGRAM_ID,
+            &mint.pubkey(),
+            &payer,
+            &mint_authority.pubkey(),
+            Some(&mint_authority.pubkey()),
+            6,
+        ),
+        &token_program,
+    )];
+    for wallet in [user.pubkey(), recipient.pubkey()] {
+        instructions.push(instruction::create_token_account_with_program(
+            &PROGRAM_ID,
+            &payer,
+            &wallet,
+            &mint.pubkey(),
+            &token_program,
+        ));
+    }
+    instructions.push(instruction::with_token_program(
+        instruction::mint_tokens(&PROGRAM_ID, &mint.pubkey(), &user_account, &mint_authority.pubkey(), &payer, 1000),
+        &token_program,
+    ));
+    send_instructions(&mut context, &instructions, &[&mint, &mint_authority]).await.unwrap();
+
+    // 通过本程序转账，代币程序为 Token-2022
+    let transfer_ix = |amount| {
+        instruction::with_token_program(
+            instruction::transfer_tokens(
+                &PROGRAM_ID,
+                &user_account,
+                &recipient_account,
+                &user.pubkey(),
+                &mint.pubkey(),
+                amount,
+            ),
+            &token_program,
+        )
+    };
+    send_instruction(&mut context, transfer_ix(400), &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 600);
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 400);
+
+    // 传入的代币程序必须是持有代币账户的程序
+    let result = send_instruction(
+        &mut context,
+        instruction::transfer_tokens(
+            &PROGRAM_ID,
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &mint.pubkey(),
+            1,
+        ),
+        &[&user],
+    )
+    .await;
+    assert_custom_error(result, TokenError::IncorrectAccountOwner);
+
+    // 没有配置的铸造账户也必须附带配置 PDA，省略或地址不符时拒绝
+    let mut without_config = transfer_ix(1);
+    without_config.accounts.remove(6);
+    let result = send_instruction(&mut context, without_config, &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    let mut wrong_config = transfer_ix(1);
+    wrong_config.accounts[6].pubkey = config::find_mint_config_address(&PROGRAM_ID, &Pubkey::new_unique()).0;
+    let result = send_instruction(&mut context, wrong_config, &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidProgramAddress);
+
+    // 铸造账户的配置可以由铸造权限设置
+    send_instruction(
+        &mut context,
+        config::set_launch_guard(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), None),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    let (config_address, _) = config::find_mint_config_address(&PROGRAM_ID, &mint.pubkey());
+    assert!(context.banks_client.get_account(config_address).await.unwrap().is_some());
+
+    // 配置存在后需附带双方开盘记录和系统程序
+    let transfer_ix = |amount| {
+        instruction::with_token_program(
+            config::transfer_tokens_with_config(
+                &PROGRAM_ID,
+                &user_account,
+                &recipient_account,
+                &user.pubkey(),
+                &mint.pubkey(),
+                &user.pubkey(),
+                &recipient.pubkey(),
+                amount,
+            ),
+            &token_program,
+        )
+    };
+    send_instruction(&mut context, transfer_ix(100), &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 500);
+
+    // 冻结转入账户后转账失败
+    send_instruction(
+        &mut context,
+        instruction::with_token_program(
+            instruction::freeze_account(&PROGRAM_ID, &recipient_account, &mint.pubkey(), &mint_authority.pubkey()),
+            &token_program,
+        ),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    assert!(send_instruction(&mut context, transfer_ix(100), &[&user]).await.is_err());
+
+    // 放弃铸造权限
+    send_instruction(
+        &mut context,
+        instruction::with_token_program(
+            instruction::set_authority(
+                &PROGRAM_ID,
+                &mint.pubkey(),
+                &mint_authority.pubkey(),
+                AuthorityType::MintTokens,
+                None,
+            ),
+            &token_program,
+        ),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    let account = context.banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+    let state = StateWithExtensions::<Mint2022>::unpack(&account.data).unwrap().base;
+    assert_eq!(state.mint_authority, COption::None);
+    assert_eq!(get_token_balance(&mut context, user_account).await, 500);
+}
+
 /// 测试代币账户验证功能
 #[tokio::test]
 async fn test_validate_token_account() {
@@ -348,13 +667,8 @@ async fn test_validate_token_account() {
     let user_token_account = get_associated_token_address(&user.pubkey(), &mint_pubkey);
 
     // 创建并初始化账户
-    let init_mint_ix = initialize_mint(
-        &spl_token::id(),
-        &mint_pubkey,
-        &context.payer.pubkey(),
-        None,
-        9,
-    );
+    let mut instructions =
+        create_mint_instructions(&context.payer.pubkey(), &mint_pubkey, &context.payer.pubkey(), 9);
 
     let create_account_ix = create_associated_token_account(
         &context.payer.pubkey(),
@@ -363,8 +677,9 @@ async fn test_validate_token_account() {
         &spl_token::id(),
     );
 
+    instructions.push(create_account_ix);
     let transaction = Transaction::new_signed_with_payer(
-        &[init_mint_ix, create_account_ix],
+        &instructions,
         Some(&context.payer.pubkey()),
         &[&context.payer, &mint_keypair],
         context.last_blockhash,
@@ -373,7 +688,7 @@ async fn test_validate_token_account() {
     context.banks_client.process_transaction(transaction).await.unwrap();
 
     // 获取代币账户信息
-    let account_info = context
+    let mut account = context
         .banks_client
         .get_account(user_token_account)
         .await
@@ -385,11 +700,11 @@ async fn test_validate_token_account() {
         &user_token_account,
         false,
         true,
-        &mut account_info.lamports,
-        &mut account_info.data,
-        &account_info.owner,
-        account_info.executable,
-        account_info.rent_epoch,
+        &mut account.lamports,
+        &mut account.data,
+        &account.owner,
+        account.executable,
+        account.rent_epoch,
     );
 
     // 验证代币账户
@@ -412,13 +727,16 @@ fn test_instruction_serialization() {
         decimals: 9,
         mint_authority: Pubkey::new_unique(),
         freeze_authority: Some(Pubkey::new_unique()),
+        non_transferable: false,
+        clawback_authority: None,
+        interest_rate: None,
     };
 
     let serialized = init_instruction.try_to_vec().unwrap();
     let deserialized = TokenInstruction::try_from_slice(&serialized).unwrap();
 
     match deserialized {
-        TokenInstruction::InitializeMint { decimals, mint_authority, freeze_authority } => {
+        TokenInstruction::InitializeMint { decimals, freeze_authority, .. } => {
             assert_eq!(decimals, 9);
             assert!(freeze_authority.is_some());
         }
@@ -475,13 +793,8 @@ async fn test_bulk_transfers() {
     let recipient_token_account = get_associated_token_address(&recipient.pubkey(), &mint_pubkey);
 
     // 设置账户
-    let init_mint_ix = initialize_mint(
-        &spl_token::id(),
-        &mint_pubkey,
-        &mint_authority.pubkey(),
-        None,
-        9,
-    );
+    let mut instructions =
+        create_mint_instructions(&context.payer.pubkey(), &mint_pubkey, &mint_authority.pubkey(), 9);
 
     let create_user_account_ix = create_associated_token_account(
         &context.payer.pubkey(),
@@ -505,15 +818,12 @@ async fn test_bulk_transfers() {
         &mint_authority.pubkey(),
         &[],
         mint_amount,
-    );
+    )
+    .unwrap();
 
+    instructions.extend([create_user_account_ix, create_recipient_account_ix, mint_ix]);
     let setup_transaction = Transaction::new_signed_with_payer(
-        &[
-            init_mint_ix,
-            create_user_account_ix,
-            create_recipient_account_ix,
-            mint_ix,
-        ],
+        &instructions,
         Some(&context.payer.pubkey()),
         &[&context.payer, &mint_keypair, &mint_authority],
         context.last_blockhash,
@@ -533,13 +843,16 @@ async fn test_bulk_transfers() {
             &user.pubkey(),
             &[],
             transfer_amount,
-        );
+        )
+        .unwrap();
 
+        // 相同的交易需要新的 blockhash，否则会被当作重复交易
+        let blockhash = context.get_new_latest_blockhash().await.unwrap();
         let transaction = Transaction::new_signed_with_payer(
             &[transfer_ix],
             Some(&context.payer.pubkey()),
             &[&context.payer, &user],
-            context.last_blockhash,
+            blockhash,
         );
 
         let result = context.banks_client.process_transaction(transaction).await;
@@ -547,23 +860,4564 @@ async fn test_bulk_transfers() {
     }
 
     // 验证最终余额
-    let final_user_balance = context
-        .banks_client
-        .get_token_account_balance(user_token_account)
-        .await
-        .unwrap();
-
-    let final_recipient_balance = context
-        .banks_client
-        .get_token_account_balance(recipient_token_account)
-        .await
-        .unwrap();
+    let final_user_balance = get_token_balance(&mut context, user_token_account).await;
+    let final_recipient_balance = get_token_balance(&mut context, recipient_token_account).await;
 
     let expected_user_balance = mint_amount - (transfer_count * transfer_amount);
     let expected_recipient_balance = transfer_count * transfer_amount;
 
-    assert_eq!(final_user_balance.amount, expected_user_balance.to_string());
-    assert_eq!(final_recipient_balance.amount, expected_recipient_balance.to_string());
+    assert_eq!(final_user_balance, expected_user_balance);
+    assert_eq!(final_recipient_balance, expected_recipient_balance);
+
+    // 通过本程序转回：每次转账消耗的计算单元相同，且不超过基线
+    let mut units = Vec::new();
+    for _ in 0..transfer_count {
+        context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let transfer_ix = instruction::transfer_tokens(
+            &PROGRAM_ID,
+            &recipient_token_account,
+            &user_token_account,
+            &recipient.pubkey(),
+            &mint_pubkey,
+            transfer_amount,
+        );
+        units.push(send_with_compute_units(&mut context, &[transfer_ix], &[&recipient]).await);
+    }
+    assert!(units.iter().all(|&consumed| consumed == units[0]), "计算单元随转账次数变化: {:?}", units);
+    if let Some(&baseline) = load_compute_unit_baseline().as_ref().and_then(|baseline| baseline.get("TransferTokens")) {
+        assert!(
+            units[0] <= compute_unit_limit(baseline),
+            "TransferTokens 消耗 {} CU，超过基线 {} CU",
+            units[0],
+            baseline
+        );
+    }
+    assert_eq!(get_token_balance(&mut context, user_token_account).await, mint_amount);
+    assert_eq!(get_token_balance(&mut context, recipient_token_account).await, 0);
+
+    println!("✅ 批量代币转移测试通过，本程序转账每次消耗 {} CU", units[0]);
+}
+/// 质押测试环境：质押代币、奖励代币（铸造权限为质押池 PDA）以及持有质押代币的用户
+struct StakeTestEnv {
+    reward_mint: Pubkey,
+    pool: Pubkey,
+    user_stake_account: Pubkey,
+    user_reward_account: Pubkey,
+}
+
+/// 创建质押池并给用户铸造质押代币
+async fn setup_stake_pool(
+    context: &mut ProgramTestContext,
+    mint_authority: &Keypair,
+    user: &Keypair,
+    reward_per_slot: u64,
+    lock_period: i64,
+) -> StakeTestEnv {
+    let stake_mint = Keypair::new();
+    let reward_mint = Keypair::new();
+    let payer = context.payer.pubkey();
+    let (pool, _) =
+        stake::find_stake_pool_address(&PROGRAM_ID, &payer, &stake_mint.pubkey(), &reward_mint.pubkey());
+    let user_stake_account = get_associated_token_address(&user.pubkey(), &stake_mint.pubkey());
+    let user_reward_account = get_associated_token_address(&user.pubkey(), &reward_mint.pubkey());
+
+    let mut instructions =
+        create_mint_instructions(&payer, &stake_mint.pubkey(), &mint_authority.pubkey(), 9);
+    instructions.extend(create_mint_instructions(&payer, &reward_mint.pubkey(), &pool, 9));
+    instructions.extend([
+        create_associated_token_account(&payer, &user.pubkey(), &stake_mint.pubkey(), &spl_token::id()),
+        create_associated_token_account(&payer, &user.pubkey(), &reward_mint.pubkey(), &spl_token::id()),
+        mint_to(
+            &spl_token::id(),
+            &stake_mint.pubkey(),
+            &user_stake_account,
+            &mint_authority.pubkey(),
+            &[],
+            1000 * LAMPORTS_PER_TOKEN,
+        )
+        .unwrap(),
+        stake::create_stake_pool(
+            &PROGRAM_ID,
+            &payer,
+            &stake_mint.pubkey(),
+            &reward_mint.pubkey(),
+            reward_per_slot,
+            lock_period,
+            stake::RewardSource::Mint,
+        ),
+    ]);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer),
+        &[&context.payer, &stake_mint, &reward_mint, mint_authority],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    StakeTestEnv {
+        reward_mint: reward_mint.pubkey(),
+        pool,
+        user_stake_account,
+        user_reward_account,
+    }
+}
+
+/// 读取质押池状态
+async fn get_stake_pool(context: &mut ProgramTestContext, pool: Pubkey) -> stake::StakePool {
+    let account = context.banks_client.get_account(pool).await.unwrap().unwrap();
+    stake::StakePool::unpack_account(&account.data).unwrap()
+}
+
+/// 测试奖励累积的计算
+#[test]
+fn test_stake_reward_accounting() {
+    let mut pool = stake::StakePool {
+        is_initialized: true,
+        authority: Pubkey::new_unique(),
+        stake_mint: Pubkey::new_unique(),
+        reward_mint: Pubkey::new_unique(),
+        reward_source: stake::RewardSource::Mint,
+        reward_per_slot: 100,
+        lock_period: 0,
+        acc_reward_per_share: 0,
+        last_update_slot: 10,
+        total_staked: 0,
+        bump: 255,
+        vault_bump: 255,
+        reward_vault_bump: 255,
+    };
+    assert_eq!(ACCOUNT_HEADER_LEN + pool.try_to_vec().unwrap().len(), stake::StakePool::LEN);
+
+    // 无人质押时不累积奖励
+    pool.update(20).unwrap();
+    assert_eq!(pool.acc_reward_per_share, 0);
+    assert_eq!(pool.last_update_slot, 20);
+
+    // 两个用户按 1:3 的比例分配 10 个 slot 的奖励
+    let mut alice = stake::UserStake {
+        is_initialized: true,
+        pool: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount: 0,
+        reward_debt: 0,
+        pending_rewards: 0,
+        lock_until: 0,
+        bump: 255,
+    };
+    assert_eq!(ACCOUNT_HEADER_LEN + alice.try_to_vec().unwrap().len(), stake::UserStake::LEN);
+    let mut bob = alice.clone();
+
+    alice.amount = 100;
+    alice.reset_debt(pool.acc_reward_per_share).unwrap();
+    bob.amount = 300;
+    bob.reset_debt(pool.acc_reward_per_share).unwrap();
+    pool.total_staked = 400;
+
+    pool.update(30).unwrap();
+    alice.settle(pool.acc_reward_per_share).unwrap();
+    bob.settle(pool.acc_reward_per_share).unwrap();
+    assert_eq!(alice.pending_rewards, 250);
+    assert_eq!(bob.pending_rewards, 750);
+
+    // 结算后重置债务，重复结算不会重复计奖
+    alice.reset_debt(pool.acc_reward_per_share).unwrap();
+    alice.settle(pool.acc_reward_per_share).unwrap();
+    assert_eq!(alice.pending_rewards, 250);
+
+    // 旧版本的奖励债务放大了 REWARD_PRECISION 倍，读取时换算为奖励数量
+    let legacy = stake::UserStake { reward_debt: 250 * stake::REWARD_PRECISION, ..alice.clone() };
+    let body = legacy.try_to_vec().unwrap();
+    assert_eq!(stake::UserStake::unpack_version(1, &body).unwrap(), stake::UserStake { reward_debt: 250, ..legacy });
+}
+
+/// 测试极小质押：池中只有 1 个最小单位时累积值很大，之后的大额质押仍能结算和解除
+#[test]
+fn test_stake_dust_staker() {
+    let mut pool = stake::StakePool {
+        is_initialized: true,
+        authority: Pubkey::new_unique(),
+        stake_mint: Pubkey::new_unique(),
+        reward_mint: Pubkey::new_unique(),
+        reward_source: stake::RewardSource::Mint,
+        reward_per_slot: stake::MAX_REWARD_PER_SLOT,
+        lock_period: 0,
+        acc_reward_per_share: 0,
+        last_update_slot: 0,
+        total_staked: 0,
+        bump: 255,
+        vault_bump: 255,
+        reward_vault_bump: 255,
+    };
+    let mut dust = stake::UserStake {
+        is_initialized: true,
+        pool: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount: 1,
+        reward_debt: 0,
+        pending_rewards: 0,
+        lock_until: 0,
+        bump: 255,
+    };
+    dust.reset_debt(pool.acc_reward_per_share).unwrap();
+    pool.total_staked = 1;
+
+    // 1 个最小单位独占 1000 万个 slot 的奖励，累积值约 1e31
+    pool.update(10_000_000).unwrap();
+    assert_eq!(pool.acc_reward_per_share, 10_000_000 * stake::MAX_REWARD_PER_SLOT as u128 * stake::REWARD_PRECISION);
+    dust.settle(pool.acc_reward_per_share).unwrap();
+    assert_eq!(dust.pending_rewards, 10_000_000 * stake::MAX_REWARD_PER_SLOT);
+
+    // 大额质押的 amount * acc_reward_per_share 超出 u128，结算仍然精确
+    let mut whale = stake::UserStake { amount: u64::MAX / 2, pending_rewards: 0, ..dust.clone() };
+    assert!((whale.amount as u128).checked_mul(pool.acc_reward_per_share).is_none());
+    whale.reset_debt(pool.acc_reward_per_share).unwrap();
+    pool.total_staked += whale.amount;
+
+    pool.update(10_000_001).unwrap();
+    whale.settle(pool.acc_reward_per_share).unwrap();
+    // 累积值按整数截断，大额质押损失不到 0.01% 的奖励
+    assert!(whale.pending_rewards <= stake::MAX_REWARD_PER_SLOT);
+    assert!(whale.pending_rewards >= stake::MAX_REWARD_PER_SLOT / 10_000 * 9_999);
+
+    // 解除质押时的结算和债务重置不会溢出
+    whale.amount = 0;
+    whale.reset_debt(pool.acc_reward_per_share).unwrap();
+    assert_eq!(whale.reward_debt, 0);
+}
+
+/// 测试质押、领取奖励与解除质押的完整流程
+#[tokio::test]
+async fn test_stake_and_claim_rewards() {
+    let (mut context, mint_authority, user, _recipient) = setup_program_test().await;
+    let env = setup_stake_pool(&mut context, &mint_authority, &user, 1000, 0).await;
+
+    // 每 slot 奖励超过上限时拒绝创建
+    let pool = get_stake_pool(&mut context, env.pool).await;
+    let create_ix = stake::create_stake_pool(
+        &PROGRAM_ID,
+        &context.payer.pubkey(),
+        &pool.stake_mint,
+        &pool.reward_mint,
+        stake::MAX_REWARD_PER_SLOT + 1,
+        0,
+        stake::RewardSource::Mint,
+    );
+    let result = send_instruction(&mut context, create_ix, &[]).await;
+    assert_custom_error(result, TokenError::InvalidAmount);
+
+    let stake_amount = 100 * LAMPORTS_PER_TOKEN;
+    let transaction = Transaction::new_signed_with_payer(
+        &[stake::stake(&PROGRAM_ID, &user.pubkey(), &env.pool, &env.user_stake_account, stake_amount)],
+        Some(&user.pubkey()),
+        &[&user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let pool = get_stake_pool(&mut context, env.pool).await;
+    assert_eq!(pool.total_staked, stake_amount);
+    let (stake_vault, _) = stake::find_stake_vault_address(&PROGRAM_ID, &env.pool);
+    assert_eq!(get_token_balance(&mut context, stake_vault).await, stake_amount);
+
+    // 推进 slot 后领取奖励
+    let staked_at = pool.last_update_slot;
+    context.warp_to_slot(staked_at + 50).unwrap();
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[stake::claim_rewards(
+            &PROGRAM_ID,
+            &user.pubkey(),
+            &env.pool,
+            &env.reward_mint,
+            &env.user_reward_account,
+        )],
+        Some(&user.pubkey()),
+        &[&user],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let pool = get_stake_pool(&mut context, env.pool).await;
+    let expected_reward = (pool.last_update_slot - staked_at) * 1000;
+    assert!(expected_reward >= 50 * 1000);
+    assert_eq!(get_token_balance(&mut context, env.user_reward_account).await, expected_reward);
+
+    // 全部解除质押
+    let transaction = Transaction::new_signed_with_payer(
+        &[stake::unstake(&PROGRAM_ID, &user.pubkey(), &env.pool, &env.user_stake_account, stake_amount)],
+        Some(&user.pubkey()),
+        &[&user],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(
+        get_token_balance(&mut context, env.user_stake_account).await,
+        1000 * LAMPORTS_PER_TOKEN
+    );
+    assert_eq!(get_stake_pool(&mut context, env.pool).await.total_staked, 0);
+    assert_eq!(get_token_balance(&mut context, stake_vault).await, 0);
+}
+
+/// 测试锁定期内无法解除质押
+#[tokio::test]
+async fn test_unstake_respects_lock_period() {
+    let (mut context, mint_authority, user, _recipient) = setup_program_test().await;
+    let lock_period = 3600;
+    let env = setup_stake_pool(&mut context, &mint_authority, &user, 1000, lock_period).await;
+
+    let stake_amount = 10 * LAMPORTS_PER_TOKEN;
+    let transaction = Transaction::new_signed_with_payer(
+        &[stake::stake(&PROGRAM_ID, &user.pubkey(), &env.pool, &env.user_stake_account, stake_amount)],
+        Some(&user.pubkey()),
+        &[&user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let unstake_ix =
+        stake::unstake(&PROGRAM_ID, &user.pubkey(), &env.pool, &env.user_stake_account, stake_amount);
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&unstake_ix),
+        Some(&user.pubkey()),
+        &[&user],
+        context.last_blockhash,
+    );
+    let result = context.banks_client.process_transaction(transaction).await;
+    assert_custom_error(result, TokenError::StakeLocked);
+
+    // 锁定期结束后可以解除质押
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp += lock_period + 1;
+    context.set_sysvar(&clock);
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[unstake_ix],
+        Some(&user.pubkey()),
+        &[&user],
+        blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(
+        get_token_balance(&mut context, env.user_stake_account).await,
+        1000 * LAMPORTS_PER_TOKEN
+    );
+}
+
+/// 测试从日志中解析结构化事件
+#[test]
+fn test_parse_events() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let minted = TokenEvent::Minted {
+        mint: Pubkey::new_unique(),
+        account: Pubkey::new_unique(),
+        amount: 42,
+    };
+    let burned = TokenEvent::Burned {
+        mint: Pubkey::new_unique(),
+        account: Pubkey::new_unique(),
+        amount: 7,
+    };
+    let encode = |event: &TokenEvent| {
+        format!(
+            "Program data: {} {}",
+            STANDARD.encode(crate::events::EVENT_TAG),
+            STANDARD.encode(event.try_to_vec().unwrap())
+        )
+    };
+
+    let other_program = Pubkey::new_unique();
+    let forged = TokenEvent::Transferred {
+        mint: Pubkey::new_unique(),
+        from: Pubkey::new_unique(),
+        to: Pubkey::new_unique(),
+        amount: u64::MAX,
+    };
+    let logs = vec![
+        // 其他程序在本程序之前输出伪造的事件
+        format!("Program {} invoke [1]", other_program),
+        encode(&forged),
+        format!("Program {} consumed 100 of 200000 compute units", other_program),
+        format!("Program {} success", other_program),
+        format!("Program {} invoke [1]", PROGRAM_ID),
+        "Program log: 指令: 铸造代币".to_string(),
+        encode(&minted),
+        // 格式不符的数据不会被解析
+        format!("Program data: {}", STANDARD.encode(b"other")),
+        format!("Program data: {} !!!", STANDARD.encode(crate::events::EVENT_TAG)),
+        // 本程序 CPI 调用的其他程序输出的数据同样不会被解析
+        format!("Program {} invoke [2]", other_program),
+        encode(&forged),
+        format!("Program {} failed: custom program error: 0x1", other_program),
+        encode(&burned),
+        format!("Program {} success", PROGRAM_ID),
+        // 调用本程序的其他程序在本程序返回后输出的数据
+        format!("Program {} invoke [1]", other_program),
+        format!("Program {} invoke [2]", PROGRAM_ID),
+        encode(&minted),
+        format!("Program {} success", PROGRAM_ID),
+        encode(&forged),
+        format!("Program {} success", other_program),
+    ];
+
+    assert_eq!(parse_events(&logs), vec![minted.clone(), burned, minted.clone()]);
+    assert_eq!(parse_events_for(&PROGRAM_ID, &logs), parse_events(&logs));
+    assert!(parse_events_for(&other_program, &logs).iter().all(|event| *event == forged));
+    assert!(parse_events(&[]).is_empty());
+    // 没有调用记录的数据不属于任何程序
+    assert!(parse_events(&[encode(&minted)]).is_empty());
+    // 截断之后的日志不再解析
+    let truncated = vec![
+        format!("Program {} invoke [1]", PROGRAM_ID),
+        "Log truncated".to_string(),
+        encode(&minted),
+    ];
+    assert!(parse_events(&truncated).is_empty());
+}
+
+/// 测试处理函数在交易日志中输出事件
+///
+/// 原生处理器下 `sol_log_data` 不会写入交易日志，需通过 `cargo test-bpf` 运行。
+#[tokio::test]
+#[cfg_attr(not(feature = "test-bpf"), ignore)]
+async fn test_events_emitted() {
+    let (mut context, mint_authority, user, _recipient) = setup_program_test().await;
+
+    let mint_keypair = Keypair::new();
+    let mint_pubkey = mint_keypair.pubkey();
+    let user_token_account = get_associated_token_address(&user.pubkey(), &mint_pubkey);
+    let payer = context.payer.pubkey();
+
+    let init_mint_ix = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(mint_pubkey, true),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(solana_sdk::sysvar::rent::id(), false),
+        ],
+        data: TokenInstruction::InitializeMint {
+            decimals: 6,
+            mint_authority: mint_authority.pubkey(),
+            freeze_authority: None,
+            non_transferable: false,
+            clawback_authority: None,
+            interest_rate: None,
+        }
+        .pack(),
+    };
+    let create_account_ix =
+        create_associated_token_account(&payer, &user.pubkey(), &mint_pubkey, &spl_token::id());
+    let mint_ix = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(mint_pubkey, false),
+            AccountMeta::new(user_token_account, false),
+            AccountMeta::new_readonly(mint_authority.pubkey(), true),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: TokenInstruction::MintTokens { amount: 500 }.pack(),
+    };
 
-    println!("✅ 批量代币转移测试通过");
-}
\ No newline at end of file
+    let transaction = Transaction::new_signed_with_payer(
+        &[init_mint_ix, create_account_ix, mint_ix],
+        Some(&payer),
+        &[&context.payer, &mint_keypair, &mint_authority],
+        context.last_blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert!(result.result.is_ok());
+
+    let logs = result.metadata.unwrap().log_messages;
+    assert_eq!(
+        parse_events(&logs),
+        vec![
+            TokenEvent::MintInitialized {
+                mint: mint_pubkey,
+                decimals: 6,
+                mint_authority: mint_authority.pubkey(),
+                freeze_authority: None,
+            },
+            TokenEvent::Minted {
+                mint: mint_pubkey,
+                account: user_token_account,
+                amount: 500,
+            },
+        ]
+    );
+}
+
+/// 测试错误码稳定且可以在客户端还原
+#[test]
+fn test_error_codes() {
+    use num_traits::FromPrimitive;
+    use solana_program::program_error::ProgramError;
+
+    // 已发布的错误码不可变更
+    let catalog = [
+        (0, TokenError::InvalidInstruction),
+        (1, TokenError::AccountNotInitialized),
+        (2, TokenError::InsufficientPrivileges),
+        (3, TokenError::InsufficientBalance),
+        (4, TokenError::InvalidAmount),
+        (5, TokenError::StakeLocked),
+        (6, TokenError::MissingSignature),
+        (7, TokenError::IncorrectTokenProgram),
+        (8, TokenError::IncorrectSystemProgram),
+        (9, TokenError::InvalidProgramAddress),
+        (10, TokenError::AccountAlreadyInitialized),
+        (11, TokenError::IncorrectAccountOwner),
+        (12, TokenError::MintMismatch),
+        (13, TokenError::OwnerMismatch),
+        (14, TokenError::Overflow),
+        (15, TokenError::InvalidLockPeriod),
+        (16, TokenError::RewardMintAuthorityMismatch),
+        (17, TokenError::StakeAccountMismatch),
+        (18, TokenError::AccountNotWritable),
+        (19, TokenError::IncorrectRentSysvar),
+        (20, TokenError::IncorrectAssociatedTokenProgram),
+        (21, TokenError::InvalidReleaseTime),
+        (22, TokenError::ScheduleNotReleased),
+        (23, TokenError::ScheduleAlreadyReleased),
+        (24, TokenError::InvalidStreamTime),
+        (25, TokenError::InvalidAuthorizationSignature),
+        (26, TokenError::AuthorizationExpired),
+        (27, TokenError::NonceAlreadyUsed),
+        (28, TokenError::IncorrectInstructionsSysvar),
+        (29, TokenError::DividendCheckpointMismatch),
+        (30, TokenError::InvalidSaleConfig),
+        (31, TokenError::SaleNotActive),
+        (32, TokenError::SaleNotEnded),
+        (33, TokenError::SaleSoldOut),
+        (34, TokenError::TradingNotStarted),
+        (35, TokenError::TransferCooldown),
+        (36, TokenError::LaunchLimitExceeded),
+        (37, TokenError::NonTransferable),
+        (38, TokenError::ClawbackNotEnabled),
+        (39, TokenError::InterestNotEnabled),
+        (40, TokenError::TransferHookMismatch),
+        (41, TokenError::MemoRequired),
+        (42, TokenError::CollectionMismatch),
+        (43, TokenError::MetadataTooLong),
+        (44, TokenError::AccountTypeMismatch),
+        (45, TokenError::AccountVersionMismatch),
+        (46, TokenError::AccountFrozen),
+        (47, TokenError::DividendFreezeAuthorityMismatch),
+    ];
+    for (code, error) in catalog {
+        assert_eq!(error as u32, code);
+        assert_eq!(TokenError::from_u32(code), Some(error));
+        assert_eq!(ProgramError::from(error), ProgramError::Custom(code));
+        assert!(!error.to_string().is_empty());
+    }
+    assert_eq!(TokenError::from_u32(catalog.len() as u32), None);
+    assert_eq!(TokenError::StakeLocked.to_string(), "质押仍处于锁定期");
+}
+
+/// 测试核心指令拒绝伪造的程序、sysvar 和账户
+#[tokio::test]
+async fn test_account_validation_rejects_substitution() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let fake_program = Pubkey::new_unique();
+
+    // InitializeMint：替换系统程序、Token 程序或租金 sysvar
+    let mint_keypair = Keypair::new();
+    let init_mint = |system: Pubkey, token: Pubkey, rent: Pubkey, payer: Pubkey| Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(mint_keypair.pubkey(), true),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(system, false),
+            AccountMeta::new_readonly(token, false),
+            AccountMeta::new_readonly(rent, false),
+        ],
+        data: TokenInstruction::InitializeMint {
+            decimals: 9,
+            mint_authority: mint_authority.pubkey(),
+            freeze_authority: None,
+            non_transferable: false,
+            clawback_authority: None,
+            interest_rate: None,
+        }
+        .pack(),
+    };
+    let payer = context.payer.pubkey();
+    let system = solana_sdk::system_program::id();
+    let rent = solana_sdk::sysvar::rent::id();
+    let cases = [
+        (init_mint(fake_program, spl_token::id(), rent, payer), TokenError::IncorrectSystemProgram),
+        (init_mint(system, fake_program, rent, payer), TokenError::IncorrectTokenProgram),
+        (init_mint(system, spl_token::id(), fake_program, payer), TokenError::IncorrectRentSysvar),
+    ];
+    for (instruction, error) in cases {
+        let result = send_instruction(&mut context, instruction, &[&mint_keypair]).await;
+        assert_custom_error(result, error);
+    }
+
+    // 准备铸造账户和两个持有代币的关联代币账户
+    let mint_pubkey = mint_keypair.pubkey();
+    let user_token_account = get_associated_token_address(&user.pubkey(), &mint_pubkey);
+    let recipient_token_account = get_associated_token_address(&recipient.pubkey(), &mint_pubkey);
+    let mut instructions =
+        create_mint_instructions(&payer, &mint_pubkey, &mint_authority.pubkey(), 9);
+    instructions.extend([
+        create_associated_token_account(&payer, &user.pubkey(), &mint_pubkey, &spl_token::id()),
+        create_associated_token_account(&payer, &recipient.pubkey(), &mint_pubkey, &spl_token::id()),
+        mint_to(
+            &spl_token::id(),
+            &mint_pubkey,
+            &user_token_account,
+            &mint_authority.pubkey(),
+            &[],
+            100,
+        )
+        .unwrap(),
+    ]);
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer),
+        &[&context.payer, &mint_keypair, &mint_authority],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    // TransferTokens：只读账户、缺少签名、伪造程序和非代币账户
+    let transfer_ix = |to: AccountMeta, authority_signs: bool, token: Pubkey| Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(user_token_account, false),
+            to,
+            AccountMeta::new_readonly(user.pubkey(), authority_signs),
+            AccountMeta::new_readonly(token, false),
+            AccountMeta::new_readonly(mint_pubkey, false),
+        ],
+        data: TokenInstruction::TransferTokens { amount: 10 }.pack(),
+    };
+    let cases = [
+        (
+            transfer_ix(AccountMeta::new_readonly(recipient_token_account, false), true, spl_token::id()),
+            TokenError::AccountNotWritable,
+        ),
+        (
+            transfer_ix(AccountMeta::new(recipient_token_account, false), true, fake_program),
+            TokenError::IncorrectTokenProgram,
+        ),
+        (
+            transfer_ix(AccountMeta::new(recipient.pubkey(), false), true, spl_token::id()),
+            TokenError::IncorrectAccountOwner,
+        ),
+    ];
+    for (instruction, error) in cases {
+        let result = send_instruction(&mut context, instruction, &[&user]).await;
+        assert_custom_error(result, error);
+    }
+    let instruction =
+        transfer_ix(AccountMeta::new(recipient_token_account, false), false, spl_token::id());
+    assert_custom_error(
+        send_instruction(&mut context, instruction, &[]).await,
+        TokenError::MissingSignature,
+    );
+
+    // CreateTokenAccount：地址不是关联代币账户，或替换关联代币账户程序
+    let wallet = Keypair::new().pubkey();
+    let create_ix = |token_account: Pubkey, ata_program: Pubkey| Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(wallet, false),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new_readonly(mint_pubkey, false),
+            AccountMeta::new_readonly(system, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(ata_program, false),
+        ],
+        data: TokenInstruction::CreateTokenAccount.pack(),
+    };
+    let ata = get_associated_token_address(&wallet, &mint_pubkey);
+    let cases = [
+        (create_ix(Pubkey::new_unique(), spl_associated_token_account::id()), TokenError::InvalidProgramAddress),
+        (create_ix(ata, fake_program), TokenError::IncorrectAssociatedTokenProgram),
+    ];
+    for (instruction, error) in cases {
+        assert_custom_error(send_instruction(&mut context, instruction, &[]).await, error);
+    }
+
+    // 余额未被任何失败的指令改动
+    assert_eq!(get_token_balance(&mut context, user_token_account).await, 100);
+    assert_eq!(get_token_balance(&mut context, recipient_token_account).await, 0);
+}
+
+/// 测试质押指令拒绝伪造的质押池和 Token 程序
+#[tokio::test]
+async fn test_stake_account_validation() {
+    let (mut context, mint_authority, user, _recipient) = setup_program_test().await;
+    let env = setup_stake_pool(&mut context, &mint_authority, &user, 1000, 0).await;
+
+    // 用不属于本程序的账户冒充质押池
+    let mut instruction =
+        stake::stake(&PROGRAM_ID, &user.pubkey(), &env.pool, &env.user_stake_account, 10);
+    instruction.accounts[1] = AccountMeta::new(user.pubkey(), false);
+    assert_custom_error(
+        send_instruction(&mut context, instruction, &[&user]).await,
+        TokenError::IncorrectAccountOwner,
+    );
+
+    // 替换 Token 程序
+    let mut instruction =
+        stake::stake(&PROGRAM_ID, &user.pubkey(), &env.pool, &env.user_stake_account, 10);
+    let token_index = instruction.accounts.len() - 1;
+    instruction.accounts[token_index] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    assert_custom_error(
+        send_instruction(&mut context, instruction, &[&user]).await,
+        TokenError::IncorrectTokenProgram,
+    );
+
+    assert_eq!(get_stake_pool(&mut context, env.pool).await.total_staked, 0);
+
+    // 质押池地址包含管理员：奖励铸造权限交给管理员的质押池 PDA 后，其他人无法抢先创建该质押池
+    let stake_mint = Keypair::new();
+    let reward_mint = Keypair::new();
+    let (admin_pool, _) = stake::find_stake_pool_address(
+        &PROGRAM_ID,
+        &mint_authority.pubkey(),
+        &stake_mint.pubkey(),
+        &reward_mint.pubkey(),
+    );
+    let payer = context.payer.pubkey();
+    let mut instructions = create_mint_instructions(&payer, &stake_mint.pubkey(), &mint_authority.pubkey(), 9);
+    instructions.extend(create_mint_instructions(&payer, &reward_mint.pubkey(), &admin_pool, 9));
+    send_instructions(&mut context, &instructions, &[&stake_mint, &reward_mint]).await.unwrap();
+
+    let create_pool = |authority: &Pubkey| {
+        stake::create_stake_pool(
+            &PROGRAM_ID,
+            authority,
+            &stake_mint.pubkey(),
+            &reward_mint.pubkey(),
+            stake::MAX_REWARD_PER_SLOT,
+            0,
+            stake::RewardSource::Mint,
+        )
+    };
+    let mut instruction = create_pool(&user.pubkey());
+    instruction.accounts[1] = AccountMeta::new(admin_pool, false);
+    assert_custom_error(
+        send_instruction(&mut context, instruction, &[&user]).await,
+        TokenError::InvalidProgramAddress,
+    );
+    // 以自己为管理员的质押池不是奖励代币的铸造权限
+    assert_custom_error(
+        send_instruction(&mut context, create_pool(&user.pubkey()), &[&user]).await,
+        TokenError::RewardMintAuthorityMismatch,
+    );
+    send_instruction(&mut context, create_pool(&mint_authority.pubkey()), &[&mint_authority])
+        .await
+        .unwrap();
+    assert_eq!(get_stake_pool(&mut context, admin_pool).await.authority, mint_authority.pubkey());
+}
+
+/// 测试销毁、权限变更与冻结指令
+#[tokio::test]
+async fn test_burn_set_authority_and_freeze() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let mint_keypair = Keypair::new();
+    let mint_pubkey = mint_keypair.pubkey();
+    let payer = context.payer.pubkey();
+    let user_token_account = get_associated_token_address(&user.pubkey(), &mint_pubkey);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::initialize_mint(
+                &PROGRAM_ID,
+                &mint_pubkey,
+                &payer,
+                &mint_authority.pubkey(),
+                Some(&mint_authority.pubkey()),
+                9,
+            ),
+            instruction::create_token_account(&PROGRAM_ID, &payer, &user.pubkey(), &mint_pubkey),
+            instruction::mint_tokens(
+                &PROGRAM_ID,
+                &mint_pubkey,
+                &user_token_account,
+                &mint_authority.pubkey(),
+                &payer,
+                100,
+            ),
+            instruction::burn_tokens(&PROGRAM_ID, &user_token_account, &mint_pubkey, &user.pubkey(), 40),
+        ],
+        Some(&payer),
+        &[&context.payer, &mint_keypair, &mint_authority, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(get_token_balance(&mut context, user_token_account).await, 60);
+    let mint_account = context.banks_client.get_account(mint_pubkey).await.unwrap().unwrap();
+    assert_eq!(Mint::unpack(&mint_account.data).unwrap().supply, 60);
+
+    // 转移账户所有权后冻结该账户，并放弃铸造权限
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_authority(
+                &PROGRAM_ID,
+                &user_token_account,
+                &user.pubkey(),
+                AuthorityType::AccountOwner,
+                Some(&recipient.pubkey()),
+            ),
+            instruction::freeze_account(&PROGRAM_ID, &user_token_account, &mint_pubkey, &mint_authority.pubkey()),
+            instruction::set_authority(
+                &PROGRAM_ID,
+                &mint_pubkey,
+                &mint_authority.pubkey(),
+                AuthorityType::MintTokens,
+                None,
+            ),
+        ],
+        Some(&payer),
+        &[&context.payer, &mint_authority, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let account = context.banks_client.get_account(user_token_account).await.unwrap().unwrap();
+    let token_account = SplAccount::unpack(&account.data).unwrap();
+    assert!(token_account.is_frozen());
+    assert_eq!(token_account.owner, recipient.pubkey());
+    let mint_account = context.banks_client.get_account(mint_pubkey).await.unwrap().unwrap();
+    assert_eq!(Mint::unpack(&mint_account.data).unwrap().mint_authority, COption::None);
+
+    // 权限已放弃后无法继续铸造
+    let blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::mint_tokens(
+            &PROGRAM_ID,
+            &mint_pubkey,
+            &user_token_account,
+            &mint_authority.pubkey(),
+            &payer,
+            1,
+        )],
+        Some(&payer),
+        &[&context.payer, &mint_authority],
+        blockhash,
+    );
+    assert!(context.banks_client.process_transaction(transaction).await.is_err());
+}
+
+/// 奖励程序 PDA 的种子，该 PDA 作为奖励代币的铸造权限
+const REWARDS_AUTHORITY_SEED: &[u8] = b"rewards_authority";
+
+/// 通过 `cpi` 模块调用本程序的示例奖励程序
+///
+/// 指令数据为 1 字节操作码加 8 字节数量：
+/// - `0`：以 PDA 签名铸造奖励，账户为 本程序、铸造账户、接收账户、PDA、付费账户、Token 程序
+/// - `1`：转移代币，账户为 本程序、转出账户、转入账户、所有者、Token 程序、铸造账户、备注要求 PDA、配置 PDA、
+///   分红池 PDA，其后为配置和分红账户
+fn process_rewards_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (tag, amount) = instruction_data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let amount = u64::from_le_bytes(
+        amount.try_into().map_err(|_| ProgramError::InvalidInstructionData)?,
+    );
+    let accounts_iter = &mut accounts.iter();
+    let token_program_account = next_account_info(accounts_iter)?.clone();
+
+    match tag {
+        0 => {
+            let accounts = cpi::MintTokens {
+                mint: next_account_info(accounts_iter)?.clone(),
+                token_account: next_account_info(accounts_iter)?.clone(),
+                mint_authority: next_account_info(accounts_iter)?.clone(),
+                payer: next_account_info(accounts_iter)?.clone(),
+                token_program: next_account_info(accounts_iter)?.clone(),
+            };
+            let (_, bump) = Pubkey::find_program_address(&[REWARDS_AUTHORITY_SEED], program_id);
+            cpi::mint_tokens(
+                cpi::CpiContext::new_with_signer(
+                    token_program_account,
+                    accounts,
+                    &[&[REWARDS_AUTHORITY_SEED, &[bump]]],
+                ),
+                amount,
+            )
+        }
+        1 => {
+            let accounts = cpi::TransferTokens {
+                from: next_account_info(accounts_iter)?.clone(),
+                to: next_account_info(accounts_iter)?.clone(),
+                authority: next_account_info(accounts_iter)?.clone(),
+                token_program: next_account_info(accounts_iter)?.clone(),
+                mint: next_account_info(accounts_iter)?.clone(),
+                memo_requirement: next_account_info(accounts_iter)?.clone(),
+                config: next_account_info(accounts_iter)?.clone(),
+                dividend_pool: next_account_info(accounts_iter)?.clone(),
+                remaining_accounts: accounts_iter.cloned().collect(),
+            };
+            cpi::transfer_tokens(cpi::CpiContext::new(token_program_account, accounts), amount)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// 构建示例奖励程序的指令数据
+fn rewards_instruction_data(tag: u8, amount: u64) -> Vec<u8> {
+    let mut data = vec![tag];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// 测试其他程序通过 `cpi` 模块调用本程序
+#[tokio::test]
+async fn test_cpi_from_another_program() {
+    let rewards_program_id = Pubkey::new_unique();
+    let mut program_test = program_test();
+    program_test.add_program(
+        "rewards_program",
+        rewards_program_id,
+        processor!(process_rewards_instruction),
+    );
+    let (mut context, _mint_authority, user, recipient) = start_program_test(program_test).await;
+
+    // 奖励代币的铸造权限是奖励程序的 PDA
+    let (rewards_authority, _) =
+        Pubkey::find_program_address(&[REWARDS_AUTHORITY_SEED], &rewards_program_id);
+    let mint_keypair = Keypair::new();
+    let mint_pubkey = mint_keypair.pubkey();
+    let payer = context.payer.pubkey();
+    let user_token_account = get_associated_token_address(&user.pubkey(), &mint_pubkey);
+    let recipient_token_account = get_associated_token_address(&recipient.pubkey(), &mint_pubkey);
+    let mut instructions = create_mint_instructions(&payer, &mint_pubkey, &rewards_authority, 9);
+    instructions.extend([
+        create_associated_token_account(&payer, &user.pubkey(), &mint_pubkey, &spl_token::id()),
+        create_associated_token_account(&payer, &recipient.pubkey(), &mint_pubkey, &spl_token::id()),
+        // 以 PDA 签名铸造
+        Instruction {
+            program_id: rewards_program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new(mint_pubkey, false),
+                AccountMeta::new(user_token_account, false),
+                AccountMeta::new_readonly(rewards_authority, false),
+                AccountMeta::new_readonly(payer, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            data: rewards_instruction_data(0, 500),
+        },
+        // 用户签名，奖励程序代为转移
+        Instruction {
+            program_id: rewards_program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(PROGRAM_ID, false),
+                AccountMeta::new(user_token_account, false),
+                AccountMeta::new(recipient_token_account, false),
+                AccountMeta::new_readonly(user.pubkey(), true),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(mint_pubkey, false),
+                AccountMeta::new_readonly(
+                    memo::find_memo_requirement_address(&PROGRAM_ID, &recipient_token_account).0,
+                    false,
+                ),
+                AccountMeta::new_readonly(config::find_mint_config_address(&PROGRAM_ID, &mint_pubkey).0, false),
+                AccountMeta::new(dividend::find_dividend_pool_address(&PROGRAM_ID, &mint_pubkey).0, false),
+            ],
+            data: rewards_instruction_data(1, 200),
+        },
+    ]);
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer),
+        &[&context.payer, &mint_keypair, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(get_token_balance(&mut context, user_token_account).await, 300);
+    assert_eq!(get_token_balance(&mut context, recipient_token_account).await, 200);
+}
+
+/// 测试以其他程序 ID 部署时拒绝执行
+#[tokio::test]
+async fn test_rejects_foreign_program_id() {
+    let foreign_program_id = Pubkey::new_unique();
+    let mut program_test = program_test();
+    program_test.add_program(
+        "solana_spl_token_copy",
+        foreign_program_id,
+        processor!(process_instruction),
+    );
+    let (mut context, mint_authority, _user, _recipient) = start_program_test(program_test).await;
+
+    let mint_keypair = Keypair::new();
+    let mut instruction = instruction::initialize_mint(
+        &PROGRAM_ID,
+        &mint_keypair.pubkey(),
+        &context.payer.pubkey(),
+        &mint_authority.pubkey(),
+        None,
+        9,
+    );
+    assert_eq!(TokenInstruction::CreateTokenAccount.into_instruction().program_id, PROGRAM_ID);
+
+    instruction.program_id = foreign_program_id;
+    let result = send_instruction(&mut context, instruction, &[&mint_keypair]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+}
+
+/// 测试 SOL 包装、wSOL 转移与解包
+#[tokio::test]
+async fn test_wrap_transfer_and_unwrap_sol() {
+    let (mut context, _mint_authority, user, recipient) = setup_program_test().await;
+    let native_mint = spl_token::native_mint::id();
+    let payer = context.payer.pubkey();
+    let user_wsol = get_associated_token_address(&user.pubkey(), &native_mint);
+    let recipient_wsol = get_associated_token_address(&recipient.pubkey(), &native_mint);
+
+    // 首次包装创建 wSOL 账户，再次包装向已有账户追加
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            native::wrap_sol(&PROGRAM_ID, &user.pubkey(), 2 * LAMPORTS_PER_TOKEN),
+            native::wrap_sol(&PROGRAM_ID, &user.pubkey(), LAMPORTS_PER_TOKEN),
+            instruction::create_token_account(&PROGRAM_ID, &payer, &recipient.pubkey(), &native_mint),
+            instruction::transfer_tokens(
+                &PROGRAM_ID,
+                &user_wsol,
+                &recipient_wsol,
+                &user.pubkey(),
+                &native_mint,
+                LAMPORTS_PER_TOKEN,
+            ),
+        ],
+        Some(&payer),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    assert_eq!(get_token_balance(&mut context, user_wsol).await, 2 * LAMPORTS_PER_TOKEN);
+    assert_eq!(get_token_balance(&mut context, recipient_wsol).await, LAMPORTS_PER_TOKEN);
+    let account = context.banks_client.get_account(user_wsol).await.unwrap().unwrap();
+    assert!(SplAccount::unpack(&account.data).unwrap().is_native());
+
+    // 解包后 wSOL 账户关闭，余额与租金退还给用户
+    let wsol_lamports = account.lamports;
+    let user_lamports = context.banks_client.get_balance(user.pubkey()).await.unwrap();
+    send_instruction(&mut context, native::unwrap_sol(&PROGRAM_ID, &user.pubkey()), &[&user])
+        .await
+        .unwrap();
+    assert!(context.banks_client.get_account(user_wsol).await.unwrap().is_none());
+    assert_eq!(
+        context.banks_client.get_balance(user.pubkey()).await.unwrap(),
+        user_lamports + wsol_lamports
+    );
+
+    // 包装数量不能为 0
+    let result = send_instruction(
+        &mut context,
+        native::wrap_sol(&PROGRAM_ID, &user.pubkey(), 0),
+        &[&user],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InvalidAmount);
+}
+
+/// 测试定时转账的创建、提前执行、到期执行与取消
+#[tokio::test]
+async fn test_scheduled_transfers() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let user_account =
+        setup_funded_mint(&mut context, &mint, &mint_authority, &user.pubkey(), 100).await;
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+    send_instruction(
+        &mut context,
+        create_associated_token_account(&payer, &recipient.pubkey(), &mint.pubkey(), &spl_token::id()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let release_at = clock.unix_timestamp + 3600;
+    let schedule_ix = |amount, release_at| {
+        schedule::schedule_transfer(
+            &PROGRAM_ID,
+            &user.pubkey(),
+            &user_account,
+            &mint.pubkey(),
+            &recipient.pubkey(),
+            amount,
+            release_at,
+        )
+    };
+
+    // 释放时间必须晚于当前时间
+    let result = send_instruction(&mut context, schedule_ix(40, clock.unix_timestamp), &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidReleaseTime);
+
+    // 创建两笔定时转账，第二笔稍后取消
+    let (first, _) = schedule::find_schedule_address(
+        &PROGRAM_ID,
+        &user.pubkey(),
+        &recipient.pubkey(),
+        &mint.pubkey(),
+        release_at,
+    );
+    let (second, _) = schedule::find_schedule_address(
+        &PROGRAM_ID,
+        &user.pubkey(),
+        &recipient.pubkey(),
+        &mint.pubkey(),
+        release_at + 1,
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[schedule_ix(40, release_at), schedule_ix(25, release_at + 1)],
+        Some(&payer),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 35);
+    let (first_vault, _) = schedule::find_schedule_vault_address(&PROGRAM_ID, &first);
+    assert_eq!(get_token_balance(&mut context, first_vault).await, 40);
+
+    let account = context.banks_client.get_account(first).await.unwrap().unwrap();
+    let state = schedule::ScheduledTransfer::unpack_account(&account.data).unwrap();
+    assert_eq!(state.recipient, recipient.pubkey());
+    assert_eq!(state.amount, 40);
+    assert_eq!(state.release_at, release_at);
+
+    // 未到释放时间不能执行
+    let execute_ix =
+        schedule::execute_scheduled(&PROGRAM_ID, &first, &user.pubkey(), &recipient_account);
+    let result = send_instruction(&mut context, execute_ix.clone(), &[]).await;
+    assert_custom_error(result, TokenError::ScheduleNotReleased);
+
+    // 直接向金库转入的代币不影响关闭，随余额一起转出
+    let (second_vault, _) = schedule::find_schedule_vault_address(&PROGRAM_ID, &second);
+    for (vault, amount) in [(second_vault, 5), (first_vault, 3)] {
+        send_instruction(
+            &mut context,
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &user_account,
+                &vault,
+                &user.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+            &[&user],
+        )
+        .await
+        .unwrap();
+    }
+    assert_eq!(get_token_balance(&mut context, user_account).await, 27);
+
+    // 释放前发送方可以取消，代币退回
+    send_instruction(
+        &mut context,
+        schedule::cancel_scheduled(&PROGRAM_ID, &user.pubkey(), &second, &user_account),
+        &[&user],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 57);
+    assert!(context.banks_client.get_account(second).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(second_vault).await.unwrap().is_none());
+
+    // 到达释放时间后任何人都可以执行，租金退还给发送方
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = release_at;
+    context.set_sysvar(&clock);
+    context.get_new_latest_blockhash().await.unwrap();
+    let user_lamports = context.banks_client.get_balance(user.pubkey()).await.unwrap();
+    send_instruction(&mut context, execute_ix, &[]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 43);
+    assert!(context.banks_client.get_account(first).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(first_vault).await.unwrap().is_none());
+    assert!(context.banks_client.get_balance(user.pubkey()).await.unwrap() > user_lamports);
+
+    // 到达释放时间后不能再取消
+    send_instruction(&mut context, schedule_ix(10, release_at + 10), &[&user]).await.unwrap();
+    let (third, _) = schedule::find_schedule_address(
+        &PROGRAM_ID,
+        &user.pubkey(),
+        &recipient.pubkey(),
+        &mint.pubkey(),
+        release_at + 10,
+    );
+    clock.unix_timestamp = release_at + 10;
+    context.set_sysvar(&clock);
+    let result = send_instruction(
+        &mut context,
+        schedule::cancel_scheduled(&PROGRAM_ID, &user.pubkey(), &third, &user_account),
+        &[&user],
+    )
+    .await;
+    assert_custom_error(result, TokenError::ScheduleAlreadyReleased);
+}
+
+/// 测试支付流的线性释放计算
+#[test]
+fn test_stream_accrual() {
+    let stream = stream::Stream {
+        is_initialized: true,
+        sender: Pubkey::new_unique(),
+        recipient: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        rate_per_second: 10,
+        start: 1000,
+        stop: 1100,
+        deposit: 1000,
+        withdrawn: 0,
+        bump: 255,
+        vault_bump: 255,
+    };
+    assert_eq!(ACCOUNT_HEADER_LEN + stream.try_to_vec().unwrap().len(), stream::Stream::LEN);
+    assert_eq!(stream::Stream::deposit_for(10, 1000, 1100).unwrap(), 1000);
+    assert!(stream::Stream::deposit_for(u64::MAX, 0, 2).is_err());
+
+    // 开始前不释放，结束后全部释放
+    assert_eq!(stream.streamed_amount(900).unwrap(), 0);
+    assert_eq!(stream.streamed_amount(1030).unwrap(), 300);
+    assert_eq!(stream.streamed_amount(5000).unwrap(), 1000);
+
+    // 可提取数量扣除已提取部分
+    let stream = stream::Stream { withdrawn: 300, ..stream };
+    assert_eq!(stream.withdrawable_amount(1030).unwrap(), 0);
+    assert_eq!(stream.withdrawable_amount(1050).unwrap(), 200);
+}
+
+/// 测试支付流的创建、按时提取与按比例取消
+#[tokio::test]
+async fn test_payment_stream() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let user_account =
+        setup_funded_mint(&mut context, &mint, &mint_authority, &user.pubkey(), 10_000).await;
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+    send_instruction(
+        &mut context,
+        create_associated_token_account(&payer, &recipient.pubkey(), &mint.pubkey(), &spl_token::id()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let start = clock.unix_timestamp + 100;
+    let stop = start + 1000;
+    let create_ix = |start, stop| {
+        stream::create_stream(
+            &PROGRAM_ID,
+            &user.pubkey(),
+            &user_account,
+            &mint.pubkey(),
+            &recipient.pubkey(),
+            5,
+            start,
+            stop,
+        )
+    };
+
+    // 结束时间必须晚于开始时间
+    let result = send_instruction(&mut context, create_ix(start, start), &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidStreamTime);
+
+    send_instruction(&mut context, create_ix(start, stop), &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 5000);
+    let (stream_address, _) = stream::find_stream_address(
+        &PROGRAM_ID,
+        &user.pubkey(),
+        &recipient.pubkey(),
+        &mint.pubkey(),
+        start,
+    );
+    let (vault, _) = stream::find_stream_vault_address(&PROGRAM_ID, &stream_address);
+    assert_eq!(get_token_balance(&mut context, vault).await, 5000);
+
+    // 开始前没有可提取的代币
+    let withdraw_ix =
+        stream::withdraw_from_stream(&PROGRAM_ID, &recipient.pubkey(), &stream_address, &recipient_account);
+    let result = send_instruction(&mut context, withdraw_ix.clone(), &[&recipient]).await;
+    assert_custom_error(result, TokenError::InsufficientBalance);
+
+    // 只有接收方可以提取
+    let result = send_instruction(
+        &mut context,
+        stream::withdraw_from_stream(&PROGRAM_ID, &user.pubkey(), &stream_address, &recipient_account),
+        &[&user],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InsufficientPrivileges);
+
+    // 经过 200 秒后提取 1000
+    clock.unix_timestamp = start + 200;
+    context.set_sysvar(&clock);
+    context.get_new_latest_blockhash().await.unwrap();
+    send_instruction(&mut context, withdraw_ix, &[&recipient]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 1000);
+
+    // 他人直接向金库转入 300，取消时随剩余部分退回发送方，金库仍能关闭
+    send_instruction(
+        &mut context,
+        spl_token::instruction::transfer(
+            &spl_token::id(),
+            &user_account,
+            &vault,
+            &user.pubkey(),
+            &[],
+            300,
+        )
+        .unwrap(),
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    // 经过 500 秒时发送方取消：接收方再得 1500，发送方退回 2500 与额外转入的 300
+    clock.unix_timestamp = start + 500;
+    context.set_sysvar(&clock);
+    send_instruction(
+        &mut context,
+        stream::cancel_stream(
+            &PROGRAM_ID,
+            &user.pubkey(),
+            &user.pubkey(),
+            &stream_address,
+            &user_account,
+            &recipient_account,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 2500);
+    assert_eq!(get_token_balance(&mut context, user_account).await, 7500);
+    assert!(context.banks_client.get_account(stream_address).await.unwrap().is_none());
+    assert!(context.banks_client.get_account(vault).await.unwrap().is_none());
+}
+
+/// 测试中继者按链下签名完成转账，以及过期、篡改与重放的拒绝
+#[tokio::test]
+async fn test_transfer_with_authorization() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let user_account =
+        setup_funded_mint(&mut context, &mint, &mint_authority, &user.pubkey(), 1000).await;
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+
+    // 持有人一次性授权委托 PDA，之后只需链下签名
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            create_associated_token_account(&payer, &recipient.pubkey(), &mint.pubkey(), &spl_token::id()),
+            permit::approve_permit_delegate(&PROGRAM_ID, &user.pubkey(), &user_account, 500).unwrap(),
+        ],
+        Some(&payer),
+        &[&context.payer, &user],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await.unwrap();
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let expiry = clock.unix_timestamp + 600;
+    let nonce = [7u8; 32];
+    let signed = |amount: u64, expiry: i64| {
+        let message = permit::permit_message(
+            &PROGRAM_ID,
+            &mint.pubkey(),
+            &user.pubkey(),
+            &recipient.pubkey(),
+            amount,
+            &nonce,
+            expiry,
+        );
+        let signature: [u8; 64] = user.sign_message(&message).as_ref().try_into().unwrap();
+        permit::ed25519_verify_instruction(&user.pubkey(), &signature, &message)
+    };
+    let transfer_ix = |amount: u64, expiry: i64| {
+        permit::transfer_with_authorization(
+            &PROGRAM_ID,
+            &payer,
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &recipient.pubkey(),
+            amount,
+            nonce,
+            expiry,
+        )
+    };
+    let assert_error_at = |result: Result<(), BanksClientError>, index: u8, error: TokenError| {
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(index, InstructionError::Custom(error as u32))
+        );
+    };
+
+    // 缺少验签指令
+    let result = send_instructions(&mut context, &[transfer_ix(200, expiry)], &[]).await;
+    assert_custom_error(result, TokenError::InvalidAuthorizationSignature);
+
+    // 签名的数量与指令不一致
+    let result =
+        send_instructions(&mut context, &[signed(200, expiry), transfer_ix(300, expiry)], &[]).await;
+    assert_error_at(result, 1, TokenError::InvalidAuthorizationSignature);
+
+    // 已过期的授权
+    let past = clock.unix_timestamp - 1;
+    let result =
+        send_instructions(&mut context, &[signed(200, past), transfer_ix(200, past)], &[]).await;
+    assert_error_at(result, 1, TokenError::AuthorizationExpired);
+
+    // 他人伪造的签名由 Ed25519 程序拒绝
+    let message = permit::permit_message(
+        &PROGRAM_ID,
+        &mint.pubkey(),
+        &user.pubkey(),
+        &recipient.pubkey(),
+        200,
+        &nonce,
+        expiry,
+    );
+    let forged: [u8; 64] = recipient.sign_message(&message).as_ref().try_into().unwrap();
+    let result = send_instructions(
+        &mut context,
+        &[
+            permit::ed25519_verify_instruction(&user.pubkey(), &forged, &message),
+            transfer_ix(200, expiry),
+        ],
+        &[],
+    )
+    .await;
+    assert!(result.is_err());
+
+    // 中继者支付手续费，持有人无需签署交易
+    let user_lamports = context.banks_client.get_balance(user.pubkey()).await.unwrap();
+    send_instructions(&mut context, &[signed(200, expiry), transfer_ix(200, expiry)], &[])
+        .await
+        .unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 200);
+    assert_eq!(get_token_balance(&mut context, user_account).await, 800);
+    assert_eq!(context.banks_client.get_balance(user.pubkey()).await.unwrap(), user_lamports);
+
+    // 同一签名不能重放
+    context.get_new_latest_blockhash().await.unwrap();
+    let result =
+        send_instructions(&mut context, &[signed(200, expiry), transfer_ix(200, expiry)], &[]).await;
+    assert_error_at(result, 1, TokenError::NonceAlreadyUsed);
+}
+
+/// 测试分红累积与结算的计算
+#[test]
+fn test_dividend_accounting() {
+    let mut pool = dividend::DividendPool {
+        is_initialized: true,
+        mint: Pubkey::new_unique(),
+        reward_per_token: 0,
+        total_tracked: 0,
+        total_deposited: 0,
+        undistributed: 0,
+        bump: 255,
+        vault_bump: 255,
+    };
+    assert_eq!(ACCOUNT_HEADER_LEN + pool.try_to_vec().unwrap().len(), dividend::DividendPool::LEN);
+
+    // 无人登记时分红暂存，随下一次存入一起分配
+    pool.distribute(100).unwrap();
+    assert_eq!(pool.reward_per_token, 0);
+    assert_eq!(pool.undistributed, 100);
+
+    let mut alice = dividend::DividendCheckpoint {
+        is_initialized: true,
+        pool: Pubkey::new_unique(),
+        token_account: Pubkey::new_unique(),
+        balance: 0,
+        reward_per_token_paid: 0,
+        pending: 0,
+        bump: 255,
+    };
+    assert_eq!(ACCOUNT_HEADER_LEN + alice.try_to_vec().unwrap().len(), dividend::DividendCheckpoint::LEN);
+    let mut bob = alice.clone();
+    pool.track(&mut alice, 100).unwrap();
+    pool.track(&mut bob, 300).unwrap();
+    assert_eq!(pool.total_tracked, 400);
+
+    pool.distribute(300).unwrap();
+    assert_eq!(pool.undistributed, 0);
+    assert_eq!(pool.total_deposited, 400);
+    alice.settle(pool.reward_per_token).unwrap();
+    bob.settle(pool.reward_per_token).unwrap();
+    assert_eq!(alice.pending, 100);
+    assert_eq!(bob.pending, 300);
+
+    // 重复结算不会重复计入
+    alice.settle(pool.reward_per_token).unwrap();
+    assert_eq!(alice.pending, 100);
+
+    // 结算后更新登记余额，此后的分红按新余额分配
+    pool.track(&mut alice, 0).unwrap();
+    pool.distribute(300).unwrap();
+    alice.settle(pool.reward_per_token).unwrap();
+    bob.settle(pool.reward_per_token).unwrap();
+    assert_eq!(alice.pending, 100);
+    assert_eq!(bob.pending, 600);
+}
+
+/// 创建冻结权限为分红池的铸造账户，并为每个钱包创建关联代币账户、铸造初始余额
+async fn setup_dividend_mint(
+    context: &mut ProgramTestContext,
+    mint: &Keypair,
+    mint_authority: &Keypair,
+    holders: &[(Pubkey, u64)],
+) -> Vec<Pubkey> {
+    let payer = context.payer.pubkey();
+    let (pool_address, _) = dividend::find_dividend_pool_address(&PROGRAM_ID, &mint.pubkey());
+    let mut instructions = vec![
+        system_instruction::create_account(
+            &payer,
+            &mint.pubkey(),
+            solana_sdk::rent::Rent::default().minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        initialize_mint(&spl_token::id(), &mint.pubkey(), &mint_authority.pubkey(), Some(&pool_address), 9)
+            .unwrap(),
+    ];
+    let mut accounts = Vec::new();
+    for (owner, amount) in holders {
+        let account = get_associated_token_address(owner, &mint.pubkey());
+        instructions.push(create_associated_token_account(&payer, owner, &mint.pubkey(), &spl_token::id()));
+        if *amount > 0 {
+            instructions.push(
+                mint_to(&spl_token::id(), &mint.pubkey(), &account, &mint_authority.pubkey(), &[], *amount).unwrap(),
+            );
+        }
+        accounts.push(account);
+    }
+    send_instructions(context, &instructions, &[mint, mint_authority]).await.unwrap();
+    accounts
+}
+
+/// 读取代币账户是否已冻结
+async fn is_frozen(context: &mut ProgramTestContext, address: Pubkey) -> bool {
+    let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+    SplAccount::unpack(&account.data).unwrap().is_frozen()
+}
+
+/// 测试分红的存入、领取，以及转账时的检查点结算和登记账户的冻结
+#[tokio::test]
+async fn test_dividends() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+
+    // 铸造账户的冻结权限不是分红池时不能创建分红池
+    let plain_mint = Keypair::new();
+    let plain_account =
+        setup_funded_mint(&mut context, &plain_mint, &mint_authority, &user.pubkey(), 100).await;
+    let ix = dividend::deposit_dividends(&PROGRAM_ID, &user.pubkey(), &plain_account, &plain_mint.pubkey(), 10);
+    let result = send_instruction(&mut context, ix, &[&user]).await;
+    assert_custom_error(result, TokenError::DividendFreezeAuthorityMismatch);
+
+    let mint = Keypair::new();
+    let accounts = setup_dividend_mint(
+        &mut context,
+        &mint,
+        &mint_authority,
+        &[(user.pubkey(), 1000), (recipient.pubkey(), 0), (payer, 1000)],
+    )
+    .await;
+    let (user_account, recipient_account, depositor_account) = (accounts[0], accounts[1], accounts[2]);
+
+    let deposit_ix = |amount| {
+        dividend::deposit_dividends(&PROGRAM_ID, &payer, &depositor_account, &mint.pubkey(), amount)
+    };
+    let claim_ix = |owner: &Keypair, account: &Pubkey| {
+        dividend::claim_dividends(&PROGRAM_ID, &owner.pubkey(), account, &mint.pubkey())
+    };
+    let transfer_ix = |from: &Pubkey, to: &Pubkey, authority: &Keypair, amount| {
+        dividend::transfer_tokens_with_dividends(
+            &PROGRAM_ID,
+            from,
+            to,
+            &authority.pubkey(),
+            &mint.pubkey(),
+            amount,
+        )
+    };
+
+    // 首次存入创建分红池，无人登记时暂存；随后双方通过领取登记，登记后账户被冻结
+    send_instruction(&mut context, deposit_ix(40), &[]).await.unwrap();
+    send_instructions(
+        &mut context,
+        &[claim_ix(&user, &user_account), claim_ix(&recipient, &recipient_account)],
+        &[&user, &recipient],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 1000);
+    assert!(is_frozen(&mut context, user_account).await);
+    assert!(is_frozen(&mut context, recipient_account).await);
+    assert!(!is_frozen(&mut context, depositor_account).await);
+
+    // 附带分红账户转账后登记余额随之更新，双方保持冻结
+    let ix = transfer_ix(&user_account, &recipient_account, &user, 250);
+    send_instruction(&mut context, ix, &[&user]).await.unwrap();
+    let (pool_address, _) = dividend::find_dividend_pool_address(&PROGRAM_ID, &mint.pubkey());
+    let get_pool = |account: solana_sdk::account::Account| {
+        dividend::DividendPool::unpack_account(&account.data).unwrap()
+    };
+    let pool = get_pool(context.banks_client.get_account(pool_address).await.unwrap().unwrap());
+    assert_eq!(pool.total_tracked, 1000);
+    assert!(is_frozen(&mut context, user_account).await);
+    assert!(is_frozen(&mut context, recipient_account).await);
+
+    // 存入 60，连同暂存的 40 按 750:250 分配
+    send_instruction(&mut context, deposit_ix(60), &[]).await.unwrap();
+
+    // 接收方在领取前把余额转回，双方按转账前的余额结算，不会多得分红
+    let ix = transfer_ix(&recipient_account, &user_account, &recipient, 250);
+    send_instruction(&mut context, ix, &[&recipient]).await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    send_instructions(
+        &mut context,
+        &[claim_ix(&user, &user_account), claim_ix(&recipient, &recipient_account)],
+        &[&user, &recipient],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 1075);
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 25);
+
+    // 绕过本程序直接转出或转入登记账户都会因账户冻结失败
+    let spl_frozen = TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(spl_token::error::TokenError::AccountFrozen as u32),
+    );
+    let direct = transfer(&spl_token::id(), &user_account, &depositor_account, &user.pubkey(), &[], 1).unwrap();
+    let result = send_instruction(&mut context, direct, &[&user]).await;
+    assert_eq!(result.unwrap_err().unwrap(), spl_frozen);
+    let direct = transfer(&spl_token::id(), &depositor_account, &user_account, &payer, &[], 1).unwrap();
+    let result = send_instruction(&mut context, direct, &[]).await;
+    assert_eq!(result.unwrap_err().unwrap(), spl_frozen);
+
+    // 转到未登记的账户后可以自由使用，登记余额随之下调
+    let ix = transfer_ix(&user_account, &depositor_account, &user, 75);
+    send_instruction(&mut context, ix, &[&user]).await.unwrap();
+    let pool = get_pool(context.banks_client.get_account(pool_address).await.unwrap().unwrap());
+    assert_eq!(pool.total_tracked, 1025);
+    assert!(is_frozen(&mut context, user_account).await);
+    assert!(!is_frozen(&mut context, depositor_account).await);
+
+    // 检查点不能挪用给其他代币账户
+    let mut ix = claim_ix(&user, &user_account);
+    let (recipient_checkpoint, _) =
+        dividend::find_dividend_checkpoint_address(&PROGRAM_ID, &pool_address, &recipient_account);
+    ix.accounts[3].pubkey = recipient_checkpoint;
+    let result = send_instruction(&mut context, ix, &[&user]).await;
+    assert_custom_error(result, TokenError::DividendCheckpointMismatch);
+    let mut ix = claim_ix(&user, &user_account);
+    ix.accounts[7].pubkey = plain_mint.pubkey();
+    let result = send_instruction(&mut context, ix, &[&user]).await;
+    assert_custom_error(result, TokenError::MintMismatch);
+
+    // 分红池存在后转账必须附带双方检查点，地址按双方代币账户校验
+    let bare_transfer =
+        instruction::transfer_tokens(&PROGRAM_ID, &recipient_account, &user_account, &recipient.pubkey(), &mint.pubkey(), 1);
+    let result = send_instruction(&mut context, bare_transfer, &[&recipient]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    let mut swapped = transfer_ix(&recipient_account, &user_account, &recipient, 1);
+    let len = swapped.accounts.len();
+    swapped.accounts.swap(len - 2, len - 1);
+    let result = send_instruction(&mut context, swapped, &[&recipient]).await;
+    assert_custom_error(result, TokenError::InvalidProgramAddress);
+    let mut wrong_pool = transfer_ix(&recipient_account, &user_account, &recipient, 1);
+    wrong_pool.accounts[7].pubkey = dividend::find_dividend_pool_address(&PROGRAM_ID, &Pubkey::new_unique()).0;
+    let result = send_instruction(&mut context, wrong_pool, &[&recipient]).await;
+    assert_custom_error(result, TokenError::InvalidProgramAddress);
+
+    // 转出方与转入方相同时同样保持冻结
+    let ix = transfer_ix(&user_account, &user_account, &user, 1);
+    send_instruction(&mut context, ix, &[&user]).await.unwrap();
+    assert!(is_frozen(&mut context, user_account).await);
+
+    // 按登记余额 1000:25 分配
+    send_instruction(&mut context, deposit_ix(410), &[]).await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    send_instructions(
+        &mut context,
+        &[claim_ix(&user, &user_account), claim_ix(&recipient, &recipient_account)],
+        &[&user, &recipient],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 1400);
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 35);
+}
+
+/// 测试领取后转移余额再用另一个账户领取，总发放不超过存入的分红
+#[tokio::test]
+async fn test_dividend_claim_move_claim() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let second = Keypair::new();
+    let funding = system_instruction::transfer(&payer, &second.pubkey(), LAMPORTS_PER_TOKEN);
+    send_instruction(&mut context, funding, &[]).await.unwrap();
+    let mint = Keypair::new();
+    let accounts = setup_dividend_mint(
+        &mut context,
+        &mint,
+        &mint_authority,
+        &[(user.pubkey(), 1000), (second.pubkey(), 0), (recipient.pubkey(), 1000), (payer, 1000)],
+    )
+    .await;
+    let (first_account, second_account, recipient_account, depositor_account) =
+        (accounts[0], accounts[1], accounts[2], accounts[3]);
+    let holdings = [first_account, second_account, recipient_account];
+
+    let claim_ix = |owner: &Keypair, account: &Pubkey| {
+        dividend::claim_dividends(&PROGRAM_ID, &owner.pubkey(), account, &mint.pubkey())
+    };
+    let deposit_ix = |amount| {
+        dividend::deposit_dividends(&PROGRAM_ID, &payer, &depositor_account, &mint.pubkey(), amount)
+    };
+
+    send_instruction(&mut context, deposit_ix(50), &[]).await.unwrap();
+    send_instructions(
+        &mut context,
+        &[claim_ix(&user, &first_account), claim_ix(&recipient, &recipient_account)],
+        &[&user, &recipient],
+    )
+    .await
+    .unwrap();
+    send_instruction(&mut context, deposit_ix(150), &[]).await.unwrap();
+
+    // 领取后直接转给另一个账户再领取：转账因登记账户冻结失败，整笔交易回滚
+    let result = send_instructions(
+        &mut context,
+        &[
+            claim_ix(&user, &first_account),
+            transfer(&spl_token::id(), &first_account, &second_account, &user.pubkey(), &[], 1000).unwrap(),
+            claim_ix(&second, &second_account),
+        ],
+        &[&user, &second],
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(spl_token::error::TokenError::AccountFrozen as u32)
+        )
+    );
+
+    // 通过本程序转移后再领取：转出方的登记余额随转账下调，新账户从登记时起计入
+    send_instructions(
+        &mut context,
+        &[
+            claim_ix(&user, &first_account),
+            dividend::transfer_tokens_with_dividends(
+                &PROGRAM_ID,
+                &first_account,
+                &second_account,
+                &user.pubkey(),
+                &mint.pubkey(),
+                1100,
+            ),
+            claim_ix(&second, &second_account),
+        ],
+        &[&user, &second],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, second_account).await, 1100);
+    send_instruction(&mut context, deposit_ix(210), &[]).await.unwrap();
+
+    // 来回转移并在两个账户上反复领取
+    for (from, from_owner, to, to_owner) in [
+        (second_account, &second, first_account, &user),
+        (first_account, &user, second_account, &second),
+    ] {
+        send_instructions(
+            &mut context,
+            &[
+                claim_ix(from_owner, &from),
+                dividend::transfer_tokens_with_dividends(
+                    &PROGRAM_ID,
+                    &from,
+                    &to,
+                    &from_owner.pubkey(),
+                    &mint.pubkey(),
+                    1100,
+                ),
+                claim_ix(to_owner, &to),
+            ],
+            &[&user, &second],
+        )
+        .await
+        .unwrap();
+    }
+    send_instruction(&mut context, claim_ix(&recipient, &recipient_account), &[&recipient]).await.unwrap();
+
+    // 总发放不超过存入的分红，其他持有人按持有量拿到一半
+    let mut total = 0;
+    for account in holdings {
+        total += get_token_balance(&mut context, account).await;
+    }
+    let paid = total - 2000;
+    let (pool_address, _) = dividend::find_dividend_pool_address(&PROGRAM_ID, &mint.pubkey());
+    let account = context.banks_client.get_account(pool_address).await.unwrap().unwrap();
+    let deposited = dividend::DividendPool::unpack_account(&account.data).unwrap().total_deposited;
+    assert_eq!(deposited, 410);
+    assert!(paid <= deposited);
+    let (vault_address, _) = dividend::find_dividend_vault_address(&PROGRAM_ID, &pool_address);
+    assert_eq!(get_token_balance(&mut context, vault_address).await, deposited - paid);
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 1200);
+}
+
+/// 测试联合曲线的储备积分与买入数量计算
+#[test]
+fn test_sale_curves() {
+    let linear = sale::CurveParams { initial_price: 1_000_000, growth: 1_000_000 };
+    assert_eq!(sale::Curve::Linear.reserve_at(&linear, 0, 9).unwrap(), 0);
+    assert_eq!(sale::Curve::Linear.reserve_at(&linear, LAMPORTS_PER_TOKEN, 9).unwrap(), 1_500_000);
+    assert_eq!(sale::Curve::Linear.reserve_at(&linear, 10 * LAMPORTS_PER_TOKEN, 9).unwrap(), 60_000_000);
+    assert_eq!(
+        sale::Curve::Linear.buy_cost(&linear, LAMPORTS_PER_TOKEN, 9 * LAMPORTS_PER_TOKEN, 9).unwrap(),
+        58_500_000
+    );
+
+    // 每个完整代币上涨 10%，不足一个代币的部分按当前价格计
+    let exponential = sale::CurveParams { initial_price: 1_000_000, growth: 1_000 };
+    assert_eq!(sale::Curve::Exponential.reserve_at(&exponential, 1, 0).unwrap(), 1_000_000);
+    assert_eq!(sale::Curve::Exponential.reserve_at(&exponential, 2, 0).unwrap(), 2_100_000);
+    assert_eq!(sale::Curve::Exponential.reserve_at(&exponential, 3, 0).unwrap(), 3_310_000);
+    assert_eq!(sale::Curve::Exponential.reserve_at(&exponential, 15, 1).unwrap(), 1_550_000);
+    let flat = sale::CurveParams { initial_price: 1_000_000, growth: 0 };
+    assert_eq!(sale::Curve::Exponential.reserve_at(&flat, 7, 0).unwrap(), 7_000_000);
+
+    // 买入数量是不超过预算的最大值，并受剩余额度限制
+    for (curve, params, decimals) in [
+        (sale::Curve::Linear, linear, 9),
+        (sale::Curve::Exponential, exponential, 6),
+    ] {
+        let sold = 3 * 10u64.pow(decimals as u32);
+        let amount = curve.tokens_for_lamports(&params, sold, 50_000_000, u64::MAX / 2, decimals);
+        assert!(amount > 0);
+        assert!(curve.buy_cost(&params, sold, amount, decimals).unwrap() <= 50_000_000);
+        assert!(curve.buy_cost(&params, sold, amount + 1, decimals).unwrap() > 50_000_000);
+        assert_eq!(curve.tokens_for_lamports(&params, sold, 50_000_000, 5, decimals), 5);
+    }
+    // 不足 1 lamport 的零头向下取整，累计支付始终等于储备积分
+    let dust = sale::Curve::Linear.tokens_for_lamports(&linear, 0, 0, u64::MAX / 2, 9);
+    assert_eq!(sale::Curve::Linear.buy_cost(&linear, 0, dust, 9).unwrap(), 0);
+    assert_eq!(sale::Curve::Linear.buy_cost(&linear, 0, dust + 1, 9).unwrap(), 1);
+}
+
+/// 测试联合曲线发售的买入、卖出与毕业
+#[tokio::test]
+async fn test_bonding_curve_sale() {
+    let (mut context, mint_authority, user, _recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let user_account = get_associated_token_address(&user.pubkey(), &mint.pubkey());
+    let mut instructions = create_mint_instructions(&payer, &mint.pubkey(), &mint_authority.pubkey(), 9);
+    instructions.push(create_associated_token_account(
+        &payer,
+        &user.pubkey(),
+        &mint.pubkey(),
+        &spl_token::id(),
+    ));
+    send_instructions(&mut context, &instructions, &[&mint]).await.unwrap();
+
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    let end = clock.unix_timestamp + 3600;
+    let params = sale::CurveParams { initial_price: 1_000_000, growth: 1_000_000 };
+    let cap = 100 * LAMPORTS_PER_TOKEN;
+    let create_ix = |creator: &Pubkey, mint: &Pubkey, start, end| {
+        sale::create_sale(&PROGRAM_ID, creator, mint, sale::Curve::Linear, params, cap, start, end)
+    };
+
+    // 结束时间必须晚于开始时间，发起人必须是铸造权限
+    let result = send_instruction(
+        &mut context,
+        create_ix(&mint_authority.pubkey(), &mint.pubkey(), end, end),
+        &[&mint_authority],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InvalidSaleConfig);
+    let result = send_instruction(
+        &mut context,
+        create_ix(&user.pubkey(), &mint.pubkey(), clock.unix_timestamp, end),
+        &[&user],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InsufficientPrivileges);
+
+    // 已有供应量的代币不能发售
+    let funded_mint = Keypair::new();
+    setup_funded_mint(&mut context, &funded_mint, &mint_authority, &user.pubkey(), 1).await;
+    let result = send_instruction(
+        &mut context,
+        create_ix(&mint_authority.pubkey(), &funded_mint.pubkey(), clock.unix_timestamp, end),
+        &[&mint_authority],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InvalidSaleConfig);
+
+    send_instruction(
+        &mut context,
+        create_ix(&mint_authority.pubkey(), &mint.pubkey(), clock.unix_timestamp, end),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    let (sale_address, _) = sale::find_sale_address(&PROGRAM_ID, &mint.pubkey());
+    let account = context.banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+    assert_eq!(Mint::unpack(&account.data).unwrap().mint_authority, COption::Some(sale_address));
+
+    // 买入：代币由发售 PDA 铸造，支付的 lamports 等于储备
+    let user_lamports = context.banks_client.get_balance(user.pubkey()).await.unwrap();
+    let buy_ix = sale::buy(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), &user_account, LAMPORTS_PER_TOKEN);
+    send_instruction(&mut context, buy_ix, &[&user]).await.unwrap();
+    let account = context.banks_client.get_account(sale_address).await.unwrap().unwrap();
+    let state = sale::Sale::unpack_account(&account.data).unwrap();
+    assert_eq!(ACCOUNT_HEADER_LEN + state.try_to_vec().unwrap().len(), sale::Sale::LEN);
+    assert!(state.sold > 43 * LAMPORTS_PER_TOKEN && state.sold < 44 * LAMPORTS_PER_TOKEN);
+    assert_eq!(state.reserve, sale::Curve::Linear.reserve_at(&params, state.sold, 9).unwrap());
+    assert!(state.reserve <= LAMPORTS_PER_TOKEN);
+    assert_eq!(get_token_balance(&mut context, user_account).await, state.sold);
+    assert_eq!(
+        context.banks_client.get_balance(user.pubkey()).await.unwrap(),
+        user_lamports - state.reserve
+    );
+    let rent = context.banks_client.get_rent().await.unwrap().minimum_balance(sale::Sale::LEN);
+    assert_eq!(account.lamports, rent + state.reserve);
+
+    // 卖出一半，沿曲线退款
+    let half = state.sold / 2;
+    let refund = sale::Curve::Linear
+        .buy_cost(&params, state.sold - half, half, 9)
+        .unwrap();
+    let sell_ix = sale::sell(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), &user_account, half);
+    send_instruction(&mut context, sell_ix, &[&user]).await.unwrap();
+    let account = context.banks_client.get_account(sale_address).await.unwrap().unwrap();
+    let sold_state = sale::Sale::unpack_account(&account.data).unwrap();
+    assert_eq!(sold_state.sold, state.sold - half);
+    assert_eq!(sold_state.reserve, state.reserve - refund);
+    assert_eq!(
+        sold_state.reserve,
+        sale::Curve::Linear.reserve_at(&params, sold_state.sold, 9).unwrap()
+    );
+    assert_eq!(get_token_balance(&mut context, user_account).await, sold_state.sold);
+    assert_eq!(
+        context.banks_client.get_balance(user.pubkey()).await.unwrap(),
+        user_lamports - sold_state.reserve
+    );
+
+    // 预算不能为 0，发售结束前不能毕业
+    let buy_ix = sale::buy(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), &user_account, 0);
+    let result = send_instruction(&mut context, buy_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidAmount);
+    let graduate_ix = sale::graduate_sale(&PROGRAM_ID, &mint.pubkey());
+    let result = send_instruction(&mut context, graduate_ix.clone(), &[]).await;
+    assert_custom_error(result, TokenError::SaleNotEnded);
+
+    // 结束后停止买入，任何人都可以触发毕业
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = end;
+    context.set_sysvar(&clock);
+    context.get_new_latest_blockhash().await.unwrap();
+    let buy_ix = sale::buy(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), &user_account, LAMPORTS_PER_TOKEN);
+    let result = send_instruction(&mut context, buy_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::SaleNotActive);
+
+    // 储备不会转给发起人，继续锁定在发售 PDA 中
+    let creator_lamports = context.banks_client.get_balance(mint_authority.pubkey()).await.unwrap();
+    send_instruction(&mut context, graduate_ix.clone(), &[]).await.unwrap();
+    assert_eq!(
+        context.banks_client.get_balance(mint_authority.pubkey()).await.unwrap(),
+        creator_lamports
+    );
+    let account = context.banks_client.get_account(sale_address).await.unwrap().unwrap();
+    let state = sale::Sale::unpack_account(&account.data).unwrap();
+    assert!(state.graduated);
+    assert_eq!(state.reserve, sold_state.reserve);
+    assert_eq!(account.lamports, rent + sold_state.reserve);
+    let account = context.banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+    assert_eq!(Mint::unpack(&account.data).unwrap().mint_authority, COption::None);
+
+    context.get_new_latest_blockhash().await.unwrap();
+    let result = send_instruction(&mut context, graduate_ix, &[]).await;
+    assert_custom_error(result, TokenError::SaleNotActive);
+    let buy_ix = sale::buy(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), &user_account, LAMPORTS_PER_TOKEN);
+    let result = send_instruction(&mut context, buy_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::SaleNotActive);
+
+    // 毕业后持有人仍可沿曲线卖出全部代币，储备恰好清零
+    let sell_ix = sale::sell(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), &user_account, state.sold);
+    send_instruction(&mut context, sell_ix, &[&user]).await.unwrap();
+    let account = context.banks_client.get_account(sale_address).await.unwrap().unwrap();
+    let state = sale::Sale::unpack_account(&account.data).unwrap();
+    assert_eq!((state.sold, state.reserve), (0, 0));
+    assert_eq!(account.lamports, rent);
+    assert_eq!(get_token_balance(&mut context, user_account).await, 0);
+    assert_eq!(context.banks_client.get_balance(user.pubkey()).await.unwrap(), user_lamports);
+}
+
+/// 测试开盘防护：开盘前禁止转账，窗口内限制冷却与单钱包额度，窗口结束后自动失效
+#[tokio::test]
+async fn test_launch_guard() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let user_account =
+        setup_funded_mint(&mut context, &mint, &mint_authority, &user.pubkey(), 1000).await;
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+    send_instruction(
+        &mut context,
+        create_associated_token_account(&payer, &recipient.pubkey(), &mint.pubkey(), &spl_token::id()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let start = context.banks_client.get_root_slot().await.unwrap() + 10;
+    let guard = config::LaunchGuard {
+        trading_start_slot: start,
+        launch_slots: 100,
+        cooldown_slots: 5,
+        max_amount_per_wallet: 300,
+    };
+
+    // 只有铸造权限可以设置
+    let result = send_instruction(
+        &mut context,
+        config::set_launch_guard(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), Some(guard)),
+        &[&user],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InsufficientPrivileges);
+    send_instruction(
+        &mut context,
+        config::set_launch_guard(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), Some(guard)),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    let (config_address, _) = config::find_mint_config_address(&PROGRAM_ID, &mint.pubkey());
+    let account = context.banks_client.get_account(config_address).await.unwrap().unwrap();
+    let state = config::MintConfig::unpack_account(&account.data).unwrap();
+    let full = config::MintConfig {
+        clawback_authority: Some(Pubkey::new_unique()),
+        interest_rate: Some(crate::interest::InterestRate {
+            rate_authority: Pubkey::new_unique(),
+            folded_bps_seconds: 0,
+            history: vec![
+                crate::interest::RateChange { timestamp: 0, rate_bps: 0 };
+                crate::interest::MAX_RATE_HISTORY
+            ],
+        }),
+        transfer_hook_program: Some(Pubkey::new_unique()),
+        ..state.clone()
+    };
+    assert_eq!(ACCOUNT_HEADER_LEN + full.try_to_vec().unwrap().len(), config::MintConfig::LEN);
+    assert_eq!(state.launch_guard, Some(guard));
+
+    let transfer_ix = |amount| {
+        config::transfer_tokens_with_config(
+            &PROGRAM_ID,
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &mint.pubkey(),
+            &user.pubkey(),
+            &recipient.pubkey(),
+            amount,
+        )
+    };
+
+    // 开盘前拒绝转账
+    let result = send_instruction(&mut context, transfer_ix(100), &[&user]).await;
+    assert_custom_error(result, TokenError::TradingNotStarted);
+
+    // 开盘后首次转账创建双方钱包记录，只有转出方进入冷却
+    context.warp_to_slot(start).unwrap();
+    send_instruction(&mut context, transfer_ix(200), &[&user]).await.unwrap();
+    let (recipient_record, _) =
+        config::find_launch_wallet_address(&PROGRAM_ID, &mint.pubkey(), &recipient.pubkey());
+    let account = context.banks_client.get_account(recipient_record).await.unwrap().unwrap();
+    let record = config::LaunchWallet::unpack_account(&account.data).unwrap();
+    assert_eq!(record.received, 200);
+    assert_eq!(record.last_transfer_slot, 0);
+    let (user_record, _) = config::find_launch_wallet_address(&PROGRAM_ID, &mint.pubkey(), &user.pubkey());
+    let account = context.banks_client.get_account(user_record).await.unwrap().unwrap();
+    let record = config::LaunchWallet::unpack_account(&account.data).unwrap();
+    assert_eq!(record.received, 0);
+    assert!(record.last_transfer_slot >= start);
+
+    // 配置存在时开盘记录必需，省略或替换为其他钱包的记录都不能绕过冷却
+    let without_wallets = instruction::transfer_tokens(
+        &PROGRAM_ID,
+        &user_account,
+        &recipient_account,
+        &user.pubkey(),
+        &mint.pubkey(),
+        50,
+    );
+    let result = send_instruction(&mut context, without_wallets, &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    let mut fresh_wallet = transfer_ix(50);
+    fresh_wallet.accounts[9].pubkey =
+        config::find_launch_wallet_address(&PROGRAM_ID, &mint.pubkey(), &Pubkey::new_unique()).0;
+    let result = send_instruction(&mut context, fresh_wallet, &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidProgramAddress);
+
+    // 冷却期内再次转账被拒绝
+    let result = send_instruction(&mut context, transfer_ix(50), &[&user]).await;
+    assert_custom_error(result, TokenError::TransferCooldown);
+
+    // 冷却结束后，单钱包累计转入不能超过额度
+    context.warp_to_slot(record.last_transfer_slot + 5).unwrap();
+    let result = send_instruction(&mut context, transfer_ix(150), &[&user]).await;
+    assert_custom_error(result, TokenError::LaunchLimitExceeded);
+    send_instruction(&mut context, transfer_ix(100), &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 300);
+
+    // 开盘窗口结束后防护自动失效
+    context.warp_to_slot(start + 100).unwrap();
+    send_instruction(&mut context, transfer_ix(400), &[&user]).await.unwrap();
+    context.get_new_latest_blockhash().await.unwrap();
+    send_instruction(&mut context, transfer_ix(300), &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 1000);
+
+    // 关闭防护
+    send_instruction(
+        &mut context,
+        config::set_launch_guard(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), None),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    let account = context.banks_client.get_account(config_address).await.unwrap().unwrap();
+    assert_eq!(config::MintConfig::unpack_account(&account.data).unwrap().launch_guard, None);
+}
+
+/// 测试开盘防护不能被第三方利用：转入不触发接收方冷却，0 数量转账不能创建或刷新他人的钱包记录
+#[tokio::test]
+async fn test_launch_guard_griefing() {
+    let (mut context, mint_authority, attacker, victim) = setup_program_test().await;
+    let mint = Keypair::new();
+    let attacker_account =
+        setup_funded_mint(&mut context, &mint, &mint_authority, &attacker.pubkey(), 1000).await;
+    let victim_account = get_associated_token_address(&victim.pubkey(), &mint.pubkey());
+    let payer = context.payer.pubkey();
+    let start = context.banks_client.get_root_slot().await.unwrap() + 10;
+    let guard = config::LaunchGuard {
+        trading_start_slot: start,
+        launch_slots: 1000,
+        cooldown_slots: 500,
+        max_amount_per_wallet: 300,
+    };
+    send_instructions(
+        &mut context,
+        &[
+            create_associated_token_account(&payer, &victim.pubkey(), &mint.pubkey(), &spl_token::id()),
+            mint_to(&spl_token::id(), &mint.pubkey(), &victim_account, &mint_authority.pubkey(), &[], 100).unwrap(),
+            config::set_launch_guard(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), Some(guard)),
+        ],
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    context.warp_to_slot(start).unwrap();
+
+    let transfer_ix = |from: &Keypair, from_account, to: &Keypair, to_account, amount| {
+        config::transfer_tokens_with_config(
+            &PROGRAM_ID,
+            from_account,
+            to_account,
+            &from.pubkey(),
+            &mint.pubkey(),
+            &from.pubkey(),
+            &to.pubkey(),
+            amount,
+        )
+    };
+    let (victim_record, _) = config::find_launch_wallet_address(&PROGRAM_ID, &mint.pubkey(), &victim.pubkey());
+
+    // 0 数量转账不能为受害者创建钱包记录
+    let result = send_instruction(
+        &mut context,
+        transfer_ix(&attacker, &attacker_account, &victim, &victim_account, 0),
+        &[&attacker],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InvalidAmount);
+    assert!(context.banks_client.get_account(victim_record).await.unwrap().is_none());
+
+    // 小额转入只累计额度，不会让受害者进入冷却
+    send_instruction(
+        &mut context,
+        transfer_ix(&attacker, &attacker_account, &victim, &victim_account, 1),
+        &[&attacker],
+    )
+    .await
+    .unwrap();
+    let account = context.banks_client.get_account(victim_record).await.unwrap().unwrap();
+    let record = config::LaunchWallet::unpack_account(&account.data).unwrap();
+    assert_eq!((record.received, record.last_transfer_slot), (1, 0));
+    send_instruction(
+        &mut context,
+        transfer_ix(&victim, &victim_account, &attacker, &attacker_account, 50),
+        &[&victim],
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_token_balance(&mut context, victim_account).await, 51);
+
+    // 转出方仍受冷却约束
+    let result = send_instruction(
+        &mut context,
+        transfer_ix(&attacker, &attacker_account, &victim, &victim_account, 2),
+        &[&attacker],
+    )
+    .await;
+    assert_custom_error(result, TokenError::TransferCooldown);
+    let result = send_instruction(
+        &mut context,
+        transfer_ix(&victim, &victim_account, &attacker, &attacker_account, 1),
+        &[&victim],
+    )
+    .await;
+    assert_custom_error(result, TokenError::TransferCooldown);
+}
+
+/// 测试不可转让代币：只能使用 Token-2022，可以铸造和销毁，但不能转账
+#[tokio::test]
+async fn test_non_transferable_mint() {
+    use spl_token_2022::extension::{
+        non_transferable::NonTransferable, BaseStateWithExtensions, StateWithExtensions,
+    };
+
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let token_program = spl_token_2022::id();
+    let mint = Keypair::new();
+    let initialize_ix = instruction::initialize_non_transferable_mint(
+        &PROGRAM_ID,
+        &mint.pubkey(),
+        &payer,
+        &mint_authority.pubkey(),
+        None,
+        0,
+    );
+
+    // SPL Token 铸造账户无法阻止直接转账，拒绝创建
+    let mut classic_ix = initialize_ix.clone();
+    classic_ix.accounts[3].pubkey = spl_token::id();
+    let result = send_instruction(&mut context, classic_ix, &[&mint]).await;
+    assert_custom_error(result, TokenError::IncorrectTokenProgram);
+
+    // 缺少配置账户时无法记录不可转让标记
+    let mut missing_config = initialize_ix.clone();
+    missing_config.accounts.pop();
+    let result = send_instruction(&mut context, missing_config, &[&mint]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    let user_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &user.pubkey(),
+        &mint.pubkey(),
+        &token_program,
+    );
+    let recipient_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+        &recipient.pubkey(),
+        &mint.pubkey(),
+        &token_program,
+    );
+    send_instructions(
+        &mut context,
+        &[
+            initialize_ix,
+            instruction::create_token_account_with_program(
+                &PROGRAM_ID,
+                &payer,
+                &user.pubkey(),
+                &mint.pubkey(),
+                &token_program,
+            ),
+            instruction::create_token_account_with_program(
+                &PROGRAM_ID,
+                &payer,
+                &recipient.pubkey(),
+                &mint.pubkey(),
+                &token_program,
+            ),
+            instruction::with_token_program(
+                instruction::mint_tokens(
+                    &PROGRAM_ID,
+                    &mint.pubkey(),
+                    &user_account,
+                    &mint_authority.pubkey(),
+                    &payer,
+                    2,
+                ),
+                &token_program,
+            ),
+        ],
+        &[&mint, &mint_authority],
+    )
+    .await
+    .unwrap();
+
+    let (config_address, _) = config::find_mint_config_address(&PROGRAM_ID, &mint.pubkey());
+    let account = context.banks_client.get_account(config_address).await.unwrap().unwrap();
+    assert!(config::MintConfig::unpack_account(&account.data).unwrap().non_transferable);
+
+    // 扩展在代币程序层面禁止直接转账
+    let account = context.banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+    assert!(state.get_extension::<NonTransferable>().is_ok());
+    let direct_transfer = spl_token_2022::instruction::transfer_checked(
+        &token_program,
+        &user_account,
+        &mint.pubkey(),
+        &recipient_account,
+        &user.pubkey(),
+        &[],
+        1,
+        0,
+    )
+    .unwrap();
+    assert!(send_instruction(&mut context, direct_transfer, &[&user]).await.is_err());
+
+    // 附带配置的 TransferTokens 被拒绝
+    let transfer_ix = instruction::with_token_program(
+        config::transfer_tokens_with_config(
+            &PROGRAM_ID,
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &mint.pubkey(),
+            &user.pubkey(),
+            &recipient.pubkey(),
+            1,
+        ),
+        &token_program,
+    );
+    let result = send_instruction(&mut context, transfer_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::NonTransferable);
+
+    // 省略配置 PDA 不能绕过限制
+    let mut without_config = instruction::with_token_program(
+        instruction::transfer_tokens(
+            &PROGRAM_ID,
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &mint.pubkey(),
+            1,
+        ),
+        &token_program,
+    );
+    without_config.accounts.remove(6);
+    let result = send_instruction(&mut context, without_config, &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    // 持有人仍然可以销毁
+    let burn_ix = instruction::with_token_program(
+        instruction::burn_tokens(&PROGRAM_ID, &user_account, &mint.pubkey(), &user.pubkey(), 1),
+        &token_program,
+    );
+    send_instruction(&mut context, burn_ix, &[&user]).await.unwrap();
+    let account = context.banks_client.get_account(user_account).await.unwrap().unwrap();
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap();
+    assert_eq!(state.base.amount, 1);
+}
+
+/// 测试回收：Token-2022 通过永久委托回收，SPL Token 通过持有人授予的委托回收并冻结源账户
+#[tokio::test]
+async fn test_clawback() {
+    use spl_token_2022::extension::{
+        permanent_delegate::PermanentDelegate, BaseStateWithExtensions, StateWithExtensions,
+    };
+
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let authority = Keypair::new();
+
+    for token_program in [spl_token::id(), spl_token_2022::id()] {
+        let mint = Keypair::new();
+        let (config_address, _) = config::find_mint_config_address(&PROGRAM_ID, &mint.pubkey());
+        let user_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &user.pubkey(),
+            &mint.pubkey(),
+            &token_program,
+        );
+        let recipient_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &recipient.pubkey(),
+            &mint.pubkey(),
+            &token_program,
+        );
+        send_instructions(
+            &mut context,
+            &[
+                instruction::initialize_clawback_mint(
+                    &PROGRAM_ID,
+                    &token_program,
+                    &mint.pubkey(),
+                    &payer,
+                    &mint_authority.pubkey(),
+                    &authority.pubkey(),
+                    0,
+                ),
+                instruction::create_token_account_with_program(
+                    &PROGRAM_ID,
+                    &payer,
+                    &user.pubkey(),
+                    &mint.pubkey(),
+                    &token_program,
+                ),
+                instruction::create_token_account_with_program(
+                    &PROGRAM_ID,
+                    &payer,
+                    &recipient.pubkey(),
+                    &mint.pubkey(),
+                    &token_program,
+                ),
+                instruction::with_token_program(
+                    instruction::mint_tokens(
+                        &PROGRAM_ID,
+                        &mint.pubkey(),
+                        &user_account,
+                        &mint_authority.pubkey(),
+                        &payer,
+                        10,
+                    ),
+                    &token_program,
+                ),
+            ],
+            &[&mint, &mint_authority],
+        )
+        .await
+        .unwrap();
+
+        // 冻结权限固定为配置 PDA，Token-2022 的永久委托同样是配置 PDA
+        let account = context.banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+        assert_eq!(state.base.freeze_authority, COption::Some(config_address));
+        if token_program == spl_token_2022::id() {
+            let delegate = state.get_extension::<PermanentDelegate>().unwrap().delegate;
+            assert_eq!(Option::<Pubkey>::from(delegate), Some(config_address));
+        } else {
+            let approve_ix = crate::clawback::approve_clawback_delegate(
+                &PROGRAM_ID,
+                &user_account,
+                &user.pubkey(),
+                &mint.pubkey(),
+            )
+            .unwrap();
+            send_instruction(&mut context, approve_ix, &[&user]).await.unwrap();
+        }
+        let account = context.banks_client.get_account(config_address).await.unwrap().unwrap();
+        assert_eq!(
+            config::MintConfig::unpack_account(&account.data).unwrap().clawback_authority,
+            Some(authority.pubkey())
+        );
+
+        // 只有回收权限可以回收
+        let forged_ix = crate::clawback::clawback(
+            &PROGRAM_ID,
+            &user.pubkey(),
+            &user_account,
+            &recipient_account,
+            &mint.pubkey(),
+            &token_program,
+            4,
+        );
+        let result = send_instruction(&mut context, forged_ix, &[&user]).await;
+        assert_custom_error(result, TokenError::InsufficientPrivileges);
+
+        let clawback_ix = crate::clawback::clawback(
+            &PROGRAM_ID,
+            &authority.pubkey(),
+            &user_account,
+            &recipient_account,
+            &mint.pubkey(),
+            &token_program,
+            4,
+        );
+        send_instruction(&mut context, clawback_ix, &[&authority]).await.unwrap();
+        let classic = token_program == spl_token::id();
+
+        let account = context.banks_client.get_account(user_account).await.unwrap().unwrap();
+        let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap();
+        assert_eq!(state.base.amount, 6);
+        assert_eq!(state.base.is_frozen(), classic);
+        let account = context.banks_client.get_account(recipient_account).await.unwrap().unwrap();
+        let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data).unwrap();
+        assert_eq!(state.base.amount, 4);
+
+        if classic {
+            // 已冻结的账户先解冻再回收，回收后恢复冻结
+            let clawback_ix = crate::clawback::clawback(
+                &PROGRAM_ID,
+                &authority.pubkey(),
+                &user_account,
+                &recipient_account,
+                &mint.pubkey(),
+                &token_program,
+                2,
+            );
+            send_instruction(&mut context, clawback_ix, &[&authority]).await.unwrap();
+            let account = context.banks_client.get_account(user_account).await.unwrap().unwrap();
+            let state = SplAccount::unpack(&account.data).unwrap();
+            assert_eq!(state.amount, 4);
+            assert!(state.is_frozen());
+
+            // 没有授予委托的账户只会被冻结
+            let clawback_ix = crate::clawback::clawback(
+                &PROGRAM_ID,
+                &authority.pubkey(),
+                &recipient_account,
+                &user_account,
+                &mint.pubkey(),
+                &token_program,
+                1,
+            );
+            send_instruction(&mut context, clawback_ix, &[&authority]).await.unwrap();
+            let account = context.banks_client.get_account(recipient_account).await.unwrap().unwrap();
+            let state = SplAccount::unpack(&account.data).unwrap();
+            assert_eq!(state.amount, 6);
+            assert!(state.is_frozen());
+
+            // 未启用回收的铸造账户不能回收
+            let other_mint = Keypair::new();
+            let initialize_ix = instruction::initialize_interest_bearing_mint(
+                &PROGRAM_ID,
+                &token_program,
+                &other_mint.pubkey(),
+                &payer,
+                &mint_authority.pubkey(),
+                None,
+                0,
+                100,
+            );
+            send_instruction(&mut context, initialize_ix, &[&other_mint]).await.unwrap();
+            let clawback_ix = crate::clawback::clawback(
+                &PROGRAM_ID,
+                &authority.pubkey(),
+                &user_account,
+                &recipient_account,
+                &other_mint.pubkey(),
+                &token_program,
+                1,
+            );
+            let result = send_instruction(&mut context, clawback_ix, &[&authority]).await;
+            assert_custom_error(result, TokenError::ClawbackNotEnabled);
+        }
+    }
+}
+
+/// 测试利息折算：与 Token-2022 扩展的结果一致，利率历史折算后结果不变
+#[test]
+fn test_interest_accrual() {
+    use crate::interest::{InterestRate, MAX_RATE_HISTORY, SECONDS_PER_YEAR};
+    use spl_token_2022::extension::interest_bearing_mint::InterestBearingConfig;
+
+    let year = SECONDS_PER_YEAR as i64;
+    let authority = Pubkey::new_unique();
+
+    // 5% 计息一年为 exp(0.05)
+    let interest = InterestRate::new(authority, 500, 0);
+    assert_eq!(interest.amount_to_ui_amount(1, 0, year).unwrap(), "1.0512710963760241");
+    assert_eq!(interest.amount_to_ui_amount(1_000_000, 6, 0).unwrap(), "1");
+    assert_eq!(interest.ui_amount_to_amount("1.0512710963760241", 0, year).unwrap(), 1);
+    assert_eq!(interest.ui_amount_to_amount("105.12710963760241", 2, year).unwrap(), 10_000);
+    assert!(interest.ui_amount_to_amount("-1", 0, year).is_err());
+    assert!(interest.ui_amount_to_amount("abc", 0, year).is_err());
+
+    // 半年后利率改为 10%，与扩展的时间加权平均利率结果一致
+    let mut interest = InterestRate::new(authority, 500, 0);
+    interest.record(1000, year / 2).unwrap();
+    assert_eq!(interest.current_rate(), 1000);
+    let extension = InterestBearingConfig {
+        initialization_timestamp: 0.into(),
+        pre_update_average_rate: 500.into(),
+        last_update_timestamp: (year / 2).into(),
+        current_rate: 1000.into(),
+        ..Default::default()
+    };
+    let ours: f64 = interest.amount_to_ui_amount(1_000_000, 6, year).unwrap().parse().unwrap();
+    let theirs: f64 = extension.amount_to_ui_amount(1_000_000, 6, year).unwrap().parse().unwrap();
+    assert!((ours - theirs).abs() < 1e-12);
+
+    // 同一时刻多次修改只保留最后一次
+    interest.record(700, year / 2).unwrap();
+    assert_eq!(interest.history.len(), 2);
+    assert_eq!(interest.current_rate(), 700);
+
+    // 历史超过上限后折算最早的区间，结果与完整历史一致
+    let mut full = InterestRate::new(authority, 100, 0);
+    let mut bounded = full.clone();
+    for step in 1..=(MAX_RATE_HISTORY as i64 * 2) {
+        let rate = (step * 37 % 800) as i16 - 200;
+        full.history.push(crate::interest::RateChange {
+            timestamp: step * 86_400,
+            rate_bps: rate,
+        });
+        bounded.record(rate, step * 86_400).unwrap();
+    }
+    assert_eq!(bounded.history.len(), MAX_RATE_HISTORY);
+    assert_ne!(bounded.folded_bps_seconds, 0);
+    assert_eq!(
+        bounded.amount_to_ui_amount(123_456_789, 6, year),
+        full.amount_to_ui_amount(123_456_789, 6, year)
+    );
+
+    let config = config::MintConfig {
+        is_initialized: true,
+        mint: Pubkey::new_unique(),
+        non_transferable: true,
+        launch_guard: Some(config::LaunchGuard {
+            trading_start_slot: 0,
+            launch_slots: 0,
+            cooldown_slots: 0,
+            max_amount_per_wallet: 0,
+        }),
+        clawback_authority: Some(authority),
+        interest_rate: Some(bounded),
+        transfer_hook_program: Some(Pubkey::new_unique()),
+        bump: 255,
+    };
+    assert_eq!(ACCOUNT_HEADER_LEN + config.try_to_vec().unwrap().len(), config::MintConfig::LEN);
+}
+
+/// 通过模拟交易调用 `GetBalance` 并解析返回数据
+async fn simulate_get_balance(
+    context: &mut ProgramTestContext,
+    token_account: Pubkey,
+    mint: Pubkey,
+) -> crate::interest::TokenBalance {
+    let transaction = Transaction::new_signed_with_payer(
+        &[crate::interest::get_balance(&PROGRAM_ID, &token_account, &mint)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let simulation = context.banks_client.simulate_transaction(transaction).await.unwrap();
+    simulation.result.unwrap().unwrap();
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, PROGRAM_ID);
+    crate::interest::TokenBalance::try_from_slice(&return_data.data).unwrap()
+}
+
+/// 测试计息代币：`GetBalance` 返回含利息的余额，利率权限可以修改利率
+#[tokio::test]
+async fn test_interest_bearing_mint() {
+    use crate::interest::{self, SECONDS_PER_YEAR};
+    use spl_token_2022::extension::{
+        interest_bearing_mint::InterestBearingConfig, BaseStateWithExtensions, StateWithExtensions,
+    };
+
+    let (mut context, mint_authority, user, _recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+
+    // 未启用计息的铸造账户按精度换算，也不能设置利率
+    let plain_mint = Keypair::new();
+    let plain_account = setup_funded_mint(&mut context, &plain_mint, &mint_authority, &user.pubkey(), 1_500_000_000).await;
+    let balance = simulate_get_balance(&mut context, plain_account, plain_mint.pubkey()).await;
+    assert_eq!(
+        balance,
+        interest::TokenBalance { amount: 1_500_000_000, decimals: 9, ui_amount: "1.5".to_string() }
+    );
+    let other_mint = Keypair::new();
+    let initialize_ix = instruction::initialize_clawback_mint(
+        &PROGRAM_ID,
+        &spl_token::id(),
+        &other_mint.pubkey(),
+        &payer,
+        &mint_authority.pubkey(),
+        &mint_authority.pubkey(),
+        0,
+    );
+    send_instruction(&mut context, initialize_ix, &[&other_mint]).await.unwrap();
+    let set_rate_ix = interest::set_interest_rate(
+        &PROGRAM_ID,
+        &mint_authority.pubkey(),
+        &other_mint.pubkey(),
+        &spl_token::id(),
+        100,
+    );
+    let result = send_instruction(&mut context, set_rate_ix, &[&mint_authority]).await;
+    assert_custom_error(result, TokenError::InterestNotEnabled);
+
+    for token_program in [spl_token::id(), spl_token_2022::id()] {
+        let mint = Keypair::new();
+        let (config_address, _) = config::find_mint_config_address(&PROGRAM_ID, &mint.pubkey());
+        let user_account = spl_associated_token_account::get_associated_token_address_with_program_id(
+            &user.pubkey(),
+            &mint.pubkey(),
+            &token_program,
+        );
+        send_instructions(
+            &mut context,
+            &[
+                instruction::initialize_interest_bearing_mint(
+                    &PROGRAM_ID,
+                    &token_program,
+                    &mint.pubkey(),
+                    &payer,
+                    &mint_authority.pubkey(),
+                    None,
+                    6,
+                    500,
+                ),
+                instruction::create_token_account_with_program(
+                    &PROGRAM_ID,
+                    &payer,
+                    &user.pubkey(),
+                    &mint.pubkey(),
+                    &token_program,
+                ),
+                instruction::with_token_program(
+                    instruction::mint_tokens(
+                        &PROGRAM_ID,
+                        &mint.pubkey(),
+                        &user_account,
+                        &mint_authority.pubkey(),
+                        &payer,
+                        1_000_000,
+                    ),
+                    &token_program,
+                ),
+            ],
+            &[&mint, &mint_authority],
+        )
+        .await
+        .unwrap();
+
+        // 一年后余额含 5% 的连续复利
+        let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        clock.unix_timestamp += SECONDS_PER_YEAR as i64;
+        context.set_sysvar(&clock);
+        let balance = simulate_get_balance(&mut context, user_account, mint.pubkey()).await;
+        assert_eq!(balance.amount, 1_000_000);
+        assert_eq!(balance.decimals, 6);
+        let ui_amount: f64 = balance.ui_amount.parse().unwrap();
+        assert!((ui_amount - 0.05_f64.exp()).abs() < 1e-4);
+
+        let account = context.banks_client.get_account(config_address).await.unwrap().unwrap();
+        let mint_config = config::MintConfig::unpack_account(&account.data).unwrap();
+        let interest_rate = mint_config.interest_rate.clone().unwrap();
+        assert_eq!(interest_rate.rate_authority, mint_authority.pubkey());
+        assert_eq!(interest_rate.current_rate(), 500);
+        let account = context.banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+        assert_eq!(
+            balance.ui_amount,
+            interest::ui_amount_for_mint(&account.data, Some(&mint_config), 1_000_000, clock.unix_timestamp)
+                .unwrap()
+        );
+        if token_program == spl_token_2022::id() {
+            let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+            let extension = state.get_extension::<InterestBearingConfig>().unwrap();
+            assert_eq!(Option::<Pubkey>::from(extension.rate_authority), Some(config_address));
+            assert_eq!(i16::from(extension.current_rate), 500);
+        } else {
+            assert_eq!(
+                balance.ui_amount,
+                interest_rate.amount_to_ui_amount(1_000_000, 6, clock.unix_timestamp).unwrap()
+            );
+        }
+
+        // 只有利率权限可以修改利率
+        let forged_ix = interest::set_interest_rate(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), &token_program, 0);
+        let result = send_instruction(&mut context, forged_ix, &[&user]).await;
+        assert_custom_error(result, TokenError::InsufficientPrivileges);
+
+        let set_rate_ix =
+            interest::set_interest_rate(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), &token_program, -200);
+        send_instruction(&mut context, set_rate_ix, &[&mint_authority]).await.unwrap();
+        let account = context.banks_client.get_account(config_address).await.unwrap().unwrap();
+        let interest_rate = config::MintConfig::unpack_account(&account.data).unwrap().interest_rate.unwrap();
+        assert_eq!(interest_rate.history.len(), 2);
+        assert_eq!(interest_rate.history[1].timestamp, clock.unix_timestamp);
+        assert_eq!(interest_rate.current_rate(), -200);
+        if token_program == spl_token_2022::id() {
+            let account = context.banks_client.get_account(mint.pubkey()).await.unwrap().unwrap();
+            let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+            let extension = state.get_extension::<InterestBearingConfig>().unwrap();
+            assert_eq!(i16::from(extension.current_rate), -200);
+        }
+    }
+}
+
+/// 示例转账钩子否决转账时返回的错误码
+const SAMPLE_HOOK_LIMIT_EXCEEDED: u32 = 0x4000;
+
+/// 示例转账钩子程序：额外账户中保存单笔转账上限，超过上限时否决
+fn process_sample_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let hook::TransferHookInstruction::Execute { amount } = hook::TransferHookInstruction::unpack(input)?;
+    let accounts_iter = &mut accounts.iter();
+    let source = next_account_info(accounts_iter)?;
+    let _destination = next_account_info(accounts_iter)?;
+    let _authority = next_account_info(accounts_iter)?;
+    let validation = next_account_info(accounts_iter)?;
+    let limit_account = next_account_info(accounts_iter)?;
+
+    let mint = SplAccount::unpack(&source.data.borrow())?.mint;
+    if *validation.key != hook::find_extra_account_metas_address(program_id, &mint).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if limit_account.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let limit = u64::from_le_bytes(limit_account.data.borrow()[..8].try_into().unwrap());
+    if amount > limit {
+        return Err(ProgramError::Custom(SAMPLE_HOOK_LIMIT_EXCEEDED));
+    }
+    Ok(())
+}
+
+/// 测试转账钩子：设置钩子后每笔转账都必须附带钩子账户，转移后调用钩子，钩子可以否决转账
+#[tokio::test]
+async fn test_transfer_hook() {
+    let hook_program_id = Pubkey::new_unique();
+    let mint = Keypair::new();
+    let limit_address = Pubkey::new_unique();
+    let (validation_address, _) = hook::find_extra_account_metas_address(&hook_program_id, &mint.pubkey());
+    let extra_accounts = [hook::ExtraAccountMeta { pubkey: limit_address, is_signer: false, is_writable: false }];
+
+    let mut program_test = program_test();
+    program_test.add_program("sample_transfer_hook", hook_program_id, processor!(process_sample_hook));
+    program_test.add_account(
+        validation_address,
+        solana_sdk::account::Account {
+            lamports: LAMPORTS_PER_TOKEN,
+            data: hook::ExtraAccountMetas { accounts: extra_accounts.to_vec() }.try_to_vec().unwrap(),
+            owner: hook_program_id,
+            ..Default::default()
+        },
+    );
+    program_test.add_account(
+        limit_address,
+        solana_sdk::account::Account {
+            lamports: LAMPORTS_PER_TOKEN,
+            data: 100u64.to_le_bytes().to_vec(),
+            owner: hook_program_id,
+            ..Default::default()
+        },
+    );
+    let (mut context, mint_authority, user, recipient) = start_program_test(program_test).await;
+    let payer = context.payer.pubkey();
+
+    let user_account = setup_funded_mint(&mut context, &mint, &mint_authority, &user.pubkey(), 1_000).await;
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+    send_instruction(
+        &mut context,
+        instruction::create_token_account(&PROGRAM_ID, &payer, &recipient.pubkey(), &mint.pubkey()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // 只有铸造权限可以设置钩子
+    let forged_ix = config::set_transfer_hook(&PROGRAM_ID, &user.pubkey(), &mint.pubkey(), Some(hook_program_id));
+    let result = send_instruction(&mut context, forged_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::InsufficientPrivileges);
+    let set_hook_ix =
+        config::set_transfer_hook(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), Some(hook_program_id));
+    send_instruction(&mut context, set_hook_ix, &[&mint_authority]).await.unwrap();
+    let (config_address, _) = config::find_mint_config_address(&PROGRAM_ID, &mint.pubkey());
+    let account = context.banks_client.get_account(config_address).await.unwrap().unwrap();
+    assert_eq!(
+        config::MintConfig::unpack_account(&account.data).unwrap().transfer_hook_program,
+        Some(hook_program_id)
+    );
+
+    let transfer_ix = |amount, extra_accounts: &[hook::ExtraAccountMeta]| {
+        hook::transfer_tokens_with_hook(
+            &PROGRAM_ID,
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &mint.pubkey(),
+            &user.pubkey(),
+            &recipient.pubkey(),
+            &hook_program_id,
+            extra_accounts,
+            amount,
+        )
+    };
+
+    // 缺少钩子账户或额外账户不符时拒绝
+    let without_hook = config::transfer_tokens_with_config(
+        &PROGRAM_ID,
+        &user_account,
+        &recipient_account,
+        &user.pubkey(),
+        &mint.pubkey(),
+        &user.pubkey(),
+        &recipient.pubkey(),
+        50,
+    );
+    let result = send_instruction(&mut context, without_hook, &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    let bare_transfer =
+        instruction::transfer_tokens(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 150);
+    let result = send_instruction(&mut context, bare_transfer, &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    let result = send_instruction(&mut context, transfer_ix(150, &[]), &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    assert_eq!(get_token_balance(&mut context, user_account).await, 1_000);
+    let forged_extra = [hook::ExtraAccountMeta { pubkey: Pubkey::new_unique(), ..extra_accounts[0] }];
+    let result = send_instruction(&mut context, transfer_ix(50, &forged_extra), &[&user]).await;
+    assert_custom_error(result, TokenError::TransferHookMismatch);
+
+    // 钩子放行上限以内的转账
+    send_instruction(&mut context, transfer_ix(50, &extra_accounts), &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 950);
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 50);
+
+    // 钩子否决时整笔转账回滚
+    let result = send_instruction(&mut context, transfer_ix(150, &extra_accounts), &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::Custom(SAMPLE_HOOK_LIMIT_EXCEEDED))
+    );
+    assert_eq!(get_token_balance(&mut context, user_account).await, 950);
+
+    // 移除钩子后不再调用
+    let remove_hook_ix = config::set_transfer_hook(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), None);
+    send_instruction(&mut context, remove_hook_ix, &[&mint_authority]).await.unwrap();
+    let transfer_ix = config::transfer_tokens_with_config(
+        &PROGRAM_ID,
+        &user_account,
+        &recipient_account,
+        &user.pubkey(),
+        &mint.pubkey(),
+        &user.pubkey(),
+        &recipient.pubkey(),
+        150,
+    );
+    send_instruction(&mut context, transfer_ix, &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 200);
+}
+
+/// 恶意转账钩子程序：用额外账户中的签名者把源账户的全部余额转给攻击者
+fn process_malicious_hook(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    hook::TransferHookInstruction::unpack(input)?;
+    let accounts_iter = &mut accounts.iter().skip(4);
+    let source = next_account_info(accounts_iter)?;
+    let attacker = next_account_info(accounts_iter)?;
+    let owner = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let amount = SplAccount::unpack(&source.data.borrow())?.amount;
+    // 按实际拿到的权限请求签名，由 SPL Token 检查签名（测试环境中越权 CPI 会直接 panic）
+    let mut transfer_ix = transfer(token_program.key, source.key, attacker.key, owner.key, &[], amount)?;
+    transfer_ix.accounts[2].is_signer = owner.is_signer;
+    solana_program::program::invoke(
+        &transfer_ix,
+        &[source.clone(), attacker.clone(), owner.clone(), token_program.clone()],
+    )
+}
+
+/// 测试恶意钩子：额外账户不能重复转账账户，调用钩子时不转交任何签名权限
+#[tokio::test]
+async fn test_malicious_transfer_hook() {
+    let hook_program_id = Pubkey::new_unique();
+    let attacker = Keypair::new();
+    let owner_mint = Keypair::new();
+    let payer_mint = Keypair::new();
+
+    let mut program_test = program_test();
+    program_test.add_program("malicious_transfer_hook", hook_program_id, processor!(process_malicious_hook));
+    let (mut context, mint_authority, user, recipient) = start_program_test(program_test).await;
+    let payer = context.payer.pubkey();
+
+    let mut accounts = Vec::new();
+    for (mint, victim) in [(&owner_mint, user.pubkey()), (&payer_mint, payer)] {
+        let user_account = setup_funded_mint(&mut context, mint, &mint_authority, &user.pubkey(), 1_000).await;
+        let victim_account = get_associated_token_address(&victim, &mint.pubkey());
+        let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+        let attacker_account = get_associated_token_address(&attacker.pubkey(), &mint.pubkey());
+        let mut instructions = vec![
+            instruction::create_token_account(&PROGRAM_ID, &payer, &recipient.pubkey(), &mint.pubkey()),
+            instruction::create_token_account(&PROGRAM_ID, &payer, &attacker.pubkey(), &mint.pubkey()),
+            config::set_transfer_hook(&PROGRAM_ID, &mint_authority.pubkey(), &mint.pubkey(), Some(hook_program_id)),
+        ];
+        if victim != user.pubkey() {
+            instructions.extend([
+                instruction::create_token_account(&PROGRAM_ID, &payer, &victim, &mint.pubkey()),
+                mint_to(&spl_token::id(), &mint.pubkey(), &victim_account, &mint_authority.pubkey(), &[], 500)
+                    .unwrap(),
+            ]);
+        }
+        send_instructions(&mut context, &instructions, &[&mint_authority]).await.unwrap();
+
+        // 校验 PDA 声明受害者的代币账户、攻击者账户、带签名标志的受害者和 SPL Token 程序
+        let extra_accounts = vec![
+            hook::ExtraAccountMeta { pubkey: victim_account, is_signer: false, is_writable: true },
+            hook::ExtraAccountMeta { pubkey: attacker_account, is_signer: false, is_writable: true },
+            hook::ExtraAccountMeta { pubkey: victim, is_signer: true, is_writable: false },
+            hook::ExtraAccountMeta { pubkey: spl_token::id(), is_signer: false, is_writable: false },
+        ];
+        let (validation_address, _) = hook::find_extra_account_metas_address(&hook_program_id, &mint.pubkey());
+        let data = hook::ExtraAccountMetas { accounts: extra_accounts.clone() }.try_to_vec().unwrap();
+        context.set_account(
+            &validation_address,
+            &solana_sdk::account::AccountSharedData::from(solana_sdk::account::Account {
+                lamports: LAMPORTS_PER_TOKEN,
+                data,
+                owner: hook_program_id,
+                ..Default::default()
+            }),
+        );
+        accounts.push((mint, user_account, victim_account, recipient_account, attacker_account, extra_accounts));
+    }
+
+    for (mint, user_account, victim_account, recipient_account, attacker_account, extra_accounts) in accounts {
+        let mut transfer_ix = hook::transfer_tokens_with_hook(
+            &PROGRAM_ID,
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &mint.pubkey(),
+            &user.pubkey(),
+            &recipient.pubkey(),
+            &hook_program_id,
+            &extra_accounts,
+            10,
+        );
+        // 构建的指令不会按声明为额外账户请求签名
+        assert!(transfer_ix.accounts.iter().filter(|meta| meta.is_signer).all(|meta| meta.pubkey == user.pubkey()));
+        if victim_account == user_account {
+            // 额外账户重复转出账户和转出权限
+            let result = send_instruction(&mut context, transfer_ix, &[&user]).await;
+            assert_custom_error(result, TokenError::TransferHookMismatch);
+        } else {
+            // 受害者（手续费付款人）签了交易，钩子也拿不到它的签名权限
+            let victim_meta = transfer_ix.accounts.iter_mut().find(|meta| meta.pubkey == payer).unwrap();
+            victim_meta.is_signer = true;
+            let result = send_instruction(&mut context, transfer_ix, &[&user]).await;
+            assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+            );
+            assert_eq!(get_token_balance(&mut context, victim_account).await, 500);
+        }
+        assert_eq!(get_token_balance(&mut context, user_account).await, 1_000);
+        assert_eq!(get_token_balance(&mut context, attacker_account).await, 0);
+    }
+}
+
+#[tokio::test]
+async fn test_transfer_memo() {
+    let mut program_test = program_test();
+    program_test.add_program("spl_memo", spl_memo::id(), processor!(spl_memo::processor::process_instruction));
+    let (mut context, mint_authority, user, recipient) = start_program_test(program_test).await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+
+    let user_account = setup_funded_mint(&mut context, &mint, &mint_authority, &user.pubkey(), 1_000).await;
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+    send_instruction(
+        &mut context,
+        instruction::create_token_account(&PROGRAM_ID, &payer, &recipient.pubkey(), &mint.pubkey()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // 只有代币账户所有者可以设置备注要求
+    let forged_ix = memo::set_memo_required(&PROGRAM_ID, &user.pubkey(), &recipient_account, true);
+    let result = send_instruction(&mut context, forged_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::OwnerMismatch);
+    let require_ix = memo::set_memo_required(&PROGRAM_ID, &recipient.pubkey(), &recipient_account, true);
+    send_instruction(&mut context, require_ix, &[&recipient]).await.unwrap();
+    let (requirement_address, _) = memo::find_memo_requirement_address(&PROGRAM_ID, &recipient_account);
+    let account = context.banks_client.get_account(requirement_address).await.unwrap().unwrap();
+    let requirement = memo::MemoRequirement::unpack_account(&account.data).unwrap();
+    assert!(requirement.require_incoming_memo);
+    assert_eq!(requirement.token_account, recipient_account);
+
+    // 转入账户要求备注时拒绝没有备注的转账
+    let plain_ix = || {
+        instruction::transfer_tokens(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 100)
+    };
+    let result = send_instruction(&mut context, plain_ix(), &[&user]).await;
+    assert_custom_error(result, TokenError::MemoRequired);
+
+    // 省略备注要求 PDA 不能绕过要求
+    let mut without_requirement = plain_ix();
+    without_requirement.accounts.remove(5);
+    let result = send_instruction(&mut context, without_requirement, &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidProgramAddress);
+
+    // 以其他账户的备注要求 PDA 代替时拒绝
+    let mut wrong_requirement = plain_ix();
+    wrong_requirement.accounts[5].pubkey = memo::find_memo_requirement_address(&PROGRAM_ID, &user_account).0;
+    let result = send_instruction(&mut context, wrong_requirement, &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidProgramAddress);
+
+    // 空备注无效
+    let empty_ix = memo::transfer_with_memo(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 100, "");
+    let result = send_instruction(&mut context, empty_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::InvalidInstruction);
+
+    // 带备注的转账成功
+    let memo_ix =
+        memo::transfer_with_memo(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 100, "deposit:42");
+    send_instruction(&mut context, memo_ix, &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, user_account).await, 900);
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 100);
+
+    // 缺少 Memo 程序时无法记录备注
+    let mut without_memo_program =
+        memo::transfer_with_memo(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 100, "deposit:43");
+    without_memo_program.accounts.remove(5);
+    let result = send_instruction(&mut context, without_memo_program, &[&user]).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+
+    // 取消备注要求后普通转账恢复
+    let release_ix = memo::set_memo_required(&PROGRAM_ID, &recipient.pubkey(), &recipient_account, false);
+    send_instruction(&mut context, release_ix, &[&recipient]).await.unwrap();
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    send_instruction(&mut context, plain_ix(), &[&user]).await.unwrap();
+    assert_eq!(get_token_balance(&mut context, recipient_account).await, 200);
+}
+
+#[tokio::test]
+async fn test_nft_collection() {
+    let (mut context, creator, user, recipient) = setup_program_test().await;
+    let collection_mint = Keypair::new();
+    let metadata = |name: &str, collection| nft::NftMetadata {
+        name: name.to_string(),
+        symbol: "DEMO".to_string(),
+        uri: format!("https://example.com/{}.json", name),
+        collection,
+    };
+
+    // 集合本身也是一枚 NFT，创建者持有并作为集合权限
+    let collection_ix = nft::mint_nft(
+        &PROGRAM_ID,
+        &spl_token::id(),
+        &collection_mint.pubkey(),
+        &creator.pubkey(),
+        &creator.pubkey(),
+        metadata("collection", None),
+    );
+    send_instruction(&mut context, collection_ix, &[&collection_mint, &creator]).await.unwrap();
+
+    let item_mint = Keypair::new();
+    let item_ix = nft::mint_nft(
+        &PROGRAM_ID,
+        &spl_token::id(),
+        &item_mint.pubkey(),
+        &creator.pubkey(),
+        &recipient.pubkey(),
+        metadata("item-1", Some(collection_mint.pubkey())),
+    );
+    send_instruction(&mut context, item_ix, &[&item_mint, &creator]).await.unwrap();
+
+    // 供应量固定为 1，铸造权限已放弃
+    let account = context.banks_client.get_account(item_mint.pubkey()).await.unwrap().unwrap();
+    let mint_state = Mint::unpack(&account.data).unwrap();
+    assert_eq!(mint_state.supply, 1);
+    assert_eq!(mint_state.decimals, 0);
+    assert_eq!(mint_state.mint_authority, COption::None);
+    let item_account = get_associated_token_address(&recipient.pubkey(), &item_mint.pubkey());
+    assert_eq!(get_token_balance(&mut context, item_account).await, 1);
+    let mint_more = instruction::mint_tokens(
+        &PROGRAM_ID,
+        &item_mint.pubkey(),
+        &item_account,
+        &creator.pubkey(),
+        &creator.pubkey(),
+        1,
+    );
+    assert!(send_instruction(&mut context, mint_more, &[&creator]).await.is_err());
+
+    let (metadata_address, _) = nft::find_metadata_address(&PROGRAM_ID, &item_mint.pubkey());
+    let account = context.banks_client.get_account(metadata_address).await.unwrap().unwrap();
+    let stored = nft::Metadata::unpack_account(&account.data).unwrap();
+    assert_eq!(stored.data, metadata("item-1", Some(collection_mint.pubkey())));
+    assert_eq!(stored.update_authority, creator.pubkey());
+    assert!(!stored.collection_verified);
+
+    // 元数据超长时拒绝
+    let long_mint = Keypair::new();
+    let long_ix = nft::mint_nft(
+        &PROGRAM_ID,
+        &spl_token::id(),
+        &long_mint.pubkey(),
+        &creator.pubkey(),
+        &recipient.pubkey(),
+        metadata(&"x".repeat(nft::MAX_NAME_LEN + 1), None),
+    );
+    let result = send_instruction(&mut context, long_ix, &[&long_mint, &creator]).await;
+    assert_custom_error(result, TokenError::MetadataTooLong);
+
+    // 只有集合权限可以验证
+    let forged_ix = nft::verify_collection(&PROGRAM_ID, &user.pubkey(), &item_mint.pubkey(), &collection_mint.pubkey());
+    let result = send_instruction(&mut context, forged_ix, &[&user]).await;
+    assert_custom_error(result, TokenError::InsufficientPrivileges);
+
+    let verify_ix = nft::verify_collection(&PROGRAM_ID, &creator.pubkey(), &item_mint.pubkey(), &collection_mint.pubkey());
+    send_instruction(&mut context, verify_ix.clone(), &[&creator]).await.unwrap();
+    let account = context.banks_client.get_account(metadata_address).await.unwrap().unwrap();
+    assert!(nft::Metadata::unpack_account(&account.data).unwrap().collection_verified);
+    let (collection_address, _) = nft::find_collection_address(&PROGRAM_ID, &collection_mint.pubkey());
+    let account = context.banks_client.get_account(collection_address).await.unwrap().unwrap();
+    let collection = nft::Collection::unpack_account(&account.data).unwrap();
+    assert_eq!(collection.mint, collection_mint.pubkey());
+    assert_eq!(collection.size, 1);
+
+    // 重复验证或未声明该集合时拒绝
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let result = send_instruction(&mut context, verify_ix, &[&creator]).await;
+    assert_custom_error(result, TokenError::CollectionMismatch);
+    let self_ix = nft::verify_collection(
+        &PROGRAM_ID,
+        &creator.pubkey(),
+        &collection_mint.pubkey(),
+        &collection_mint.pubkey(),
+    );
+    let result = send_instruction(&mut context, self_ix, &[&creator]).await;
+    assert_custom_error(result, TokenError::CollectionMismatch);
+}
+
+/// 各类型旧布局（没有头部）的长度互不相同，迁移时据此确认账户类型
+#[test]
+fn test_legacy_layout_lengths() {
+    let lengths = [
+        stake::StakePool::LEN,
+        stake::UserStake::LEN,
+        schedule::ScheduledTransfer::LEN,
+        stream::Stream::LEN,
+        permit::PermitNonce::LEN,
+        dividend::DividendPool::LEN,
+        dividend::DividendCheckpoint::LEN,
+        sale::Sale::LEN,
+        config::MintConfig::LEN,
+        config::LaunchWallet::LEN,
+        memo::MemoRequirement::LEN,
+        nft::Metadata::LEN,
+        nft::Collection::LEN,
+        ledger::LedgerMint::LEN,
+    ];
+    let mut legacy: Vec<_> = lengths.iter().map(|len| len - ACCOUNT_HEADER_LEN).collect();
+    legacy.sort_unstable();
+    legacy.dedup();
+    assert_eq!(legacy.len(), lengths.len());
+}
+
+#[tokio::test]
+async fn test_migrate_account() {
+    let (mut context, mint_authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+
+    let user_account = setup_funded_mint(&mut context, &mint, &mint_authority, &user.pubkey(), 1_000).await;
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+    send_instruction(
+        &mut context,
+        instruction::create_token_account(&PROGRAM_ID, &payer, &recipient.pubkey(), &mint.pubkey()),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // 引入头部之前创建的备注要求账户：没有头部，长度为旧布局长度
+    let (requirement_address, bump) = memo::find_memo_requirement_address(&PROGRAM_ID, &recipient_account);
+    let requirement = memo::MemoRequirement {
+        is_initialized: true,
+        token_account: recipient_account,
+        require_incoming_memo: true,
+        bump,
+    };
+    let legacy_data = requirement.try_to_vec().unwrap();
+    assert_eq!(legacy_data.len(), memo::MemoRequirement::LEN - ACCOUNT_HEADER_LEN);
+    let mut legacy_account = solana_sdk::account::AccountSharedData::new(
+        solana_sdk::rent::Rent::default().minimum_balance(legacy_data.len()),
+        legacy_data.len(),
+        &PROGRAM_ID,
+    );
+    solana_sdk::account::WritableAccount::data_as_mut_slice(&mut legacy_account).copy_from_slice(&legacy_data);
+    context.set_account(&requirement_address, &legacy_account);
+
+    // 旧布局在迁移前不能读取
+    let plain_ix = || {
+        instruction::transfer_tokens(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 100)
+    };
+    let result = send_instruction(&mut context, plain_ix(), &[&user]).await;
+    assert_custom_error(result, TokenError::AccountVersionMismatch);
+
+    // 声明的类型与旧布局长度不符时拒绝
+    let wrong_type_ix =
+        state::migrate_account(&PROGRAM_ID, &payer, &requirement_address, state::AccountType::StakePool);
+    let result = send_instruction(&mut context, wrong_type_ix, &[]).await;
+    assert_custom_error(result, TokenError::AccountTypeMismatch);
+
+    // 原地扩容并补足租金，数据保持不变
+    let migrate_ix =
+        state::migrate_account(&PROGRAM_ID, &payer, &requirement_address, state::AccountType::MemoRequirement);
+    send_instruction(&mut context, migrate_ix.clone(), &[]).await.unwrap();
+    let account = context.banks_client.get_account(requirement_address).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), memo::MemoRequirement::LEN);
+    assert_eq!(&account.data[..ACCOUNT_HEADER_LEN], &[state::AccountType::MemoRequirement as u8, 1]);
+    assert!(account.lamports >= solana_sdk::rent::Rent::default().minimum_balance(memo::MemoRequirement::LEN));
+    assert_eq!(memo::MemoRequirement::unpack_account(&account.data).unwrap(), requirement);
+
+    // 迁移后的账户照常生效
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let result = send_instruction(&mut context, plain_ix(), &[&user]).await;
+    assert_custom_error(result, TokenError::MemoRequired);
+
+    // 已是当前版本时不做修改
+    send_instruction(&mut context, migrate_ix, &[]).await.unwrap();
+    let migrated = context.banks_client.get_account(requirement_address).await.unwrap().unwrap();
+    assert_eq!(migrated, account);
+}
+
+/// 代币余额的存储方式：SPL Token 代币账户或本程序的账本代币账户
+#[derive(Clone, Copy, Debug)]
+enum Backend {
+    Spl,
+    Ledger,
+}
+
+impl Backend {
+    /// 创建带冻结权限的铸造账户和各钱包的代币账户，返回代币账户地址
+    async fn setup(
+        self,
+        context: &mut ProgramTestContext,
+        mint: &Keypair,
+        authority: &Keypair,
+        wallets: &[Pubkey],
+    ) -> Vec<Pubkey> {
+        let payer = context.payer.pubkey();
+        let mut instructions = vec![match self {
+            Backend::Spl => instruction::initialize_mint(
+                &PROGRAM_ID,
+                &mint.pubkey(),
+                &payer,
+                &authority.pubkey(),
+                Some(&authority.pubkey()),
+                9,
+            ),
+            Backend::Ledger => ledger::initialize_ledger_mint(
+                &PROGRAM_ID,
+                &mint.pubkey(),
+                &payer,
+                &authority.pubkey(),
+                Some(&authority.pubkey()),
+                9,
+            ),
+        }];
+        let mut accounts = Vec::new();
+        for wallet in wallets {
+            match self {
+                Backend::Spl => {
+                    instructions.push(instruction::create_token_account(&PROGRAM_ID, &payer, wallet, &mint.pubkey()));
+                    accounts.push(get_associated_token_address(wallet, &mint.pubkey()));
+                }
+                Backend::Ledger => {
+                    instructions.push(ledger::create_ledger_account(&PROGRAM_ID, &payer, wallet, &mint.pubkey()));
+                    accounts.push(ledger::find_ledger_account_address(&PROGRAM_ID, &mint.pubkey(), wallet).0);
+                }
+            }
+        }
+        send_instructions(context, &instructions, &[mint]).await.unwrap();
+        accounts
+    }
+
+    fn mint_to(self, mint: &Pubkey, account: &Pubkey, authority: &Pubkey, payer: &Pubkey, amount: u64) -> Instruction {
+        match self {
+            Backend::Spl => instruction::mint_tokens(&PROGRAM_ID, mint, account, authority, payer, amount),
+            Backend::Ledger => ledger::ledger_mint_to(&PROGRAM_ID, mint, account, authority, amount),
+        }
+    }
+
+    fn transfer(self, from: &Pubkey, to: &Pubkey, authority: &Pubkey, mint: &Pubkey, amount: u64) -> Instruction {
+        match self {
+            Backend::Spl => instruction::transfer_tokens(&PROGRAM_ID, from, to, authority, mint, amount),
+            Backend::Ledger => ledger::ledger_transfer(&PROGRAM_ID, from, to, authority, amount),
+        }
+    }
+
+    fn approve(self, account: &Pubkey, delegate: &Pubkey, owner: &Pubkey, amount: u64) -> Instruction {
+        match self {
+            Backend::Spl => {
+                spl_token::instruction::approve(&spl_token::id(), account, delegate, owner, &[], amount).unwrap()
+            }
+            Backend::Ledger => ledger::ledger_approve(&PROGRAM_ID, account, delegate, owner, amount),
+        }
+    }
+
+    fn burn(self, account: &Pubkey, mint: &Pubkey, authority: &Pubkey, amount: u64) -> Instruction {
+        match self {
+            Backend::Spl => instruction::burn_tokens(&PROGRAM_ID, account, mint, authority, amount),
+            Backend::Ledger => ledger::ledger_burn(&PROGRAM_ID, account, mint, authority, amount),
+        }
+    }
+
+    fn freeze(self, account: &Pubkey, mint: &Pubkey, authority: &Pubkey) -> Instruction {
+        match self {
+            Backend::Spl => instruction::freeze_account(&PROGRAM_ID, account, mint, authority),
+            Backend::Ledger => ledger::ledger_set_frozen(&PROGRAM_ID, account, mint, authority, true),
+        }
+    }
+
+    async fn balance(self, context: &mut ProgramTestContext, account: Pubkey) -> u64 {
+        match self {
+            Backend::Spl => get_token_balance(context, account).await,
+            Backend::Ledger => {
+                let account = context.banks_client.get_account(account).await.unwrap().unwrap();
+                crate::TokenAccountState::unpack(&account.data).unwrap().amount
+            }
+        }
+    }
+
+    async fn supply(self, context: &mut ProgramTestContext, mint: Pubkey) -> u64 {
+        let account = context.banks_client.get_account(mint).await.unwrap().unwrap();
+        match self {
+            Backend::Spl => Mint::unpack(&account.data).unwrap().supply,
+            Backend::Ledger => ledger::LedgerMint::unpack_account(&account.data).unwrap().supply,
+        }
+    }
+}
+
+/// 在两种存储方式上执行同一组操作，返回每步是否成功以及最终余额和供应量
+async fn run_ledger_scenario(backend: Backend) -> (Vec<bool>, Vec<u64>, u64) {
+    let (mut context, authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let delegate = Keypair::new();
+    let accounts = backend
+        .setup(&mut context, &mint, &authority, &[user.pubkey(), recipient.pubkey()])
+        .await;
+    let (user_account, recipient_account) = (accounts[0], accounts[1]);
+    let mint_key = mint.pubkey();
+
+    let steps: Vec<(Instruction, Vec<&Keypair>)> = vec![
+        (backend.mint_to(&mint_key, &user_account, &authority.pubkey(), &payer, 1_000), vec![&authority]),
+        (backend.transfer(&user_account, &recipient_account, &user.pubkey(), &mint_key, 300), vec![&user]),
+        // 余额不足
+        (backend.transfer(&user_account, &recipient_account, &user.pubkey(), &mint_key, 800), vec![&user]),
+        (backend.approve(&user_account, &delegate.pubkey(), &user.pubkey(), 100), vec![&user]),
+        (backend.transfer(&user_account, &recipient_account, &delegate.pubkey(), &mint_key, 60), vec![&delegate]),
+        // 超出剩余授权额度
+        (backend.transfer(&user_account, &recipient_account, &delegate.pubkey(), &mint_key, 50), vec![&delegate]),
+        // 非委托人不能动用余额
+        (backend.transfer(&user_account, &recipient_account, &recipient.pubkey(), &mint_key, 10), vec![&recipient]),
+        (backend.burn(&recipient_account, &mint_key, &recipient.pubkey(), 40), vec![&recipient]),
+        (backend.freeze(&recipient_account, &mint_key, &authority.pubkey()), vec![&authority]),
+        // 冻结账户不能转出，也不能转入
+        (backend.transfer(&recipient_account, &user_account, &recipient.pubkey(), &mint_key, 10), vec![&recipient]),
+        (backend.transfer(&user_account, &recipient_account, &user.pubkey(), &mint_key, 10), vec![&user]),
+    ];
+    let mut results = Vec::new();
+    for (ix, signers) in steps {
+        context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        results.push(send_instruction(&mut context, ix, &signers).await.is_ok());
+    }
+
+    let balances = vec![
+        backend.balance(&mut context, user_account).await,
+        backend.balance(&mut context, recipient_account).await,
+    ];
+    let supply = backend.supply(&mut context, mint_key).await;
+    (results, balances, supply)
+}
+
+/// 原生账本模式与 SPL 路径的行为一致
+#[tokio::test]
+async fn test_ledger_parity() {
+    let spl = run_ledger_scenario(Backend::Spl).await;
+    let native = run_ledger_scenario(Backend::Ledger).await;
+    assert_eq!(
+        spl.0,
+        vec![true, true, false, true, true, false, false, true, true, false, false]
+    );
+    assert_eq!(spl.1, vec![640, 320]);
+    assert_eq!(spl.2, 960);
+    assert_eq!(native, spl);
+}
+
+/// 账本代币账户的定长布局和账本专有的错误
+#[tokio::test]
+async fn test_ledger_accounts() {
+    let state = crate::TokenAccountState {
+        is_initialized: true,
+        mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount: 42,
+        delegate: Some(Pubkey::new_unique()),
+        delegated_amount: 7,
+        is_frozen: true,
+        ..Default::default()
+    };
+    let mut data = vec![0; crate::TokenAccountState::LEN];
+    crate::TokenAccountState::pack(state, &mut data).unwrap();
+    assert_eq!(&data[..ACCOUNT_HEADER_LEN], &[state::AccountType::LedgerAccount as u8, 1]);
+    let unpacked = crate::TokenAccountState::unpack(&data).unwrap();
+    assert_eq!(unpacked.try_to_vec().unwrap(), state.try_to_vec().unwrap());
+    data[0] = state::AccountType::LedgerMint as u8;
+    assert_eq!(
+        crate::TokenAccountState::unpack(&data).unwrap_err(),
+        TokenError::AccountTypeMismatch.into()
+    );
+
+    let (mut context, authority, user, recipient) = setup_program_test().await;
+    let payer = context.payer.pubkey();
+    let mint = Keypair::new();
+    let accounts = Backend::Ledger
+        .setup(&mut context, &mint, &authority, &[user.pubkey()])
+        .await;
+
+    // 同一钱包不能重复创建账本代币账户
+    let result = send_instruction(
+        &mut context,
+        ledger::create_ledger_account(&PROGRAM_ID, &payer, &user.pubkey(), &mint.pubkey()),
+        &[],
+    )
+    .await;
+    assert_custom_error(result, TokenError::AccountAlreadyInitialized);
+
+    // 只有铸造权限可以铸造
+    let result = send_instruction(
+        &mut context,
+        ledger::ledger_mint_to(&PROGRAM_ID, &mint.pubkey(), &accounts[0], &recipient.pubkey(), 1),
+        &[&recipient],
+    )
+    .await;
+    assert_custom_error(result, TokenError::InsufficientPrivileges);
+
+    // 冻结后铸造被拒绝，解冻后恢复
+    send_instruction(
+        &mut context,
+        ledger::ledger_set_frozen(&PROGRAM_ID, &accounts[0], &mint.pubkey(), &authority.pubkey(), true),
+        &[&authority],
+    )
+    .await
+    .unwrap();
+    let mint_ix = ledger::ledger_mint_to(&PROGRAM_ID, &mint.pubkey(), &accounts[0], &authority.pubkey(), 5);
+    let result = send_instruction(&mut context, mint_ix.clone(), &[&authority]).await;
+    assert_custom_error(result, TokenError::AccountFrozen);
+    send_instruction(
+        &mut context,
+        ledger::ledger_set_frozen(&PROGRAM_ID, &accounts[0], &mint.pubkey(), &authority.pubkey(), false),
+        &[&authority],
+    )
+    .await
+    .unwrap();
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    send_instruction(&mut context, mint_ix, &[&authority]).await.unwrap();
+    assert_eq!(Backend::Ledger.balance(&mut context, accounts[0]).await, 5);
+}
+
+/// 计算单元允许超出基线的比例（百分比）
+const COMPUTE_UNIT_TOLERANCE_PERCENT: u64 = 10;
+
+/// 计算单元允许超出基线的最小余量，基线很小时避免任何波动都判定为退化
+const COMPUTE_UNIT_MIN_SLACK: u64 = 100;
+
+/// 设置后 `test_compute_units` 用本次结果覆盖基线文件
+const UPDATE_COMPUTE_UNITS_ENV: &str = "UPDATE_COMPUTE_UNITS";
+
+/// 是否运行编译后的程序（`cargo test-sbf`）
+fn is_sbf_mode() -> bool {
+    std::env::var("SBF_OUT_DIR").is_ok() || std::env::var("BPF_OUT_DIR").is_ok()
+}
+
+/// 当前运行方式的计算单元基线文件
+///
+/// `cargo test-sbf` 运行编译后的程序，`cargo test` 运行原生处理函数（只计入系统调用和 CPI 的消耗），
+/// 两者的结果不可比，分别保存为 `sbf.txt` 和 `native.txt`。
+fn compute_unit_baseline_path() -> std::path::PathBuf {
+    let file = if is_sbf_mode() { "sbf.txt" } else { "native.txt" };
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("compute_units")
+        .join(file)
+}
+
+/// 读取基线，每行为 `<指令> <计算单元>`，`#` 开头的行为注释
+fn load_compute_unit_baseline() -> Option<std::collections::BTreeMap<String, u64>> {
+    let text = std::fs::read_to_string(compute_unit_baseline_path()).ok()?;
+    let baseline = text
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, units) = line.split_once(' ').expect("基线格式为 `<指令> <计算单元>`");
+            (name.to_string(), units.trim().parse().expect("计算单元必须是整数"))
+        })
+        .collect();
+    Some(baseline)
+}
+
+/// 相对基线允许的最大计算单元
+fn compute_unit_limit(baseline: u64) -> u64 {
+    baseline + (baseline * COMPUTE_UNIT_TOLERANCE_PERCENT / 100).max(COMPUTE_UNIT_MIN_SLACK)
+}
+
+/// 指令变体的名称
+fn instruction_name(instruction: &TokenInstruction) -> String {
+    let debug = format!("{:?}", instruction);
+    debug.split([' ', '(', '{']).next().unwrap().to_string()
+}
+
+/// `TokenInstruction` 的全部变体名称：依次尝试每个 Borsh 标签，字段全部取零值
+fn all_instruction_names() -> Vec<String> {
+    let mut names = Vec::new();
+    for tag in 0..=u8::MAX {
+        let mut data = vec![0u8; 1024];
+        data[0] = tag;
+        match TokenInstruction::deserialize(&mut &data[..]) {
+            Ok(instruction) => names.push(instruction_name(&instruction)),
+            Err(_) => break,
+        }
+    }
+    names
+}
+
+/// 固定种子的密钥对，使 PDA 的 bump 搜索次数在每次运行中保持一致
+fn fixed_keypair(seed: u8) -> Keypair {
+    solana_sdk::signer::keypair::keypair_from_seed(&[seed; 32]).unwrap()
+}
+
+/// 把时钟设为固定时间，时间相关的 PDA 和判断在每次运行中保持一致
+async fn set_unix_timestamp(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+/// 发送交易并按其中本程序指令的名称记录计算单元
+async fn profile_instructions(
+    context: &mut ProgramTestContext,
+    units: &mut std::collections::BTreeMap<String, u64>,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) {
+    let mut names = instructions
+        .iter()
+        .filter(|ix| ix.program_id == PROGRAM_ID)
+        .map(|ix| instruction_name(&TokenInstruction::unpack(&ix.data).unwrap()));
+    let name = names.next().expect("交易中没有本程序的指令");
+    assert!(names.next().is_none(), "每笔交易只剖析一条本程序指令");
+    for ix in instructions.iter().filter(|ix| ix.program_id == PROGRAM_ID) {
+        check_idl_accounts(ix);
+    }
+    let consumed = send_with_compute_units(context, instructions, signers).await;
+    assert!(units.insert(name.clone(), consumed).is_none(), "{} 重复剖析", name);
+}
+
+/// 逐条执行每个指令变体并记录消耗的计算单元
+async fn profile_all_instructions() -> std::collections::BTreeMap<String, u64> {
+    const T0: i64 = 1_700_000_000;
+
+    let mut program_test = program_test();
+    program_test.add_program("spl_memo", spl_memo::id(), processor!(spl_memo::processor::process_instruction));
+    let mut context = program_test.start_with_context().await;
+    let mut units = std::collections::BTreeMap::new();
+
+    let payer = fixed_keypair(1);
+    let authority = fixed_keypair(2);
+    let user = fixed_keypair(3);
+    let recipient = fixed_keypair(4);
+    let delegate = fixed_keypair(5);
+    let funding: Vec<_> = [&payer, &authority, &user, &recipient]
+        .iter()
+        .map(|keypair| {
+            system_instruction::transfer(&context.payer.pubkey(), &keypair.pubkey(), 100 * LAMPORTS_PER_TOKEN)
+        })
+        .collect();
+    send_instructions(&mut context, &funding, &[]).await.unwrap();
+    set_unix_timestamp(&mut context, T0).await;
+
+    // 基础指令
+    let mint = fixed_keypair(10);
+    let user_account = get_associated_token_address(&user.pubkey(), &mint.pubkey());
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint.pubkey());
+    let payer_account = get_associated_token_address(&payer.pubkey(), &mint.pubkey());
+    let delegate_account = get_associated_token_address(&delegate.pubkey(), &mint.pubkey());
+    profile_instructions(
+        &mut context,
+        &mut units,
+        &[instruction::initialize_mint(
+            &PROGRAM_ID,
+            &mint.pubkey(),
+            &payer.pubkey(),
+            &authority.pubkey(),
+            Some(&authority.pubkey()),
+            9,
+        )],
+        &[&mint, &payer],
+    )
+    .await;
+    profile_instructions(
+        &mut context,
+        &mut units,
+        &[instruction::create_token_account(&PROGRAM_ID, &payer.pubkey(), &user.pubkey(), &mint.pubkey())],
+        &[&payer],
+    )
+    .await;
+    send_instructions(
+        &mut context,
+        &[
+            instruction::create_token_account(&PROGRAM_ID, &payer.pubkey(), &recipient.pubkey(), &mint.pubkey()),
+            instruction::create_token_account(&PROGRAM_ID, &payer.pubkey(), &payer.pubkey(), &mint.pubkey()),
+            instruction::create_token_account(&PROGRAM_ID, &payer.pubkey(), &delegate.pubkey(), &mint.pubkey()),
+            instruction::mint_tokens(&PROGRAM_ID, &mint.pubkey(), &payer_account, &authority.pubkey(), &payer.pubkey(), 1_000),
+        ],
+        &[&payer, &authority],
+    )
+    .await
+    .unwrap();
+    let mint_ix =
+        instruction::mint_tokens(&PROGRAM_ID, &mint.pubkey(), &user_account, &authority.pubkey(), &payer.pubkey(), 10_000);
+    profile_instructions(&mut context, &mut units, &[mint_ix], &[&authority]).await;
+    let transfer_ix = instruction::transfer_tokens(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 100);
+    profile_instructions(&mut context, &mut units, &[transfer_ix], &[&user]).await;
+    // 设置开盘防护和转账钩子之前剖析带备注的转账，只需附带固定账户
+    let memo_ix =
+        memo::transfer_with_memo(&PROGRAM_ID, &user_account, &recipient_account, &user.pubkey(), &mint.pubkey(), 100, "deposit:42");
+    profile_instructions(&mut context, &mut units, &[memo_ix], &[&user]).await;
+    let burn_ix = instruction::burn_tokens(&PROGRAM_ID, &user_account, &mint.pubkey(), &user.pubkey(), 10);
+    profile_instructions(&mut context, &mut units, &[burn_ix], &[&user]).await;
+    let set_authority_ix = instruction::set_authority(
+        &PROGRAM_ID,
+        &mint.pubkey(),
+        &authority.pubkey(),
+        AuthorityType::FreezeAccount,
+        Some(&authority.pubkey()),
+    );
+    profile_instructions(&mut context, &mut units, &[set_authority_ix], &[&authority]).await;
+    let freeze_ix = instruction::freeze_account(&PROGRAM_ID, &delegate_account, &mint.pubkey(), &authority.pubkey());
+    profile_instructions(&mut context, &mut units, &[freeze_ix], &[&authority]).await;
+
+    // 原生 SOL
+    let wrap_ix = native::wrap_sol(&PROGRAM_ID, &user.pubkey(), LAMPORTS_PER_TOKEN);
+    profile_instructions(&mut context, &mut units, &[wrap_ix], &[&user]).await;
+    let unwrap_ix = native::unwrap_sol(&PROGRAM_ID, &user.pubkey());
+    profile_instructions(&mut context, &mut units, &[unwrap_ix], &[&user]).await;
+
+    // 质押
+    let stake_mint = fixed_keypair(11);
+    let reward_mint = fixed_keypair(12);
+    let (pool, _) =
+        stake::find_stake_pool_address(&PROGRAM_ID, &payer.pubkey(), &stake_mint.pubkey(), &reward_mint.pubkey());
+    let user_stake_account = get_associated_token_address(&user.pubkey(), &stake_mint.pubkey());
+    let user_reward_account = get_associated_token_address(&user.pubkey(), &reward_mint.pubkey());
+    let mut instructions = create_mint_instructions(&payer.pubkey(), &stake_mint.pubkey(), &authority.pubkey(), 9);
+    instructions.extend(create_mint_instructions(&payer.pubkey(), &reward_mint.pubkey(), &pool, 9));
+    instructions.extend([
+        create_associated_token_account(&payer.pubkey(), &user.pubkey(), &stake_mint.pubkey(), &spl_token::id()),
+        create_associated_token_account(&payer.pubkey(), &user.pubkey(), &reward_mint.pubkey(), &spl_token::id()),
+        mint_to(&spl_token::id(), &stake_mint.pubkey(), &user_stake_account, &authority.pubkey(), &[], 1_000).unwrap(),
+    ]);
+    send_instructions(&mut context, &instructions, &[&payer, &stake_mint, &reward_mint, &authority])
+        .await
+        .unwrap();
+    let create_pool_ix = stake::create_stake_pool(
+        &PROGRAM_ID,
+        &payer.pubkey(),
+        &stake_mint.pubkey(),
+        &reward_mint.pubkey(),
+        1_000,
+        0,
+        stake::RewardSource::Mint,
+    );
+    profile_instructions(&mut context, &mut units, &[create_pool_ix], &[&payer]).await;
+    let stake_ix = stake::stake(&PROGRAM_ID, &user.pubkey(), &pool, &user_stake_account, 100);
+    profile_instructions(&mut context, &mut units, &[stake_ix], &[&user]).await;
+    let slot = context.banks_client.get_root_slot().await.unwrap();
+    context.warp_to_slot(slot + 50).unwrap();
+    set_unix_timestamp(&mut context, T0).await;
+    let claim_ix = stake::claim_rewards(&PROGRAM_ID, &user.pubkey(), &pool, &reward_mint.pubkey(), &user_reward_account);
+    profile_instructions(&mut context, &mut units, &[claim_ix], &[&user]).await;
+    let unstake_ix = stake::unstake(&PROGRAM_ID, &user.pubkey(), &pool, &user_stake_account, 100);
+    profile_instructions(&mut context, &mut units, &[unstake_ix], &[&user]).await;
+
+    // 定时转账
+    let schedule_ix = |amount, release_at| {
+        schedule::schedule_transfer(
+            &PROGRAM_ID,
+            &user.pubkey(),
+            &user_account,
+            &mint.pubkey(),
+            &recipient.pubkey(),
+            amount,
+            release_at,
+        )
+    };
+    let find_schedule = |release_at| {
+        schedule::find_schedule_address(&PROGRAM_ID, &user.pubkey(), &recipient.pubkey(), &mint.pubkey(), release_at).0
+    };
+    profile_instructions(&mut context, &mut units, &[schedule_ix(40, T0 + 100)], &[&user]).await;
+    send_instructions(&mut context, &[schedule_ix(25, T0 + 200)], &[&user]).await.unwrap();
+    let cancel_ix = schedule::cancel_scheduled(&PROGRAM_ID, &user.pubkey(), &find_schedule(T0 + 200), &user_account);
+    profile_instructions(&mut context, &mut units, &[cancel_ix], &[&user]).await;
+    set_unix_timestamp(&mut context, T0 + 100).await;
+    let execute_ix =
+        schedule::execute_scheduled(&PROGRAM_ID, &find_schedule(T0 + 100), &user.pubkey(), &recipient_account);
+    profile_instructions(&mut context, &mut units, &[execute_ix], &[]).await;
+
+    // 支付流
+    set_unix_timestamp(&mut context, T0).await;
+    let create_stream_ix = stream::create_stream(
+        &PROGRAM_ID,
+        &user.pubkey(),
+        &user_account,
+        &mint.pubkey(),
+        &recipient.pubkey(),
+        5,
+        T0 + 10,
+        T0 + 1_010,
+    );
+    profile_instructions(&mut context, &mut units, &[create_stream_ix], &[&user]).await;
+    let (stream_address, _) =
+        stream::find_stream_address(&PROGRAM_ID, &user.pubkey(), &recipient.pubkey(), &mint.pubkey(), T0 + 10);
+    set_unix_timestamp(&mut context, T0 + 210).await;
+    let withdraw_ix =
+        stream::withdraw_from_stream(&PROGRAM_ID, &recipient.pubkey(), &stream_address, &recipient_account);
+    profile_instructions(&mut context, &mut units, &[withdraw_ix], &[&recipient]).await;
+    let cancel_stream_ix = stream::cancel_stream(
+        &PROGRAM_ID,
+        &user.pubkey(),
+        &user.pubkey(),
+        &stream_address,
+        &user_account,
+        &recipient_account,
+    );
+    profile_instructions(&mut context, &mut units, &[cancel_stream_ix], &[&user]).await;
+
+    // 链下签名授权转账
+    set_unix_timestamp(&mut context, T0).await;
+    let approve_ix = permit::approve_permit_delegate(&PROGRAM_ID, &user.pubkey(), &user_account, 500).unwrap();
+    send_instructions(&mut context, &[approve_ix], &[&user]).await.unwrap();
+    let nonce = [7u8; 32];
+    let message = permit::permit_message(
+        &PROGRAM_ID,
+        &mint.pubkey(),
+        &user.pubkey(),
+        &recipient.pubkey(),
+        200,
+        &nonce,
+        T0 + 600,
+    );
+    let signature: [u8; 64] = user.sign_message(&message).as_ref().try_into().unwrap();
+    let permit_instructions = [
+        permit::ed25519_verify_instruction(&user.pubkey(), &signature, &message),
+        permit::transfer_with_authorization(
+            &PROGRAM_ID,
+            &payer.pubkey(),
+            &user_account,
+            &recipient_account,
+            &user.pubkey(),
+            &recipient.pubkey(),
+            200,
+            nonce,
+            T0 + 600,
+        ),
+    ];
+    profile_instructions(&mut context, &mut units, &permit_instructions, &[&payer]).await;
+
+    // 分红，冻结权限先转交给分红池
+    let (dividend_pool, _) = dividend::find_dividend_pool_address(&PROGRAM_ID, &mint.pubkey());
+    let freeze_to_pool = spl_token::instruction::set_authority(
+        &spl_token::id(),
+        &mint.pubkey(),
+        Some(&dividend_pool),
+        spl_token::instruction::AuthorityType::FreezeAccount,
+        &authority.pubkey(),
+        &[],
+    )
+    .unwrap();
+    send_instructions(&mut context, &[freeze_to_pool], &[&authority]).await.unwrap();
+    let deposit_ix = dividend::deposit_dividends(&PROGRAM_ID, &payer.pubkey(), &payer_account, &mint.pubkey(), 40);
+    profile_instructions(&mut context, &mut units, &[deposit_ix], &[&payer]).await;
+    let claim_dividends_ix = dividend::claim_dividends(&PROGRAM_ID, &user.pubkey(), &user_account, &mint.pubkey());
+    profile_instructions(&mut context, &mut units, &[claim_dividends_ix], &[&user]).await;
+
+    // 联合曲线发售
+    let sale_mint = fixed_keypair(13);
+    let user_sale_account = get_associated_token_address(&user.pubkey(), &sale_mint.pubkey());
+    let mut instructions = create_mint_instructions(&payer.pubkey(), &sale_mint.pubkey(), &authority.pubkey(), 9);
+    instructions.push(create_associated_token_account(
+        &payer.pubkey(),
+        &user.pubkey(),
+        &sale_mint.pubkey(),
+        &spl_token::id(),
+    ));
+    send_instructions(&mut context, &instructions, &[&payer, &sale_mint]).await.unwrap();
+    let params = sale::CurveParams { initial_price: 1_000_000, growth: 1_000_000 };
+    let create_sale_ix = sale::create_sale(
+        &PROGRAM_ID,
+        &authority.pubkey(),
+        &sale_mint.pubkey(),
+        sale::Curve::Linear,
+        params,
+        100 * LAMPORTS_PER_TOKEN,
+        T0,
+        T0 + 3_600,
+    );
+    profile_instructions(&mut context, &mut units, &[create_sale_ix], &[&authority]).await;
+    let buy_ix = sale::buy(&PROGRAM_ID, &user.pubkey(), &sale_mint.pubkey(), &user_sale_account, LAMPORTS_PER_TOKEN);
+    profile_instructions(&mut context, &mut units, &[buy_ix], &[&user]).await;
+    let sell_ix = sale::sell(&PROGRAM_ID, &user.pubkey(), &sale_mint.pubkey(), &user_sale_account, LAMPORTS_PER_TOKEN);
+    profile_instructions(&mut context, &mut units, &[sell_ix], &[&user]).await;
+    set_unix_timestamp(&mut context, T0 + 3_600).await;
+    let graduate_ix = sale::graduate_sale(&PROGRAM_ID, &sale_mint.pubkey());
+    profile_instructions(&mut context, &mut units, &[graduate_ix], &[]).await;
+
+    // 铸造账户配置
+    let guard = config::LaunchGuard {
+        trading_start_slot: 0,
+        launch_slots: 100,
+        cooldown_slots: 5,
+        max_amount_per_wallet: 300,
+    };
+    let guard_ix = config::set_launch_guard(&PROGRAM_ID, &authority.pubkey(), &mint.pubkey(), Some(guard));
+    profile_instructions(&mut context, &mut units, &[guard_ix], &[&authority]).await;
+    let hook_program = Pubkey::new_from_array([9; 32]);
+    let hook_ix = config::set_transfer_hook(&PROGRAM_ID, &authority.pubkey(), &mint.pubkey(), Some(hook_program));
+    profile_instructions(&mut context, &mut units, &[hook_ix], &[&authority]).await;
+
+    // 回收
+    let clawback_mint = fixed_keypair(14);
+    let user_clawback_account = get_associated_token_address(&user.pubkey(), &clawback_mint.pubkey());
+    let recipient_clawback_account = get_associated_token_address(&recipient.pubkey(), &clawback_mint.pubkey());
+    send_instructions(
+        &mut context,
+        &[
+            instruction::initialize_clawback_mint(
+                &PROGRAM_ID,
+                &spl_token::id(),
+                &clawback_mint.pubkey(),
+                &payer.pubkey(),
+                &authority.pubkey(),
+                &authority.pubkey(),
+                0,
+            ),
+            instruction::create_token_account(&PROGRAM_ID, &payer.pubkey(), &user.pubkey(), &clawback_mint.pubkey()),
+            instruction::create_token_account(
+                &PROGRAM_ID,
+                &payer.pubkey(),
+                &recipient.pubkey(),
+                &clawback_mint.pubkey(),
+            ),
+            instruction::mint_tokens(
+                &PROGRAM_ID,
+                &clawback_mint.pubkey(),
+                &user_clawback_account,
+                &authority.pubkey(),
+                &payer.pubkey(),
+                10,
+            ),
+            crate::clawback::approve_clawback_delegate(
+                &PROGRAM_ID,
+                &user_clawback_account,
+                &user.pubkey(),
+                &clawback_mint.pubkey(),
+            )
+            .unwrap(),
+        ],
+        &[&payer, &clawback_mint, &authority, &user],
+    )
+    .await
+    .unwrap();
+    let clawback_ix = crate::clawback::clawback(
+        &PROGRAM_ID,
+        &authority.pubkey(),
+        &user_clawback_account,
+        &recipient_clawback_account,
+        &clawback_mint.pubkey(),
+        &spl_token::id(),
+        4,
+    );
+    profile_instructions(&mut context, &mut units, &[clawback_ix], &[&authority]).await;
+
+    // 计息代币
+    let interest_mint = fixed_keypair(15);
+    let user_interest_account = get_associated_token_address(&user.pubkey(), &interest_mint.pubkey());
+    send_instructions(
+        &mut context,
+        &[
+            instruction::initialize_interest_bearing_mint(
+                &PROGRAM_ID,
+                &spl_token::id(),
+                &interest_mint.pubkey(),
+                &payer.pubkey(),
+                &authority.pubkey(),
+                None,
+                6,
+                500,
+            ),
+            instruction::create_token_account(&PROGRAM_ID, &payer.pubkey(), &user.pubkey(), &interest_mint.pubkey()),
+            instruction::mint_tokens(
+                &PROGRAM_ID,
+                &interest_mint.pubkey(),
+                &user_interest_account,
+                &authority.pubkey(),
+                &payer.pubkey(),
+                1_000_000,
+            ),
+        ],
+        &[&payer, &interest_mint, &authority],
+    )
+    .await
+    .unwrap();
+    let set_rate_ix = crate::interest::set_interest_rate(
+        &PROGRAM_ID,
+        &authority.pubkey(),
+        &interest_mint.pubkey(),
+        &spl_token::id(),
+        -200,
+    );
+    profile_instructions(&mut context, &mut units, &[set_rate_ix], &[&authority]).await;
+    let get_balance_ix = crate::interest::get_balance(&PROGRAM_ID, &user_interest_account, &interest_mint.pubkey());
+    profile_instructions(&mut context, &mut units, &[get_balance_ix], &[]).await;
+
+    // 转账备注
+    let require_ix = memo::set_memo_required(&PROGRAM_ID, &recipient.pubkey(), &recipient_account, true);
+    profile_instructions(&mut context, &mut units, &[require_ix], &[&recipient]).await;
+
+    // NFT
+    let collection_mint = fixed_keypair(16);
+    let item_mint = fixed_keypair(17);
+    let metadata = |name: &str, collection| nft::NftMetadata {
+        name: name.to_string(),
+        symbol: "DEMO".to_string(),
+        uri: format!("https://example.com/{}.json", name),
+        collection,
+    };
+    let collection_ix = nft::mint_nft(
+        &PROGRAM_ID,
+        &spl_token::id(),
+        &collection_mint.pubkey(),
+        &authority.pubkey(),
+        &authority.pubkey(),
+        metadata("collection", None),
+    );
+    send_instructions(&mut context, &[collection_ix], &[&collection_mint, &authority]).await.unwrap();
+    let item_ix = nft::mint_nft(
+        &PROGRAM_ID,
+        &spl_token::id(),
+        &item_mint.pubkey(),
+        &authority.pubkey(),
+        &recipient.pubkey(),
+        metadata("item-1", Some(collection_mint.pubkey())),
+    );
+    profile_instructions(&mut context, &mut units, &[item_ix], &[&item_mint, &authority]).await;
+    let verify_ix =
+        nft::verify_collection(&PROGRAM_ID, &authority.pubkey(), &item_mint.pubkey(), &collection_mint.pubkey());
+    profile_instructions(&mut context, &mut units, &[verify_ix], &[&authority]).await;
+
+    // 旧布局账户迁移
+    let (requirement_address, bump) = memo::find_memo_requirement_address(&PROGRAM_ID, &user_account);
+    let legacy_data = memo::MemoRequirement {
+        is_initialized: true,
+        token_account: user_account,
+        require_incoming_memo: false,
+        bump,
+    }
+    .try_to_vec()
+    .unwrap();
+    let mut legacy_account = solana_sdk::account::AccountSharedData::new(
+        solana_sdk::rent::Rent::default().minimum_balance(legacy_data.len()),
+        legacy_data.len(),
+        &PROGRAM_ID,
+    );
+    solana_sdk::account::WritableAccount::data_as_mut_slice(&mut legacy_account).copy_from_slice(&legacy_data);
+    context.set_account(&requirement_address, &legacy_account);
+    let migrate_ix = state::migrate_account(
+        &PROGRAM_ID,
+        &payer.pubkey(),
+        &requirement_address,
+        state::AccountType::MemoRequirement,
+    );
+    profile_instructions(&mut context, &mut units, &[migrate_ix], &[&payer]).await;
+
+    // 原生账本模式
+    let ledger_mint = fixed_keypair(18);
+    let ledger_account = |owner: &Pubkey| ledger::find_ledger_account_address(&PROGRAM_ID, &ledger_mint.pubkey(), owner).0;
+    let (user_ledger, recipient_ledger) = (ledger_account(&user.pubkey()), ledger_account(&recipient.pubkey()));
+    let initialize_ledger_ix = ledger::initialize_ledger_mint(
+        &PROGRAM_ID,
+        &ledger_mint.pubkey(),
+        &payer.pubkey(),
+        &authority.pubkey(),
+        Some(&authority.pubkey()),
+        9,
+    );
+    profile_instructions(&mut context, &mut units, &[initialize_ledger_ix], &[&ledger_mint, &payer]).await;
+    let create_ledger_ix = |owner: &Pubkey| {
+        ledger::create_ledger_account(&PROGRAM_ID, &payer.pubkey(), owner, &ledger_mint.pubkey())
+    };
+    profile_instructions(&mut context, &mut units, &[create_ledger_ix(&user.pubkey())], &[&payer]).await;
+    send_instructions(&mut context, &[create_ledger_ix(&recipient.pubkey())], &[&payer]).await.unwrap();
+    let ledger_mint_ix =
+        ledger::ledger_mint_to(&PROGRAM_ID, &ledger_mint.pubkey(), &user_ledger, &authority.pubkey(), 1_000);
+    profile_instructions(&mut context, &mut units, &[ledger_mint_ix], &[&authority]).await;
+    let ledger_transfer_ix = ledger::ledger_transfer(&PROGRAM_ID, &user_ledger, &recipient_ledger, &user.pubkey(), 100);
+    profile_instructions(&mut context, &mut units, &[ledger_transfer_ix], &[&user]).await;
+    let ledger_approve_ix = ledger::ledger_approve(&PROGRAM_ID, &user_ledger, &delegate.pubkey(), &user.pubkey(), 50);
+    profile_instructions(&mut context, &mut units, &[ledger_approve_ix], &[&user]).await;
+    let ledger_burn_ix = ledger::ledger_burn(&PROGRAM_ID, &user_ledger, &ledger_mint.pubkey(), &user.pubkey(), 10);
+    profile_instructions(&mut context, &mut units, &[ledger_burn_ix], &[&user]).await;
+    let ledger_freeze_ix =
+        ledger::ledger_set_frozen(&PROGRAM_ID, &recipient_ledger, &ledger_mint.pubkey(), &authority.pubkey(), true);
+    profile_instructions(&mut context, &mut units, &[ledger_freeze_ix], &[&authority]).await;
+
+    units
+}
+
+/// 剖析每个指令变体的计算单元，超过当前运行方式的基线 `COMPUTE_UNIT_TOLERANCE_PERCENT` 时失败
+///
+/// 设置 `UPDATE_COMPUTE_UNITS=1` 时改为用本次结果覆盖基线文件。编译后程序的基线尚未生成时
+/// `cargo test-sbf` 只输出各指令的消耗。
+#[tokio::test]
+async fn test_compute_units() {
+    let units = profile_all_instructions().await;
+    let names = all_instruction_names();
+    let missing: Vec<_> = names.iter().filter(|name| !units.contains_key(*name)).collect();
+    assert!(missing.is_empty(), "以下指令未剖析: {:?}", missing);
+    assert_eq!(units.len(), names.len());
+
+    let (mode, command) = if is_sbf_mode() {
+        ("编译后程序", "cargo test-sbf")
+    } else {
+        ("原生处理函数（只计入系统调用与 CPI）", "cargo test")
+    };
+    let path = compute_unit_baseline_path();
+    if std::env::var(UPDATE_COMPUTE_UNITS_ENV).is_ok() {
+        let mut text = format!(
+            "# {}各指令消耗的计算单元，由 test_compute_units 生成\n\
+             # 更新：{}=1 {} test_compute_units\n",
+            mode, UPDATE_COMPUTE_UNITS_ENV, command
+        );
+        for name in &names {
+            text.push_str(&format!("{} {}\n", name, units[name]));
+        }
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, text).unwrap();
+        println!("已更新计算单元基线: {}", path.display());
+        return;
+    }
+
+    let baseline = match load_compute_unit_baseline() {
+        Some(baseline) => baseline,
+        None if is_sbf_mode() => {
+            for name in &names {
+                println!("{:<28} {:>8} CU", name, units[name]);
+            }
+            println!(
+                "尚未生成编译后程序的基线 {}，设置 {}=1 运行 {} test_compute_units 生成后提交",
+                path.display(),
+                UPDATE_COMPUTE_UNITS_ENV,
+                command
+            );
+            return;
+        }
+        None => panic!("缺少计算单元基线 {}", path.display()),
+    };
+    let mut regressions = Vec::new();
+    for name in &names {
+        let consumed = units[name];
+        let expected = *baseline
+            .get(name)
+            .unwrap_or_else(|| panic!("基线缺少 {}，请设置 {}=1 运行 {} 重新生成", name, UPDATE_COMPUTE_UNITS_ENV, command));
+        println!("{:<28} {:>8} CU（基线 {}）", name, consumed, expected);
+        if consumed > compute_unit_limit(expected) {
+            regressions.push(format!("{}: {} CU，基线 {} CU", name, consumed, expected));
+        }
+    }
+    assert_eq!(baseline.len(), names.len(), "基线中有已删除的指令，请重新生成");
+    assert!(regressions.is_empty(), "计算单元超过基线: {:#?}", regressions);
+}
+
+/// 模糊测试目标 `instruction_unpack` 的回归用例：截断、补零、非法标记和超长字符串长度
+#[test]
+fn test_fuzz_instruction_data() {
+    fuzz::check_instruction_data(&[]);
+    for tag in 0..=u8::MAX {
+        for len in [1, 2, 9, 33, 64, 1024] {
+            let mut data = vec![0u8; len];
+            data[0] = tag;
+            fuzz::check_instruction_data(&data);
+        }
+    }
+
+    // Option 标记不是 0 或 1
+    let mut data = TokenInstruction::InitializeMint {
+        decimals: 9,
+        mint_authority: Pubkey::new_from_array([1; 32]),
+        freeze_authority: None,
+        non_transferable: false,
+        clawback_authority: None,
+        interest_rate: None,
+    }
+    .pack();
+    data[34] = 2;
+    assert!(TokenInstruction::unpack(&data).is_err());
+    fuzz::check_instruction_data(&data);
+
+    // 字符串长度前缀远超剩余数据，解析不能按前缀预分配内存
+    let mut data = TokenInstruction::MintNft {
+        metadata: nft::NftMetadata {
+            name: "n".to_string(),
+            symbol: "s".to_string(),
+            uri: "u".to_string(),
+            collection: None,
+        },
+    }
+    .pack();
+    data[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(TokenInstruction::unpack(&data).is_err());
+    fuzz::check_instruction_data(&data);
+
+    // 备注不是合法的 UTF-8
+    let mut data = TokenInstruction::TransferWithMemo { amount: 1, memo: "ab".to_string() }.pack();
+    let len = data.len();
+    data[len - 2..].copy_from_slice(&[0xff, 0xfe]);
+    assert!(TokenInstruction::unpack(&data).is_err());
+    fuzz::check_instruction_data(&data);
+
+    // 合法指令后的多余字节
+    let mut data = TokenInstruction::TransferTokens { amount: 1 }.pack();
+    data.push(0);
+    assert!(TokenInstruction::unpack(&data).is_err());
+    fuzz::check_instruction_data(&data);
+}
+
+/// 模糊测试目标 `ledger_invariants` 的回归用例：每一步都由进程内账本检查供应量、签名和冻结不变量
+#[test]
+fn test_fuzz_ledger_invariants() {
+    use fuzz::{Authority::*, LedgerOp::*};
+
+    // 钱包 0 为铸造权限，1 为冻结权限，2~4 为用户；代币账户 0~2 属于铸造账户 0，3 属于铸造账户 1
+    let mut ledger = fuzz::FuzzLedger::new();
+    let ops = [
+        MintTo { mint: 0, account: 0, authority: Expected, signed: true, amount: 1_000 },
+        // 未签名、非铸造权限、铸造账户不匹配
+        MintTo { mint: 0, account: 0, authority: Expected, signed: false, amount: 1 },
+        MintTo { mint: 0, account: 0, authority: Wallet(2), signed: true, amount: 1 },
+        MintTo { mint: 1, account: 0, authority: Expected, signed: true, amount: 1 },
+        // 供应量溢出
+        MintTo { mint: 0, account: 1, authority: Expected, signed: true, amount: u64::MAX },
+        Transfer { from: 0, to: 1, authority: Expected, signed: true, amount: 300 },
+        // 未签名、非所有者、跨铸造账户
+        Transfer { from: 0, to: 1, authority: Expected, signed: false, amount: 1 },
+        Transfer { from: 0, to: 1, authority: Wallet(3), signed: true, amount: 1 },
+        Transfer { from: 0, to: 3, authority: Expected, signed: true, amount: 1 },
+        // 委托人 4 只能动用授权额度，用完后委托被清除，不能再动用
+        Approve { account: 0, delegate: 4, authority: Expected, signed: true, amount: 100 },
+        Transfer { from: 0, to: 2, authority: Delegate, signed: true, amount: 101 },
+        Transfer { from: 0, to: 0, authority: Delegate, signed: true, amount: 40 },
+        Burn { mint: 0, account: 0, authority: Delegate, signed: true, amount: 60 },
+        Burn { mint: 0, account: 0, authority: Wallet(4), signed: true, amount: 1 },
+        // 冻结后转入、转出、铸造、授权和销毁都不能改变账户
+        SetFrozen { mint: 0, account: 1, authority: Wallet(0), signed: true, frozen: true },
+        SetFrozen { mint: 0, account: 1, authority: Expected, signed: false, frozen: true },
+        SetFrozen { mint: 0, account: 1, authority: Expected, signed: true, frozen: true },
+        Transfer { from: 0, to: 1, authority: Expected, signed: true, amount: 1 },
+        Transfer { from: 1, to: 0, authority: Expected, signed: true, amount: 1 },
+        MintTo { mint: 0, account: 1, authority: Expected, signed: true, amount: 1 },
+        Approve { account: 1, delegate: 2, authority: Expected, signed: true, amount: 1 },
+        Burn { mint: 0, account: 1, authority: Expected, signed: true, amount: 1 },
+        SetFrozen { mint: 0, account: 1, authority: Expected, signed: true, frozen: false },
+        Burn { mint: 1, account: 1, authority: Expected, signed: true, amount: 1 },
+        Burn { mint: 0, account: 1, authority: Expected, signed: true, amount: 100 },
+    ];
+    let results: Vec<bool> = ops.iter().map(|op| ledger.apply(op).is_ok()).collect();
+    assert_eq!(
+        results,
+        [
+            true, false, false, false, false, true, false, false, false, true, false, true, true, false, false,
+            false, true, false, false, false, false, false, true, false, true,
+        ]
+    );
+    assert_eq!(ledger.token_state(0).amount, 640);
+    assert_eq!(ledger.token_state(0).delegate, None);
+    assert_eq!(ledger.token_state(1).amount, 200);
+    assert!(!ledger.token_state(1).is_frozen);
+    assert_eq!(ledger.supply(0), 840);
+    assert_eq!(ledger.supply(1), 0);
+
+    // 取模后越界的序号和空序列
+    let mut ledger = fuzz::FuzzLedger::new();
+    ledger.run(&[]);
+    ledger.run(&[
+        MintTo { mint: 255, account: 255, authority: Wallet(255), signed: true, amount: u64::MAX },
+        Transfer { from: 255, to: 254, authority: Wallet(255), signed: true, amount: u64::MAX },
+        Burn { mint: 255, account: 255, authority: Wallet(255), signed: true, amount: u64::MAX },
+    ]);
+}
+
+/// 模糊测试目标 `token_invariants` 的回归用例：经 SPL Token 的铸造、配置/备注/分红/钩子转账、销毁、
+/// 冻结和变更权限，每一步都检查供应量、签名、冻结和分红不变量
+///
+/// 系统调用桩是进程全局的，与 `ProgramTest` 的桩冲突，因此在单独的子进程中运行。
+#[test]
+fn test_fuzz_token_invariants() {
+    use fuzz::{Authority::*, TokenOp::*};
+
+    if std::env::var_os("FUZZ_TOKEN_INVARIANTS").is_none() {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "tests::test_fuzz_token_invariants", "--test-threads=1"])
+            .env("FUZZ_TOKEN_INVARIANTS", "1")
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "子进程失败:\n{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        return;
+    }
+    fuzz::install_syscall_stubs();
+
+    // 钱包 0 为铸造权限，1 为冻结权限，2~4 为用户；代币账户 0~2 属于铸造账户 0（1、2 登记分红），
+    // 3、4 属于铸造账户 1（4 要求备注），5 为分红金库
+    let mut env = fuzz::FuzzToken::new();
+    let ops = [
+        MintTo { mint: 0, account: 0, authority: Expected, signed: true, amount: 1_000 },
+        // 登记分红的账户处于冻结状态，不能直接铸造
+        MintTo { mint: 0, account: 1, authority: Expected, signed: true, amount: 1 },
+        MintTo { mint: 1, account: 3, authority: Expected, signed: true, amount: 500 },
+        // 开盘前拒绝，开盘后转出方进入冷却
+        Transfer { from: 0, to: 1, authority: Expected, signed: true, amount: 100, memo: false },
+        Warp { slots: 5 },
+        Transfer { from: 0, to: 1, authority: Expected, signed: true, amount: 100, memo: false },
+        Transfer { from: 0, to: 1, authority: Expected, signed: true, amount: 100, memo: false },
+        Warp { slots: 3 },
+        // 钩子否决、未签名、非所有者
+        Transfer { from: 0, to: 2, authority: Expected, signed: true, amount: 10, memo: false },
+        Transfer { from: 0, to: 2, authority: Expected, signed: false, amount: 200, memo: false },
+        Transfer { from: 0, to: 2, authority: Wallet(3), signed: true, amount: 200, memo: false },
+        Transfer { from: 0, to: 2, authority: Expected, signed: true, amount: 200, memo: false },
+        // 绕过本程序不能转出已登记的账户
+        SplTransfer { from: 1, to: 0, authority: Expected, signed: true, amount: 50 },
+        Deposit { account: 0, authority: Expected, signed: true, amount: 70 },
+        Claim { account: 1, authority: Expected, signed: true },
+        Claim { account: 1, authority: Wallet(2), signed: true },
+        // 开盘窗口结束后附带备注转出全部余额
+        Warp { slots: 30 },
+        Transfer { from: 1, to: 0, authority: Expected, signed: true, amount: 123, memo: true },
+        // 转入账户要求备注
+        Transfer { from: 3, to: 4, authority: Expected, signed: true, amount: 100, memo: false },
+        Transfer { from: 3, to: 4, authority: Expected, signed: true, amount: 100, memo: true },
+        Freeze { mint: 1, account: 4, authority: Expected, signed: true },
+        SplTransfer { from: 4, to: 3, authority: Expected, signed: true, amount: 1 },
+        // 铸造账户 0 的冻结权限是分红池 PDA，不能在顶层签名，也不能被变更
+        Freeze { mint: 0, account: 0, authority: Expected, signed: true },
+        SetMintAuthority { mint: 0, freeze: true, new_authority: Some(2), authority: Expected, signed: true },
+        Burn { mint: 0, account: 0, authority: Expected, signed: true, amount: 53 },
+        Burn { mint: 0, account: 2, authority: Expected, signed: true, amount: 1 },
+        // 铸造权限移交后原权限不能再铸造
+        SetMintAuthority { mint: 1, freeze: false, new_authority: Some(4), authority: Expected, signed: true },
+        MintTo { mint: 1, account: 3, authority: Wallet(0), signed: true, amount: 1 },
+        MintTo { mint: 1, account: 3, authority: Expected, signed: true, amount: 1 },
+        // 委托人可以经本程序转入已登记的账户，但不能直接转入
+        SplApprove { account: 0, delegate: 4, authority: Expected, signed: true, amount: 50 },
+        SplTransfer { from: 0, to: 1, authority: Delegate, signed: true, amount: 10 },
+        Transfer { from: 0, to: 1, authority: Delegate, signed: true, amount: 40, memo: false },
+        // 所有者移交后原所有者不能再转出，撤销所有者被拒绝
+        SetAccountAuthority { account: 0, close: false, new_authority: Some(4), authority: Expected, signed: true },
+        Transfer { from: 0, to: 2, authority: Wallet(2), signed: true, amount: 1, memo: false },
+        SetAccountAuthority { account: 0, close: false, new_authority: None, authority: Expected, signed: true },
+        Deposit { account: 0, authority: Expected, signed: true, amount: 14 },
+        Claim { account: 2, authority: Expected, signed: true },
+    ];
+    let results: Vec<bool> = ops.iter().map(|op| env.apply(op).is_ok()).collect();
+    assert_eq!(
+        results,
+        [
+            true, false, true, false, true, true, false, true, false, false, false, true, false, true, true,
+            false, true, true, false, true, true, false, false, false, true, false, true, false, true, true,
+            false, true, true, false, false, true, true,
+        ]
+    );
+    assert_eq!(env.token_state(0).amount, 646);
+    assert_eq!(env.token_state(0).owner, Pubkey::new_from_array([5; 32]));
+    assert_eq!(env.token_state(1).amount, 40);
+    assert_eq!(env.token_state(2).amount, 258);
+    assert!(env.token_state(2).is_frozen());
+    assert_eq!(env.checkpoint(2).unwrap().balance, 258);
+    assert_eq!(env.token_state(4).amount, 100);
+    assert_eq!(env.supply(0), 947);
+    assert_eq!(env.supply(1), 501);
+
+    // 取模后越界的序号和空序列
+    let mut env = fuzz::FuzzToken::new();
+    env.run(&[]);
+    env.run(&[
+        Warp { slots: 255 },
+        MintTo { mint: 255, account: 255, authority: Wallet(255), signed: true, amount: u64::MAX },
+        Transfer { from: 255, to: 254, authority: Wallet(255), signed: true, amount: u64::MAX, memo: true },
+        Deposit { account: 255, authority: Wallet(255), signed: true, amount: u64::MAX },
+        Claim { account: 255, authority: Wallet(255), signed: true },
+        Burn { mint: 255, account: 255, authority: Wallet(255), signed: true, amount: u64::MAX },
+    ]);
+}
+
+/// 生成一次的 IDL，供各测试共用
+fn generated_idl() -> &'static idl::Idl {
+    static IDL: std::sync::OnceLock<idl::Idl> = std::sync::OnceLock::new();
+    IDL.get_or_init(idl::Idl::generate)
+}
+
+/// 指令构建函数给出的账户签名和可写标志必须与 IDL 中的账户注释一致
+fn check_idl_accounts(ix: &Instruction) {
+    let idl_instruction = &generated_idl().instructions[ix.data[0] as usize];
+    let required = idl_instruction.accounts.iter().filter(|account| !account.optional).count();
+    assert!(
+        ix.accounts.len() >= required,
+        "{} 只有 {} 个账户，IDL 要求 {} 个",
+        idl_instruction.name,
+        ix.accounts.len(),
+        required
+    );
+    for (meta, account) in ix.accounts.iter().zip(&idl_instruction.accounts) {
+        assert_eq!(
+            (meta.is_signer, meta.is_writable),
+            (account.signer, account.writable),
+            "{} 的账户 {} 与 IDL 的签名/可写标志不一致",
+            idl_instruction.name,
+            account.name
+        );
+    }
+}
+
+/// IDL 与指令、事件、错误和程序账户定义保持一致，仓库中的 IDL 文件与生成结果相同
+#[test]
+fn test_idl() {
+    let idl = generated_idl();
+    assert_eq!(idl.address, PROGRAM_ID);
+
+    let names = all_instruction_names();
+    assert_eq!(idl.instructions.len(), names.len());
+    for (i, (instruction, name)) in idl.instructions.iter().zip(&names).enumerate() {
+        let snake: String = name
+            .chars()
+            .enumerate()
+            .flat_map(|(j, c)| {
+                let separator = (j > 0 && c.is_ascii_uppercase()).then_some('_');
+                separator.into_iter().chain(std::iter::once(c.to_ascii_lowercase()))
+            })
+            .collect();
+        assert_eq!(instruction.name, snake);
+        assert_eq!(instruction.discriminator as usize, i);
+    }
+    let set_authority = &idl.instructions[TokenInstruction::SetAuthority { authority_type: AuthorityType::MintTokens, new_authority: None }.pack()[0] as usize];
+    assert_eq!(set_authority.name, "set_authority");
+    assert_eq!(set_authority.args.len(), 2);
+    assert_eq!(set_authority.args[0].ty, idl::IdlType::Defined("AuthorityType".to_string()));
+    assert_eq!(set_authority.args[1].ty, idl::IdlType::Option(Box::new(idl::IdlType::Primitive("pubkey"))));
+    let get_balance = idl.instructions.iter().find(|instruction| instruction.name == "get_balance").unwrap();
+    assert!(get_balance.account("config").unwrap().optional);
+    let stake = idl.instructions.iter().find(|instruction| instruction.name == "stake").unwrap();
+    let owner = stake.account("owner").unwrap();
+    assert!(owner.signer && owner.writable && !owner.optional);
+
+    // 事件：依次尝试每个 Borsh 标签
+    let mut event_names = Vec::new();
+    for tag in 0..=u8::MAX {
+        let mut data = vec![0u8; 1024];
+        data[0] = tag;
+        match TokenEvent::deserialize(&mut &data[..]) {
+            Ok(event) => event_names.push(format!("{:?}", event).split([' ', '(', '{']).next().unwrap().to_string()),
+            Err(_) => break,
+        }
+    }
+    let idl_event_names: Vec<_> = idl.events.iter().map(|event| event.name.clone()).collect();
+    assert_eq!(idl_event_names, event_names);
+    assert!(idl.events.iter().enumerate().all(|(i, event)| event.discriminator as usize == i));
+    for event in &idl.events {
+        assert!(idl.types.iter().any(|def| def.name == event.name), "缺少事件 {} 的类型定义", event.name);
+    }
+
+    let last = idl.errors.last().unwrap();
+    assert_eq!(last.code, TokenError::DividendFreezeAuthorityMismatch as u32);
+    assert_eq!(last.name, "DividendFreezeAuthorityMismatch");
+    assert_eq!(last.msg, TokenError::DividendFreezeAuthorityMismatch.to_string());
+    assert!(idl.errors.iter().enumerate().all(|(i, error)| error.code as usize == i));
+
+    // 每个实现 ProgramAccount 的结构体都出现在 IDL 中，并有对应的类型定义
+    let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    let impls: usize = std::fs::read_dir(&src)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap_or_default())
+        .map(|source| source.matches("\nimpl ProgramAccount for ").count())
+        .sum();
+    assert_eq!(idl.accounts.len(), impls);
+    let metadata = idl.accounts.iter().find(|account| account.name == "Metadata").unwrap();
+    assert_eq!(metadata.discriminator, [state::AccountType::NftMetadata as u8, nft::Metadata::VERSION]);
+    for account in &idl.accounts {
+        assert!(idl.types.iter().any(|def| def.name == account.name), "缺少账户 {} 的类型定义", account.name);
+    }
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("idl/solana_spl_token.json");
+    let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        checked_in == idl.to_json(),
+        "{} 与源码不一致，请运行 `cargo run --bin idl > idl/solana_spl_token.json` 更新",
+        path.display()
+    );
+}